@@ -0,0 +1,26 @@
+// Darkens the part of the power icon that is still on cooldown. The dark
+// part starts covering the whole icon and is swept clockwise from the top.
+#import bevy_ui::ui_vertex_output::UiVertexOutput
+
+@group(1) @binding(0) var<uniform> color: vec4<f32>;
+// Only `x` is used: how much of the cooldown has passed, from 0 to 1
+@group(1) @binding(1) var<uniform> progress: vec4<f32>;
+
+const TAU: f32 = 6.28318530718;
+
+@fragment
+fn fragment(in: UiVertexOutput) -> @location(0) vec4<f32> {
+    let from_center = in.uv - vec2<f32>(0.5, 0.5);
+
+    // 0 at the top of the icon, growing clockwise up to 1
+    var angle = atan2(from_center.x, -from_center.y) / TAU;
+    if angle < 0.0 {
+        angle += 1.0;
+    }
+
+    if angle < progress.x {
+        return vec4<f32>(0.0);
+    }
+
+    return color;
+}
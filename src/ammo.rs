@@ -8,6 +8,7 @@ use crate::util::get_ammo_sprite_based_on_weapon_type;
 use crate::util::EquippedTypeEnum;
 use crate::CleanupWhenPlayerDies;
 use crate::Player;
+use crate::Tilemap;
 
 #[cfg_attr(not(feature = "web"), derive(Reflect, Component, Debug, Clone))]
 #[cfg_attr(not(feature = "web"), reflect(Component))]
@@ -115,6 +116,7 @@ pub fn move_player_ammo(
     mut commands: Commands,
     mut ammos_query: Query<(Entity, &mut Transform, &Direction, &Ammo), With<Ammo>>,
     player_query: Query<Entity, With<Player>>,
+    tilemap: Option<Res<Tilemap>>,
     timer: Res<Time>,
 ) {
     let Ok(player_entity) = player_query.get_single() else {
@@ -144,6 +146,17 @@ pub fn move_player_ammo(
             return;
         }
 
+        // Obstacles and walls stop the ammo. Carried ammo doesn't move.
+        let is_moving = ammo_direction.0.truncate() != Vec2::ZERO;
+        let hits_solid = tilemap.as_ref().is_some_and(|tilemap| {
+            tilemap.is_solid(Vec2::new(new_translation_x, new_translation_y))
+        });
+
+        if is_moving && hits_solid {
+            commands.entity(entity).despawn_recursive();
+            continue;
+        }
+
         transform.translation.x = new_translation_x;
         transform.translation.y = new_translation_y;
     }
@@ -153,6 +166,7 @@ pub fn move_enemy_ammo(
     mut commands: Commands,
     mut ammos_query: Query<(Entity, &mut Transform, &Direction, &Ammo), With<Ammo>>,
     player_query: Query<Entity, With<Player>>,
+    tilemap: Option<Res<Tilemap>>,
     timer: Res<Time>,
 ) {
     let Ok(player_entity) = player_query.get_single() else {
@@ -182,6 +196,17 @@ pub fn move_enemy_ammo(
             return;
         }
 
+        // Obstacles and walls stop the ammo. Carried ammo doesn't move.
+        let is_moving = ammo_direction.0.truncate() != Vec2::ZERO;
+        let hits_solid = tilemap.as_ref().is_some_and(|tilemap| {
+            tilemap.is_solid(Vec2::new(new_translation_x, new_translation_y))
+        });
+
+        if is_moving && hits_solid {
+            commands.entity(entity).despawn_recursive();
+            continue;
+        }
+
         transform.translation.x = new_translation_x;
         transform.translation.y = new_translation_y;
     }
@@ -0,0 +1,480 @@
+use std::collections::VecDeque;
+
+use rand::{Rng, SeedableRng};
+use rand_chacha::ChaCha8Rng;
+
+use crate::{prelude::*, Boss, Enemy, ObstacleKind, PropKind, SplitFromElite, TileKind, Tilemap};
+
+/// Tiles of an arena while it is being generated.
+struct ArenaGrid {
+    columns: usize,
+    rows: usize,
+    tiles: Vec<TileKind>,
+    // Nothing is placed on the player start nor on the spawn zones
+    protected: Vec<bool>,
+    // Top left tile of each obstacle
+    obstacles: Vec<(usize, usize, ObstacleKind)>,
+    props: Vec<(usize, usize, PropKind)>,
+}
+
+impl ArenaGrid {
+    fn new(columns: usize, rows: usize) -> Self {
+        Self {
+            columns,
+            rows,
+            tiles: vec![TileKind::Walkable; columns * rows],
+            protected: vec![false; columns * rows],
+            obstacles: vec![],
+            props: vec![],
+        }
+    }
+
+    fn start(&self) -> (usize, usize) {
+        (self.columns / 2, self.rows / 2)
+    }
+
+    fn get(&self, column: usize, row: usize) -> TileKind {
+        self.tiles[row * self.columns + column]
+    }
+
+    /// Only places the tile if nothing protects that spot.
+    fn place(&mut self, column: usize, row: usize, kind: TileKind) {
+        let idx = row * self.columns + column;
+        if !self.protected[idx] {
+            self.tiles[idx] = kind;
+        }
+    }
+
+    fn set(&mut self, column: usize, row: usize, kind: TileKind) {
+        self.tiles[row * self.columns + column] = kind;
+    }
+
+    fn protect(&mut self, column: usize, row: usize) {
+        self.protected[row * self.columns + column] = true;
+    }
+
+    fn distance_to_start(&self, column: usize, row: usize) -> f32 {
+        let (start_column, start_row) = self.start();
+        Vec2::new(column as f32, row as f32)
+            .distance(Vec2::new(start_column as f32, start_row as f32))
+    }
+
+    /// Tiles that can be reached from the player start without crossing a
+    /// solid tile.
+    fn reachable_from_start(&self) -> Vec<bool> {
+        let mut reached = vec![false; self.columns * self.rows];
+        let mut queue = VecDeque::from([self.start()]);
+        let (start_column, start_row) = self.start();
+        reached[start_row * self.columns + start_column] = true;
+
+        while let Some((column, row)) = queue.pop_front() {
+            let neighbours = [
+                (column.wrapping_sub(1), row),
+                (column + 1, row),
+                (column, row.wrapping_sub(1)),
+                (column, row + 1),
+            ];
+
+            for (next_column, next_row) in neighbours {
+                if next_column >= self.columns || next_row >= self.rows {
+                    continue;
+                }
+
+                let idx = next_row * self.columns + next_column;
+                if reached[idx] || self.tiles[idx] == TileKind::Solid {
+                    continue;
+                }
+
+                reached[idx] = true;
+                queue.push_back((next_column, next_row));
+            }
+        }
+
+        reached
+    }
+}
+
+/// Builds the arena of a level from the run seed, so the same run always
+/// gets the same arenas. The player start is an open field, obstacles are
+/// grown in clusters, a few walls make chokepoints and the enemies spawn as
+/// far as possible from the player. Every walkable tile can be reached from
+/// the player start.
+pub(crate) fn generate_arena(run_seed: u64, game_level: u16) -> Tilemap {
+    let mut rng =
+        ChaCha8Rng::seed_from_u64(run_seed.wrapping_add(game_level as u64 * ARENA_LEVEL_SEED_STEP));
+    let mut grid = ArenaGrid::new(ARENA_COLUMNS, ARENA_ROWS);
+
+    // Open killing field around the player start
+    for row in 0..grid.rows {
+        for column in 0..grid.columns {
+            if grid.distance_to_start(column, row) <= ARENA_START_CLEARING_RADIUS {
+                grid.protect(column, row);
+            }
+        }
+    }
+
+    let spawn_zones = place_spawn_zones(&mut grid, &mut rng);
+
+    // More obstacles and hazards as the levels go by
+    for _ in 0..ARENA_OBSTACLE_CLUSTERS + game_level as u32 {
+        grow_obstacle_cluster(&mut grid, &mut rng);
+    }
+    for _ in 0..ARENA_CHOKEPOINT_WALLS {
+        place_chokepoint_wall(&mut grid, &mut rng);
+    }
+    for _ in 0..ARENA_HAZARD_POOLS + game_level as u32 / 3 {
+        place_hazard_pool(&mut grid, &mut rng);
+    }
+
+    // Makes sure the enemies can get to the player
+    let reached = grid.reachable_from_start();
+    for &(column, row) in spawn_zones.iter() {
+        if !reached[row * grid.columns + column] {
+            carve_corridor_to_start(&mut grid, column, row);
+        }
+    }
+
+    // After the corridors, so none is carved through an obstacle
+    for _ in 0..ARENA_OBSTACLES + game_level as u32 / 2 {
+        place_obstacle(&mut grid, &mut rng, &spawn_zones);
+    }
+
+    // Pockets that can't be reached are filled up
+    let reached = grid.reachable_from_start();
+    for (idx, reached) in reached.into_iter().enumerate() {
+        if !reached {
+            grid.tiles[idx] = TileKind::Solid;
+        }
+    }
+
+    // After the pockets are filled, so no prop ends up inside a wall
+    for _ in 0..ARENA_PROPS {
+        place_prop(&mut grid, &mut rng);
+    }
+
+    let mut tilemap = Tilemap::new(grid.columns, grid.rows, grid.tiles);
+    for (column, row, obstacle_kind) in grid.obstacles {
+        tilemap.add_obstacle(column, row, obstacle_kind);
+    }
+    for (column, row, prop_kind) in grid.props {
+        tilemap.add_prop(column, row, prop_kind);
+    }
+
+    tilemap
+}
+
+/// One spawn zone on each corner of the arena. Returns the center of each.
+fn place_spawn_zones(grid: &mut ArenaGrid, rng: &mut ChaCha8Rng) -> Vec<(usize, usize)> {
+    let mut centers = vec![];
+    let half_size = ARENA_SPAWN_ZONE_SIZE / 2;
+    let min_column = ARENA_MARGIN + half_size;
+    let max_column = grid.columns - 1 - ARENA_MARGIN - half_size;
+    let min_row = ARENA_MARGIN + half_size;
+    let max_row = grid.rows - 1 - ARENA_MARGIN - half_size;
+    let (start_column, start_row) = grid.start();
+
+    for zone in 0..ARENA_SPAWN_ZONES {
+        let columns = if zone % 2 == 0 {
+            min_column..start_column
+        } else {
+            start_column..max_column + 1
+        };
+        let rows = if zone % 4 < 2 {
+            min_row..start_row
+        } else {
+            start_row..max_row + 1
+        };
+
+        // The first try that is far enough, or the corner itself
+        let (column, row) = (0..10)
+            .map(|_| (rng.gen_range(columns.clone()), rng.gen_range(rows.clone())))
+            .find(|&(column, row)| {
+                grid.distance_to_start(column, row) >= ARENA_SPAWN_ZONE_MIN_DISTANCE
+            })
+            .unwrap_or((
+                if zone % 2 == 0 {
+                    min_column
+                } else {
+                    max_column
+                },
+                if zone % 4 < 2 { min_row } else { max_row },
+            ));
+
+        for zone_row in row - half_size..=row + half_size {
+            for zone_column in column - half_size..=column + half_size {
+                grid.set(zone_column, zone_row, TileKind::SpawnZone);
+                grid.protect(zone_column, zone_row);
+            }
+        }
+        centers.push((column, row));
+    }
+
+    centers
+}
+
+/// Random walk of solid tiles, e.g. rocks.
+fn grow_obstacle_cluster(grid: &mut ArenaGrid, rng: &mut ChaCha8Rng) {
+    let mut column = rng.gen_range(ARENA_MARGIN..grid.columns - ARENA_MARGIN);
+    let mut row = rng.gen_range(ARENA_MARGIN..grid.rows - ARENA_MARGIN);
+
+    for _ in 0..rng.gen_range(ARENA_CLUSTER_MIN_TILES..=ARENA_CLUSTER_MAX_TILES) {
+        grid.place(column, row, TileKind::Solid);
+
+        match rng.gen_range(0..4) {
+            0 => column = (column + 1).min(grid.columns - 1 - ARENA_MARGIN),
+            1 => column = column.saturating_sub(1).max(ARENA_MARGIN),
+            2 => row = (row + 1).min(grid.rows - 1 - ARENA_MARGIN),
+            _ => row = row.saturating_sub(1).max(ARENA_MARGIN),
+        }
+    }
+}
+
+/// A straight wall with a small gap on it.
+fn place_chokepoint_wall(grid: &mut ArenaGrid, rng: &mut ChaCha8Rng) {
+    let vertical = rng.gen_bool(0.5);
+    let length = if vertical { grid.rows } else { grid.columns } / 2;
+    let (start_column, start_row) = grid.start();
+
+    let (column, row) = if vertical {
+        // On one side of the player start
+        let offset = rng.gen_range(ARENA_START_CLEARING_RADIUS as usize + 2..start_column - 4);
+        let column = if rng.gen_bool(0.5) {
+            start_column - offset
+        } else {
+            start_column + offset
+        };
+        (
+            column,
+            rng.gen_range(ARENA_MARGIN..grid.rows - ARENA_MARGIN - length),
+        )
+    } else {
+        let offset =
+            rng.gen_range(ARENA_START_CLEARING_RADIUS as usize + 2..start_row - ARENA_MARGIN - 1);
+        let row = if rng.gen_bool(0.5) {
+            start_row - offset
+        } else {
+            start_row + offset
+        };
+        (
+            rng.gen_range(ARENA_MARGIN..grid.columns - ARENA_MARGIN - length),
+            row,
+        )
+    };
+
+    let gap = rng.gen_range(1..length - ARENA_CHOKEPOINT_GAP);
+
+    for step in 0..length {
+        if (gap..gap + ARENA_CHOKEPOINT_GAP).contains(&step) {
+            continue;
+        }
+
+        if vertical {
+            grid.place(column, row + step, TileKind::Solid);
+        } else {
+            grid.place(column + step, row, TileKind::Solid);
+        }
+    }
+}
+
+/// A small rectangle of hazard tiles on walkable ground.
+fn place_hazard_pool(grid: &mut ArenaGrid, rng: &mut ChaCha8Rng) {
+    let width = rng.gen_range(2..=4);
+    let height = rng.gen_range(2..=3);
+    let column = rng.gen_range(ARENA_MARGIN..grid.columns - ARENA_MARGIN - width);
+    let row = rng.gen_range(ARENA_MARGIN..grid.rows - ARENA_MARGIN - height);
+
+    for pool_row in row..row + height {
+        for pool_column in column..column + width {
+            if grid.get(pool_column, pool_row) == TileKind::Walkable {
+                grid.place(pool_column, pool_row, TileKind::Hazard);
+            }
+        }
+    }
+}
+
+/// A rock, crate or machine on free walkable ground. It is taken back if it
+/// cuts a spawn zone off the player start.
+fn place_obstacle(grid: &mut ArenaGrid, rng: &mut ChaCha8Rng, spawn_zones: &[(usize, usize)]) {
+    let obstacle_kind = match rng.gen_range(0..10) {
+        0..=4 => ObstacleKind::Rock,
+        5..=8 => ObstacleKind::Crate,
+        _ => ObstacleKind::DiggingMachine,
+    };
+    let (width, height) = obstacle_kind.footprint();
+    let column = rng.gen_range(ARENA_MARGIN..grid.columns - ARENA_MARGIN - width);
+    let row = rng.gen_range(ARENA_MARGIN..grid.rows - ARENA_MARGIN - height);
+
+    let footprint: Vec<(usize, usize)> = (row..row + height)
+        .flat_map(|obstacle_row| {
+            (column..column + width).map(move |obstacle_column| (obstacle_column, obstacle_row))
+        })
+        .collect();
+
+    let is_free = footprint.iter().all(|&(obstacle_column, obstacle_row)| {
+        grid.get(obstacle_column, obstacle_row) == TileKind::Walkable
+            && !grid.protected[obstacle_row * grid.columns + obstacle_column]
+    });
+    if !is_free {
+        return;
+    }
+
+    for &(obstacle_column, obstacle_row) in footprint.iter() {
+        grid.set(obstacle_column, obstacle_row, TileKind::Solid);
+    }
+
+    let reached = grid.reachable_from_start();
+    if spawn_zones
+        .iter()
+        .all(|&(zone_column, zone_row)| reached[zone_row * grid.columns + zone_column])
+    {
+        grid.obstacles.push((column, row, obstacle_kind));
+    } else {
+        for &(obstacle_column, obstacle_row) in footprint.iter() {
+            grid.set(obstacle_column, obstacle_row, TileKind::Walkable);
+        }
+    }
+}
+
+/// A breakable prop on free walkable ground. Props do not block anything,
+/// so they can go anywhere the player can walk.
+fn place_prop(grid: &mut ArenaGrid, rng: &mut ChaCha8Rng) {
+    let prop_kind = if rng.gen_bool(0.5) {
+        PropKind::EnergyPack
+    } else {
+        PropKind::Boulder
+    };
+    let column = rng.gen_range(ARENA_MARGIN..grid.columns - ARENA_MARGIN);
+    let row = rng.gen_range(ARENA_MARGIN..grid.rows - ARENA_MARGIN);
+
+    let is_free = grid.get(column, row) == TileKind::Walkable
+        && !grid.protected[row * grid.columns + column]
+        && !grid
+            .props
+            .iter()
+            .any(|&(prop_column, prop_row, _)| (prop_column, prop_row) == (column, row));
+
+    if is_free {
+        grid.props.push((column, row, prop_kind));
+    }
+}
+
+/// Two tiles wide path, first along the columns and then along the rows.
+fn carve_corridor_to_start(grid: &mut ArenaGrid, mut column: usize, mut row: usize) {
+    let (start_column, start_row) = grid.start();
+
+    while column != start_column || row != start_row {
+        for (carve_column, carve_row) in [(column, row), (column, row + 1)] {
+            if carve_row < grid.rows && grid.get(carve_column, carve_row) == TileKind::Solid {
+                grid.set(carve_column, carve_row, TileKind::Walkable);
+            }
+        }
+
+        if column != start_column {
+            column = if column < start_column {
+                column + 1
+            } else {
+                column - 1
+            };
+        } else {
+            row = if row < start_row { row + 1 } else { row - 1 };
+        }
+    }
+}
+
+/// Center of a random spawn zone tile, `None` if the arena has none.
+pub(crate) fn get_random_spawn_position(tilemap: &Tilemap) -> Option<Vec2> {
+    let spawn_positions = tilemap.spawn_positions();
+    if spawn_positions.is_empty() {
+        return None;
+    }
+
+    Some(spawn_positions[rand::thread_rng().gen_range(0..spawn_positions.len())])
+}
+
+/// Enemies appear on the spawn zones of the arena, far from the player.
+/// Enemies split from an elite stay where the elite died. Bosses are placed
+/// when they spawn, before their intro shows where they are.
+pub fn move_new_enemies_to_spawn_zones(
+    tilemap: Option<Res<Tilemap>>,
+    mut enemies: Query<&mut Transform, (Added<Enemy>, Without<SplitFromElite>, Without<Boss>)>,
+) {
+    let Some(tilemap) = tilemap else {
+        return;
+    };
+
+    for mut enemy_transform in enemies.iter_mut() {
+        let Some(position) = get_random_spawn_position(&tilemap) else {
+            return;
+        };
+        enemy_transform.translation.x = position.x;
+        enemy_transform.translation.y = position.y;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn same_seed_gives_same_arena() {
+        for game_level in 1..=5 {
+            let arena = generate_arena(42, game_level);
+            let again = generate_arena(42, game_level);
+
+            assert!(arena.tiles().eq(again.tiles()));
+            assert!(arena.obstacles().eq(again.obstacles()));
+            assert!(arena.props().eq(again.props()));
+        }
+    }
+
+    #[test]
+    fn every_spawn_zone_is_reachable_from_start() {
+        for run_seed in 0..20 {
+            for game_level in 1..=5 {
+                let arena = generate_arena(run_seed, game_level);
+                let tiles: Vec<TileKind> = arena.tiles().map(|(_, _, kind)| kind).collect();
+
+                let start = (arena.columns / 2, arena.rows / 2);
+                let mut reached = vec![false; tiles.len()];
+                reached[start.1 * arena.columns + start.0] = true;
+                let mut queue = VecDeque::from([start]);
+
+                while let Some((column, row)) = queue.pop_front() {
+                    let neighbours = [
+                        (column.wrapping_sub(1), row),
+                        (column + 1, row),
+                        (column, row.wrapping_sub(1)),
+                        (column, row + 1),
+                    ];
+
+                    for (next_column, next_row) in neighbours {
+                        if next_column >= arena.columns || next_row >= arena.rows {
+                            continue;
+                        }
+
+                        let idx = next_row * arena.columns + next_column;
+                        if reached[idx] || tiles[idx] == TileKind::Solid {
+                            continue;
+                        }
+
+                        reached[idx] = true;
+                        queue.push_back((next_column, next_row));
+                    }
+                }
+
+                let spawn_zones: Vec<usize> = tiles
+                    .iter()
+                    .enumerate()
+                    .filter(|&(_, &kind)| kind == TileKind::SpawnZone)
+                    .map(|(idx, _)| idx)
+                    .collect();
+
+                assert!(!spawn_zones.is_empty());
+                assert!(
+                    spawn_zones.iter().all(|&idx| reached[idx]),
+                    "seed {run_seed}, level {game_level}"
+                );
+            }
+        }
+    }
+}
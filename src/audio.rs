@@ -1,27 +1,36 @@
-use crate::{prelude::*, PlayerHitAudioTimeout};
+use crate::{prelude::*, PlayerHitAudioTimeout, SfxAudio};
 
 pub fn hit_enemy_audio(asset_server: &Res<AssetServer>, commands: &mut Commands) {
     return;
-    commands.spawn(AudioBundle {
-        source: asset_server.load("sounds/breakout_collision.ogg"),
-        ..default()
-    });
+    commands.spawn((
+        AudioBundle {
+            source: asset_server.load("sounds/breakout_collision.ogg"),
+            ..default()
+        },
+        SfxAudio,
+    ));
 }
 
 pub fn hit_item_audio(asset_server: &Res<AssetServer>, commands: &mut Commands) {
     return;
-    commands.spawn(AudioBundle {
-        source: asset_server.load("sounds/breakout_collision.ogg"),
-        ..default()
-    });
+    commands.spawn((
+        AudioBundle {
+            source: asset_server.load("sounds/breakout_collision.ogg"),
+            ..default()
+        },
+        SfxAudio,
+    ));
 }
 
 pub fn hit_weapon_audio(asset_server: &Res<AssetServer>, commands: &mut Commands) {
     return;
-    commands.spawn(AudioBundle {
-        source: asset_server.load("sounds/breakout_collision.ogg"),
-        ..default()
-    });
+    commands.spawn((
+        AudioBundle {
+            source: asset_server.load("sounds/breakout_collision.ogg"),
+            ..default()
+        },
+        SfxAudio,
+    ));
 }
 
 pub fn player_hit_audio(
@@ -34,9 +43,12 @@ pub fn player_hit_audio(
     audio_timeout.0.tick(time.delta());
 
     if audio_timeout.0.finished() {
-        commands.spawn(AudioBundle {
-            source: asset_server.load("sounds/breakout_collision.ogg"),
-            ..default()
-        });
+        commands.spawn((
+            AudioBundle {
+                source: asset_server.load("sounds/breakout_collision.ogg"),
+                ..default()
+            },
+            SfxAudio,
+        ));
     }
 }
@@ -0,0 +1,516 @@
+use std::f32::consts::TAU;
+
+use bevy::sprite::{MaterialMesh2dBundle, Mesh2dHandle};
+use rand::Rng;
+
+use crate::{
+    prelude::*,
+    spawn_bullet_pattern_emitter, spawn_orc_enemy,
+    util::{get_boss_moveset_based_on_enemy_class, get_random_vec3, EquippedTypeEnum},
+    AmmoBundle, BaseCamera, BossEnraged, BossIntroOverlay, BossPhaseChanged, CameraEffects,
+    CleanupWhenPlayerDies, Damage, Enemy, GameState, Health, Player, PlayerCamera, ScreenShake,
+    SpritesResources, Tilemap, UpdateAliveEnemiesUI,
+};
+
+/// Attack that was already chosen and is being shown (telegraphed)
+/// to the player before it happens.
+#[derive(Debug, Clone)]
+pub struct PendingBossAttack {
+    pub attack: BossAttack,
+    pub timer: Timer,
+    pub telegraph_entity: Entity,
+    // Where the attack is aimed at. For the laser it holds the start angle on x.
+    pub target: Vec2,
+}
+
+#[derive(Component, Debug, Clone)]
+pub struct Boss {
+    pub current_phase: usize,
+    pub next_attack: usize,
+    pub attack_timer: Timer,
+    pub enrage_timer: Timer,
+    pub enraged: bool,
+    pub pending_attack: Option<PendingBossAttack>,
+}
+
+impl Boss {
+    pub(crate) fn new(moveset: &BossMoveset) -> Self {
+        Self {
+            current_phase: 0,
+            next_attack: 0,
+            attack_timer: Timer::from_seconds(BOSS_FIRST_ATTACK_DELAY_SECONDS, TimerMode::Once),
+            enrage_timer: Timer::from_seconds(moveset.enrage_seconds, TimerMode::Once),
+            enraged: false,
+            pending_attack: None,
+        }
+    }
+}
+
+/// Present while the camera is showing the boss that just spawned.
+#[derive(Resource, Debug, Clone)]
+pub struct BossIntro {
+    pub boss_entity: Entity,
+    pub boss_name: &'static str,
+    // Where the base camera was before the intro, so we can go back to it
+    pub camera_start: Vec2,
+    pub timer: Timer,
+}
+
+impl BossIntro {
+    pub(crate) fn new(boss_entity: Entity, boss_name: &'static str, camera_start: Vec2) -> Self {
+        Self {
+            boss_entity,
+            boss_name,
+            camera_start,
+            timer: Timer::from_seconds(
+                BOSS_INTRO_PAN_SECONDS * 2. + BOSS_INTRO_HOLD_SECONDS,
+                TimerMode::Once,
+            ),
+        }
+    }
+}
+
+#[derive(Component, Clone)]
+pub struct BossTelegraph {
+    pub owner: Entity,
+}
+
+#[derive(Component, Clone)]
+pub struct BossLaser {
+    pub owner: Entity,
+    pub origin: Vec2,
+    pub angle: f32,
+    pub angular_speed: f32,
+    pub damage_per_second: f32,
+    pub timer: Timer,
+}
+
+pub fn update_boss_phase(
+    mut commands: Commands,
+    mut bosses: Query<(Entity, &Health, &Enemy, &mut Boss)>,
+) {
+    for (boss_entity, boss_health, enemy, mut boss) in bosses.iter_mut() {
+        let Some(moveset) = get_boss_moveset_based_on_enemy_class(enemy.class.clone()) else {
+            continue;
+        };
+
+        let health_fraction = boss_health.0 / enemy.max_health;
+        let new_phase = moveset
+            .phases
+            .iter()
+            .rposition(|phase| health_fraction <= phase.health_threshold)
+            .unwrap_or(0);
+
+        // Phases only go forward, even if the boss gets healed somehow
+        if new_phase <= boss.current_phase {
+            continue;
+        }
+
+        boss.current_phase = new_phase;
+        boss.next_attack = 0;
+
+        commands.trigger(BossPhaseChanged {
+            entity: boss_entity,
+            phase: new_phase,
+        });
+    }
+}
+
+pub fn tick_boss_attacks(
+    mut commands: Commands,
+    asset_server: Res<AssetServer>,
+    sprites: Res<SpritesResources>,
+    mut texture_atlas_layout: ResMut<Assets<TextureAtlasLayout>>,
+    mut meshes: ResMut<Assets<Mesh>>,
+    mut materials: ResMut<Assets<ColorMaterial>>,
+    time: Res<Time>,
+
+    mut bosses: Query<
+        (
+            Entity,
+            &mut Transform,
+            &mut Damage,
+            &mut Sprite,
+            &Enemy,
+            &mut Boss,
+        ),
+        Without<Player>,
+    >,
+    player_query: Query<&Transform, (With<Player>, Without<Boss>)>,
+    base_camera: Query<&Transform, (With<BaseCamera>, Without<Player>, Without<Boss>)>,
+    tilemap: Option<Res<Tilemap>>,
+) {
+    let Ok(player_transform) = player_query.get_single() else {
+        return;
+    };
+
+    let Ok(base_camera_transform) = base_camera.get_single() else {
+        return;
+    };
+
+    let player_position = Vec2::new(
+        player_transform.translation.x + base_camera_transform.translation.x,
+        player_transform.translation.y + base_camera_transform.translation.y,
+    );
+
+    for (boss_entity, mut boss_transform, mut boss_damage, mut boss_sprite, enemy, mut boss) in
+        bosses.iter_mut()
+    {
+        let Some(moveset) = get_boss_moveset_based_on_enemy_class(enemy.class.clone()) else {
+            continue;
+        };
+
+        // Enrage
+        boss.enrage_timer.tick(time.delta());
+        if boss.enrage_timer.just_finished() && !boss.enraged {
+            boss.enraged = true;
+            boss_damage.0 *= moveset.enrage_damage_multiplier;
+            boss_sprite.color = Color::srgb(1.0, 0.4, 0.4);
+
+            commands.trigger(BossEnraged {
+                entity: boss_entity,
+            });
+        }
+
+        let boss_position = boss_transform.translation.truncate();
+
+        // Attack that was being telegraphed
+        if let Some(mut pending_attack) = boss.pending_attack.take() {
+            pending_attack.timer.tick(time.delta());
+
+            if !pending_attack.timer.finished() {
+                boss.pending_attack = Some(pending_attack);
+                continue;
+            }
+
+            commands
+                .entity(pending_attack.telegraph_entity)
+                .despawn_recursive();
+            commands.trigger(ScreenShake {
+                trauma: CAMERA_BOSS_ATTACK_TRAUMA,
+            });
+
+            let attack = pending_attack.attack;
+            let damage = boss_damage.0 * attack.damage_multiplier;
+
+            match attack.attack {
+                BossAttackEnum::ProjectileRing { count } => {
+                    let rotation_offset =
+                        boss.next_attack as f32 * BOSS_PROJECTILE_RING_ROTATION_OFFSET;
+
+                    for idx in 0..count {
+                        let angle = TAU * idx as f32 / count as f32 + rotation_offset;
+                        let (sin, cos) = angle.sin_cos();
+
+                        // Ammo moves with the y axis inverted
+                        let direction = Vec3::new(cos, -sin, 1.0);
+
+                        let ammo_bundle = AmmoBundle::new(
+                            &mut texture_atlas_layout,
+                            &sprites,
+                            &asset_server,
+                            Vec3::ONE,
+                            boss_transform.translation,
+                            WeaponTypeEnum::Wand,
+                            direction,
+                            damage,
+                            Quat::from_rotation_z(angle),
+                            BASE_LAYER,
+                            boss_entity,
+                            EquippedTypeEnum::Enemy,
+                        );
+
+                        commands.spawn(ammo_bundle);
+                    }
+                }
+                BossAttackEnum::SummonWave { quantity } => {
+                    let health_bar_translation = Vec3::new(2.0, 15.0, 0.0);
+
+                    spawn_orc_enemy(
+                        &mut commands,
+                        &asset_server,
+                        &sprites,
+                        &mut texture_atlas_layout,
+                        &mut meshes,
+                        &mut materials,
+                        ENEMY_HEALTH,
+                        BOSS_SUMMON_DAMAGE,
+                        Vec3::splat(BOSS_SUMMON_SCALE),
+                        health_bar_translation,
+                        quantity,
+                        Some(boss_transform.translation),
+                    );
+
+                    // Update alive enemies UI
+                    commands.trigger(UpdateAliveEnemiesUI);
+                }
+                BossAttackEnum::Teleport => {
+                    boss_transform.translation.x = pending_attack.target.x;
+                    boss_transform.translation.y = pending_attack.target.y;
+                }
+                BossAttackEnum::LaserSweep {
+                    duration_seconds,
+                    arc_degrees,
+                } => {
+                    let start_angle = pending_attack.target.x;
+                    let angular_speed = arc_degrees * DEGREES_TO_RADIANS / duration_seconds;
+
+                    let rectangle = Mesh2dHandle(
+                        meshes.add(Rectangle::new(BOSS_LASER_LENGTH, BOSS_LASER_WIDTH)),
+                    );
+
+                    commands.spawn((
+                        MaterialMesh2dBundle {
+                            mesh: rectangle,
+                            material: materials.add(Color::srgba(1.0, 0.1, 0.1, 0.9)),
+                            transform: get_boss_laser_transform(boss_position, start_angle),
+                            ..default()
+                        },
+                        BossLaser {
+                            owner: boss_entity,
+                            origin: boss_position,
+                            angle: start_angle,
+                            angular_speed,
+                            damage_per_second: damage,
+                            timer: Timer::from_seconds(duration_seconds, TimerMode::Once),
+                        },
+                        BASE_LAYER,
+                        CleanupWhenPlayerDies,
+                    ));
+                }
+                BossAttackEnum::BulletPattern { name } => {
+                    spawn_bullet_pattern_emitter(
+                        &mut commands,
+                        boss_entity,
+                        name,
+                        attack.damage_multiplier,
+                        false,
+                    );
+                }
+            }
+
+            let mut cooldown_seconds = attack.cooldown_seconds;
+            if boss.enraged {
+                cooldown_seconds *= BOSS_ENRAGE_COOLDOWN_MULTIPLIER;
+            }
+            boss.attack_timer = Timer::from_seconds(cooldown_seconds, TimerMode::Once);
+
+            continue;
+        }
+
+        // Wait for the cooldown of the last attack
+        boss.attack_timer.tick(time.delta());
+        if !boss.attack_timer.finished() {
+            continue;
+        }
+
+        let phase = moveset.phases[boss.current_phase];
+        if phase.attacks.is_empty() {
+            continue;
+        }
+        let attack = phase.attacks[boss.next_attack % phase.attacks.len()];
+        boss.next_attack += 1;
+
+        // Choose where the attack is going to happen and show it to the player
+        let color = materials.add(Color::srgba(1.0, 0.0, 0.0, 0.3));
+        let (mesh, transform, target) = match attack.attack {
+            BossAttackEnum::ProjectileRing { .. } => (
+                meshes.add(Annulus::new(
+                    BOSS_TELEGRAPH_RADIUS - 5.,
+                    BOSS_TELEGRAPH_RADIUS,
+                )),
+                Transform::from_translation(boss_position.extend(TILE_Z_INDEX + 0.5)),
+                boss_position,
+            ),
+            BossAttackEnum::SummonWave { .. } | BossAttackEnum::BulletPattern { .. } => (
+                meshes.add(Circle::new(BOSS_TELEGRAPH_RADIUS)),
+                Transform::from_translation(boss_position.extend(TILE_Z_INDEX + 0.5)),
+                boss_position,
+            ),
+            BossAttackEnum::Teleport => {
+                let mut rng = rand::thread_rng();
+                let n1: u8 = rng.gen();
+                let mut target = get_random_vec3(boss_entity.index() as u64, Some(n1 as u64))
+                    .truncate()
+                    * BACKGROUND_TEXTURE_SCALE;
+
+                // Never inside a wall
+                if let Some(tilemap) = &tilemap {
+                    if tilemap.is_solid(target) {
+                        target = tilemap.closest_walkable_position(target).unwrap_or(target);
+                    }
+                }
+
+                (
+                    meshes.add(Circle::new(BOSS_TELEGRAPH_RADIUS)),
+                    Transform::from_translation(target.extend(TILE_Z_INDEX + 0.5)),
+                    target,
+                )
+            }
+            BossAttackEnum::LaserSweep { arc_degrees, .. } => {
+                let angle_to_player = (player_position - boss_position).to_angle();
+                let start_angle = angle_to_player - arc_degrees * DEGREES_TO_RADIANS / 2.;
+
+                let mut transform = get_boss_laser_transform(boss_position, start_angle);
+                transform.translation.z = TILE_Z_INDEX + 0.5;
+
+                (
+                    meshes.add(Rectangle::new(BOSS_LASER_LENGTH, BOSS_LASER_WIDTH)),
+                    transform,
+                    Vec2::new(start_angle, 0.),
+                )
+            }
+        };
+
+        let telegraph_entity = commands
+            .spawn((
+                MaterialMesh2dBundle {
+                    mesh: Mesh2dHandle(mesh),
+                    material: color,
+                    transform,
+                    ..default()
+                },
+                BossTelegraph { owner: boss_entity },
+                BASE_LAYER,
+                CleanupWhenPlayerDies,
+            ))
+            .id();
+
+        boss.pending_attack = Some(PendingBossAttack {
+            attack,
+            timer: Timer::from_seconds(attack.telegraph_seconds, TimerMode::Once),
+            telegraph_entity,
+            target,
+        });
+    }
+}
+
+pub fn move_boss_lasers(
+    mut commands: Commands,
+    mut lasers: Query<(Entity, &mut Transform, &mut BossLaser)>,
+    bosses: Query<&Transform, (With<Boss>, Without<BossLaser>)>,
+    time: Res<Time>,
+) {
+    for (laser_entity, mut laser_transform, mut laser) in lasers.iter_mut() {
+        laser.timer.tick(time.delta());
+
+        let Ok(boss_transform) = bosses.get(laser.owner) else {
+            commands.entity(laser_entity).despawn();
+            continue;
+        };
+
+        if laser.timer.finished() {
+            commands.entity(laser_entity).despawn();
+            continue;
+        }
+
+        laser.angle += laser.angular_speed * time.delta_seconds();
+        laser.origin = boss_transform.translation.truncate();
+
+        *laser_transform = get_boss_laser_transform(laser.origin, laser.angle);
+    }
+}
+
+/// Telegraphs are not children of the boss (otherwise they would be scaled
+/// with it), so we need to clean them when the boss dies.
+pub fn despawn_orphan_boss_telegraphs(
+    mut commands: Commands,
+    telegraphs: Query<(Entity, &BossTelegraph)>,
+    bosses: Query<(), With<Boss>>,
+) {
+    for (telegraph_entity, telegraph) in telegraphs.iter() {
+        if bosses.get(telegraph.owner).is_err() {
+            commands.entity(telegraph_entity).despawn_recursive();
+        }
+    }
+}
+
+/// The player is drawn in screen coordinates by its own camera, so it is
+/// hidden while the base camera is away from it.
+pub fn start_boss_intro(
+    mut player_camera: Query<&mut Camera, With<PlayerCamera>>,
+    mut camera_effects: ResMut<CameraEffects>,
+) {
+    if let Ok(mut camera) = player_camera.get_single_mut() {
+        camera.is_active = false;
+    }
+
+    camera_effects.zoom_out(
+        BOSS_INTRO_CAMERA_ZOOM,
+        BOSS_INTRO_PAN_SECONDS * 2. + BOSS_INTRO_HOLD_SECONDS,
+    );
+}
+
+pub fn animate_boss_intro(
+    mut commands: Commands,
+    time: Res<Time>,
+    mut boss_intro: ResMut<BossIntro>,
+    bosses: Query<&Transform, (With<Boss>, Without<BaseCamera>)>,
+    mut base_camera: Query<&mut Transform, (With<BaseCamera>, Without<Boss>)>,
+    mut player_camera: Query<&mut Camera, With<PlayerCamera>>,
+    intro_overlay: Query<Entity, With<BossIntroOverlay>>,
+    mut next_state: ResMut<NextState<GameState>>,
+) {
+    let Ok(mut base_camera_transform) = base_camera.get_single_mut() else {
+        return;
+    };
+
+    boss_intro.timer.tick(time.delta());
+
+    let camera_start = boss_intro.camera_start;
+    let camera_target = bosses
+        .get(boss_intro.boss_entity)
+        .map(|transform| get_boss_intro_camera_target(transform.translation.truncate()))
+        .unwrap_or(camera_start);
+
+    if !boss_intro.timer.finished() {
+        let elapsed = boss_intro.timer.elapsed_secs();
+        let remaining = boss_intro.timer.remaining_secs();
+        // Goes to the boss, stays there and comes back
+        let progress = (elapsed.min(remaining) / BOSS_INTRO_PAN_SECONDS).min(1.0);
+        let eased_progress = progress * progress * (3.0 - 2.0 * progress);
+        let position = camera_start.lerp(camera_target, eased_progress);
+
+        base_camera_transform.translation.x = position.x;
+        base_camera_transform.translation.y = position.y;
+        return;
+    }
+
+    // The player position depends on the base camera, so it must be back
+    // exactly where it was.
+    base_camera_transform.translation.x = camera_start.x;
+    base_camera_transform.translation.y = camera_start.y;
+
+    if let Ok(mut camera) = player_camera.get_single_mut() {
+        camera.is_active = true;
+    }
+
+    for overlay_entity in intro_overlay.iter() {
+        commands.entity(overlay_entity).despawn_recursive();
+    }
+
+    commands.remove_resource::<BossIntro>();
+    next_state.set(GameState::Alive);
+}
+
+/// Keeps the camera inside the map, like when following the player.
+fn get_boss_intro_camera_target(boss_position: Vec2) -> Vec2 {
+    let limit_x = BACKGROUND_TEXTURE_RESOLUTION.x_px / 2.0;
+    let limit_y = BACKGROUND_TEXTURE_RESOLUTION.y_px / 2.0;
+
+    Vec2::new(
+        boss_position.x.clamp(-limit_x, limit_x),
+        boss_position.y.clamp(-limit_y, limit_y),
+    )
+}
+
+/// The laser rectangle starts on the boss and goes outwards on the `angle`.
+fn get_boss_laser_transform(origin: Vec2, angle: f32) -> Transform {
+    let center = origin + Vec2::from_angle(angle) * BOSS_LASER_LENGTH / 2.;
+
+    Transform {
+        translation: center.extend(CHAR_Z_INDEX),
+        rotation: Quat::from_rotation_z(angle),
+        scale: Vec3::ONE,
+    }
+}
@@ -1,8 +1,9 @@
 use chrono::{NaiveTime, Utc};
+use rand::Rng;
 
 use crate::{
     prelude::*, util::get_item_sprite_based_on_item_type, AnimationIndices, AnimationTimer, Armor,
-    CleanupWhenPlayerDies, Health, Mana, Speed, SpritesResources,
+    CleanupWhenPlayerDies, Coins, Health, Mana, Speed, SpritesResources,
 };
 
 /*
@@ -33,6 +34,44 @@ pub struct Shield {
     pub defensive: f32,
     pub shield_type: ShieldType,
     pub duration_seconds: Option<u64>,
+    // How many shields orbit around the player
+    pub count: u32,
+    pub radius: f32,
+    // Degrees per second
+    pub orbit_speed: f32,
+    // Damage each shield absorbs before shattering
+    pub durability: f32,
+    pub regrow_seconds: f32,
+}
+
+/// Hit points of one orbiting shield. Once they run out the shield shatters
+/// and grows back after a while.
+#[derive(Component, Debug, Clone)]
+pub struct ShieldDurability {
+    pub durability: f32,
+    pub max_durability: f32,
+    pub regrow_timer: Timer,
+}
+
+impl ShieldDurability {
+    pub fn new(shield: &Shield) -> Self {
+        Self {
+            durability: shield.durability,
+            max_durability: shield.durability,
+            regrow_timer: Timer::from_seconds(shield.regrow_seconds, TimerMode::Once),
+        }
+    }
+
+    pub fn is_broken(&self) -> bool {
+        self.durability <= 0.
+    }
+}
+
+/// Piece of a shattered shield flying away from where the shield was.
+#[derive(Component, Clone)]
+pub struct ShieldShard {
+    pub velocity: Vec2,
+    pub timer: Timer,
 }
 
 #[cfg_attr(
@@ -55,6 +94,7 @@ pub enum ItemTypeEnum {
     Health(Health),
     Mana(Mana),
     Invisibility(Invisibility),
+    Coins(Coins),
 }
 
 impl Default for ItemTypeEnum {
@@ -191,3 +231,80 @@ impl BuffBundle {
         }
     }
 }
+
+/// Hides the shield and scatters a few shards on the map where it broke.
+pub(crate) fn shatter_shield(
+    commands: &mut Commands,
+    visibility: &mut Visibility,
+    durability: &mut ShieldDurability,
+    world_position: Vec2,
+) {
+    durability.durability = 0.;
+    durability.regrow_timer.reset();
+    *visibility = Visibility::Hidden;
+
+    let mut rng = rand::thread_rng();
+
+    for _ in 0..SHIELD_SHARDS {
+        let direction = Vec2::from_angle(rng.gen_range(0.0..std::f32::consts::TAU));
+
+        commands.spawn((
+            SpriteBundle {
+                sprite: Sprite {
+                    color: Color::srgba(0.6, 0.9, 1.0, 1.0),
+                    custom_size: Some(Vec2::splat(SHIELD_SHARD_SIZE)),
+                    ..default()
+                },
+                transform: Transform {
+                    translation: world_position.extend(CHAR_Z_INDEX),
+                    rotation: Quat::from_rotation_z(rng.gen_range(0.0..std::f32::consts::TAU)),
+                    ..default()
+                },
+                ..default()
+            },
+            ShieldShard {
+                velocity: direction * SHIELD_SHARD_SPEED,
+                timer: Timer::from_seconds(SHIELD_SHARD_SECONDS, TimerMode::Once),
+            },
+            Name::new("ShieldShard"),
+            BASE_LAYER,
+            CleanupWhenPlayerDies,
+        ));
+    }
+}
+
+pub fn regrow_broken_shields(
+    time: Res<Time>,
+    mut shields: Query<(&mut ShieldDurability, &mut Visibility)>,
+) {
+    for (mut durability, mut visibility) in shields.iter_mut() {
+        if !durability.is_broken() {
+            continue;
+        }
+
+        durability.regrow_timer.tick(time.delta());
+        if durability.regrow_timer.finished() {
+            durability.durability = durability.max_durability;
+            *visibility = Visibility::Visible;
+        }
+    }
+}
+
+pub fn fade_shield_shards(
+    mut commands: Commands,
+    time: Res<Time>,
+    mut shards: Query<(Entity, &mut Transform, &mut Sprite, &mut ShieldShard)>,
+) {
+    for (shard_entity, mut shard_transform, mut shard_sprite, mut shard) in shards.iter_mut() {
+        shard.timer.tick(time.delta());
+        if shard.timer.finished() {
+            commands.entity(shard_entity).despawn();
+            continue;
+        }
+
+        shard_transform.translation += (shard.velocity * time.delta_seconds()).extend(0.);
+        shard_sprite
+            .color
+            .set_alpha(shard.timer.fraction_remaining());
+    }
+}
@@ -0,0 +1,527 @@
+use std::f32::consts::{PI, TAU};
+
+use bevy::sprite::{MaterialMesh2dBundle, Mesh2dHandle};
+
+use crate::{
+    prelude::*, util::get_bullet_pattern_based_on_name, BaseCamera, CleanupWhenPlayerDies, Damage,
+    Enemy, Perception, Player, Tilemap,
+};
+
+#[derive(Debug, Clone)]
+pub enum BulletBehaviour {
+    Straight,
+    Homing {
+        delay: Timer,
+        turn_rate: f32,
+    },
+    Lobbed {
+        start: Vec2,
+        target: Vec2,
+        radius: f32,
+        ground_marker: Entity,
+    },
+}
+
+/// Enemy bullet that lives in the `BulletPool`. Instead of being despawned
+/// it is hidden and reused by the next pattern.
+#[derive(Component, Debug, Clone)]
+pub struct PooledBullet {
+    pub active: bool,
+    pub velocity: Vec2,
+    pub damage: f32,
+    // For lobbed bullets this is the flight time
+    pub lifetime: Timer,
+    pub behaviour: BulletBehaviour,
+}
+
+impl Default for PooledBullet {
+    fn default() -> Self {
+        Self {
+            active: false,
+            velocity: Vec2::ZERO,
+            damage: 0.,
+            lifetime: Timer::from_seconds(BULLET_LIFETIME_SECONDS, TimerMode::Once),
+            behaviour: BulletBehaviour::Straight,
+        }
+    }
+}
+
+#[derive(Component, Clone)]
+pub struct BulletGroundMarker;
+
+#[derive(Resource)]
+pub struct BulletPool {
+    free: Vec<Entity>,
+    mesh: Mesh2dHandle,
+    material: Handle<ColorMaterial>,
+    homing_material: Handle<ColorMaterial>,
+    lobbed_material: Handle<ColorMaterial>,
+    ground_marker_mesh: Mesh2dHandle,
+    ground_marker_material: Handle<ColorMaterial>,
+}
+
+impl BulletPool {
+    fn spawn_bullet(&self, commands: &mut Commands) -> Entity {
+        commands
+            .spawn((
+                MaterialMesh2dBundle {
+                    mesh: self.mesh.clone(),
+                    material: self.material.clone(),
+                    visibility: Visibility::Hidden,
+                    ..default()
+                },
+                PooledBullet::default(),
+                BASE_LAYER,
+                Name::new("Pooled Bullet"),
+            ))
+            .id()
+    }
+
+    /// Takes a bullet from the pool, or creates a new one if all of them are
+    /// being used.
+    fn acquire(&mut self, commands: &mut Commands) -> Entity {
+        self.free
+            .pop()
+            .unwrap_or_else(|| self.spawn_bullet(commands))
+    }
+
+    pub(crate) fn release(
+        &mut self,
+        commands: &mut Commands,
+        entity: Entity,
+        bullet: &mut PooledBullet,
+        visibility: &mut Visibility,
+    ) {
+        if !bullet.active {
+            return;
+        }
+
+        if let BulletBehaviour::Lobbed { ground_marker, .. } = bullet.behaviour {
+            commands.entity(ground_marker).despawn_recursive();
+        }
+
+        bullet.active = false;
+        *visibility = Visibility::Hidden;
+        self.free.push(entity);
+    }
+}
+
+/// Fires the volleys of a bullet pattern for its owner. Enemies have one
+/// that repeats forever, bosses spawn one for each pattern attack.
+#[derive(Component, Debug, Clone)]
+pub struct BulletPatternEmitter {
+    pub owner: Entity,
+    pub pattern: BulletPattern,
+    pub damage_multiplier: f32,
+    pub repeat: bool,
+    pub cooldown: Timer,
+    pub volley_timer: Timer,
+    pub volleys_left: u32,
+    // Current rotation of spiral patterns
+    pub angle: f32,
+}
+
+pub fn setup_bullet_pool(
+    mut commands: Commands,
+    mut meshes: ResMut<Assets<Mesh>>,
+    mut materials: ResMut<Assets<ColorMaterial>>,
+) {
+    let mut pool = BulletPool {
+        free: Vec::with_capacity(BULLET_POOL_INITIAL_SIZE),
+        mesh: Mesh2dHandle(meshes.add(Circle::new(BULLET_RADIUS))),
+        material: materials.add(Color::srgb(1.0, 0.55, 0.1)),
+        homing_material: materials.add(Color::srgb(0.7, 0.3, 1.0)),
+        lobbed_material: materials.add(Color::srgb(0.4, 0.4, 0.4)),
+        ground_marker_mesh: Mesh2dHandle(meshes.add(Circle::new(1.0))),
+        ground_marker_material: materials.add(Color::srgba(1.0, 0.0, 0.0, 0.3)),
+    };
+
+    for _ in 0..BULLET_POOL_INITIAL_SIZE {
+        let entity = pool.spawn_bullet(&mut commands);
+        pool.free.push(entity);
+    }
+
+    commands.insert_resource(pool);
+}
+
+/// Gives back to the pool every bullet that is still flying, so that a new
+/// game or level does not start with old bullets on the screen.
+pub fn reset_bullet_pool(
+    mut commands: Commands,
+    mut pool: ResMut<BulletPool>,
+    mut bullets: Query<(Entity, &mut PooledBullet, &mut Visibility)>,
+) {
+    for (entity, mut bullet, mut visibility) in bullets.iter_mut() {
+        pool.release(&mut commands, entity, &mut bullet, &mut visibility);
+    }
+}
+
+pub(crate) fn spawn_bullet_pattern_emitter(
+    commands: &mut Commands,
+    owner: Entity,
+    pattern_name: &str,
+    damage_multiplier: f32,
+    repeat: bool,
+) {
+    let Some(pattern) = get_bullet_pattern_based_on_name(pattern_name) else {
+        println!("Could not find bullet pattern {}", pattern_name);
+        return;
+    };
+
+    // Repeating emitters wait for the cooldown before the first volley,
+    // so enemies do not shoot the moment they spawn.
+    let volleys_left = if repeat { 0 } else { pattern.volleys };
+
+    commands.spawn((
+        BulletPatternEmitter {
+            owner,
+            pattern,
+            damage_multiplier,
+            repeat,
+            cooldown: Timer::from_seconds(pattern.cooldown_seconds, TimerMode::Once),
+            volley_timer: Timer::from_seconds(pattern.volley_interval_seconds, TimerMode::Once),
+            volleys_left,
+            angle: 0.,
+        },
+        CleanupWhenPlayerDies,
+        Name::new("Bullet Pattern Emitter"),
+    ));
+}
+
+pub fn tick_bullet_pattern_emitters(
+    mut commands: Commands,
+    mut pool: ResMut<BulletPool>,
+    time: Res<Time>,
+    mut emitters: Query<(Entity, &mut BulletPatternEmitter)>,
+    enemies: Query<(&Transform, &Damage, Option<&Perception>), With<Enemy>>,
+    tilemap: Option<Res<Tilemap>>,
+    player_query: Query<&Transform, With<Player>>,
+    base_camera: Query<&Transform, (With<BaseCamera>, Without<Player>)>,
+) {
+    let Ok(player_transform) = player_query.get_single() else {
+        return;
+    };
+
+    let Ok(base_camera_transform) = base_camera.get_single() else {
+        return;
+    };
+
+    let player_position = Vec2::new(
+        player_transform.translation.x + base_camera_transform.translation.x,
+        player_transform.translation.y + base_camera_transform.translation.y,
+    );
+
+    for (emitter_entity, mut emitter) in emitters.iter_mut() {
+        let Ok((owner_transform, owner_damage, owner_perception)) = enemies.get(emitter.owner)
+        else {
+            commands.entity(emitter_entity).despawn();
+            continue;
+        };
+
+        // Ranged enemies hold their fire while they can't see the player
+        if owner_perception.is_some_and(|perception| !perception.can_see_player) {
+            continue;
+        }
+
+        // Nor do they waste bullets on an obstacle
+        let owner_position = owner_transform.translation.truncate();
+        if tilemap
+            .as_ref()
+            .is_some_and(|tilemap| !tilemap.has_line_of_sight(owner_position, player_position))
+        {
+            continue;
+        }
+
+        let fire_volley = if emitter.volleys_left == 0 {
+            if !emitter.repeat {
+                commands.entity(emitter_entity).despawn();
+                continue;
+            }
+
+            emitter.cooldown.tick(time.delta());
+            if !emitter.cooldown.finished() {
+                continue;
+            }
+
+            emitter.cooldown.reset();
+            emitter.volleys_left = emitter.pattern.volleys;
+            // The first volley goes right away
+            true
+        } else {
+            emitter.volley_timer.tick(time.delta());
+            emitter.volley_timer.finished()
+        };
+
+        if !fire_volley {
+            continue;
+        }
+
+        emitter.volley_timer.reset();
+        emitter.volleys_left -= 1;
+
+        let damage = owner_damage.0 * emitter.damage_multiplier * emitter.pattern.damage_multiplier;
+        let pattern = emitter.pattern;
+
+        fire_bullet_volley(
+            &mut commands,
+            &mut pool,
+            &pattern,
+            owner_position,
+            player_position,
+            damage,
+            &mut emitter.angle,
+        );
+    }
+}
+
+fn fire_bullet_volley(
+    commands: &mut Commands,
+    pool: &mut BulletPool,
+    pattern: &BulletPattern,
+    origin: Vec2,
+    player_position: Vec2,
+    damage: f32,
+    spiral_angle: &mut f32,
+) {
+    let aim_angle = (player_position - origin).to_angle();
+
+    match pattern.pattern {
+        BulletPatternEnum::SpreadFan { count, arc_degrees } => {
+            for angle in get_fan_angles(aim_angle, count, arc_degrees) {
+                fire_straight_bullet(commands, pool, pattern, origin, angle, damage);
+            }
+        }
+        BulletPatternEnum::Ring { count } => {
+            for idx in 0..count {
+                let angle = aim_angle + TAU * idx as f32 / count as f32;
+                fire_straight_bullet(commands, pool, pattern, origin, angle, damage);
+            }
+        }
+        BulletPatternEnum::Spiral {
+            arms,
+            rotation_per_volley_degrees,
+        } => {
+            for idx in 0..arms {
+                let angle = *spiral_angle + TAU * idx as f32 / arms as f32;
+                fire_straight_bullet(commands, pool, pattern, origin, angle, damage);
+            }
+            *spiral_angle += rotation_per_volley_degrees * DEGREES_TO_RADIANS;
+        }
+        BulletPatternEnum::AimedBurst => {
+            fire_straight_bullet(commands, pool, pattern, origin, aim_angle, damage);
+        }
+        BulletPatternEnum::HomingOrb {
+            count,
+            delay_seconds,
+            turn_rate_degrees,
+        } => {
+            for angle in get_fan_angles(aim_angle, count, 90.) {
+                let behaviour = BulletBehaviour::Homing {
+                    delay: Timer::from_seconds(delay_seconds, TimerMode::Once),
+                    turn_rate: turn_rate_degrees * DEGREES_TO_RADIANS,
+                };
+                let material = pool.homing_material.clone();
+                fire_bullet(
+                    commands,
+                    pool,
+                    origin,
+                    Vec2::from_angle(angle) * pattern.speed,
+                    damage,
+                    BULLET_LIFETIME_SECONDS,
+                    behaviour,
+                    material,
+                );
+            }
+        }
+        BulletPatternEnum::LobbedAoe {
+            count,
+            radius,
+            flight_seconds,
+        } => {
+            for idx in 0..count {
+                // The first shell goes on the player, the others around him
+                let target = if idx == 0 {
+                    player_position
+                } else {
+                    let angle = TAU * idx as f32 / (count - 1) as f32;
+                    player_position + Vec2::from_angle(angle) * radius * 1.5
+                };
+
+                let ground_marker = commands
+                    .spawn((
+                        MaterialMesh2dBundle {
+                            mesh: pool.ground_marker_mesh.clone(),
+                            material: pool.ground_marker_material.clone(),
+                            transform: Transform {
+                                translation: target.extend(TILE_Z_INDEX + 0.5),
+                                scale: Vec3::splat(radius),
+                                ..default()
+                            },
+                            ..default()
+                        },
+                        BulletGroundMarker,
+                        BASE_LAYER,
+                        CleanupWhenPlayerDies,
+                    ))
+                    .id();
+
+                let behaviour = BulletBehaviour::Lobbed {
+                    start: origin,
+                    target,
+                    radius,
+                    ground_marker,
+                };
+                let material = pool.lobbed_material.clone();
+                fire_bullet(
+                    commands,
+                    pool,
+                    origin,
+                    Vec2::ZERO,
+                    damage,
+                    flight_seconds,
+                    behaviour,
+                    material,
+                );
+            }
+        }
+    }
+}
+
+fn fire_straight_bullet(
+    commands: &mut Commands,
+    pool: &mut BulletPool,
+    pattern: &BulletPattern,
+    origin: Vec2,
+    angle: f32,
+    damage: f32,
+) {
+    let material = pool.material.clone();
+    fire_bullet(
+        commands,
+        pool,
+        origin,
+        Vec2::from_angle(angle) * pattern.speed,
+        damage,
+        BULLET_LIFETIME_SECONDS,
+        BulletBehaviour::Straight,
+        material,
+    );
+}
+
+fn fire_bullet(
+    commands: &mut Commands,
+    pool: &mut BulletPool,
+    origin: Vec2,
+    velocity: Vec2,
+    damage: f32,
+    lifetime_seconds: f32,
+    behaviour: BulletBehaviour,
+    material: Handle<ColorMaterial>,
+) {
+    let entity = pool.acquire(commands);
+
+    commands.entity(entity).insert((
+        PooledBullet {
+            active: true,
+            velocity,
+            damage,
+            lifetime: Timer::from_seconds(lifetime_seconds, TimerMode::Once),
+            behaviour,
+        },
+        Transform::from_translation(origin.extend(CHAR_Z_INDEX + 0.5)),
+        Visibility::Visible,
+        material,
+    ));
+}
+
+/// `count` angles evenly spread on an arc centered on `aim_angle`.
+fn get_fan_angles(aim_angle: f32, count: u32, arc_degrees: f32) -> impl Iterator<Item = f32> {
+    let arc = arc_degrees * DEGREES_TO_RADIANS;
+
+    (0..count).map(move |idx| {
+        if count <= 1 {
+            return aim_angle;
+        }
+
+        aim_angle - arc / 2. + arc * idx as f32 / (count - 1) as f32
+    })
+}
+
+pub fn move_pooled_bullets(
+    mut commands: Commands,
+    mut pool: ResMut<BulletPool>,
+    time: Res<Time>,
+    mut bullets: Query<(Entity, &mut Transform, &mut PooledBullet, &mut Visibility)>,
+    tilemap: Option<Res<Tilemap>>,
+    player_query: Query<&Transform, (With<Player>, Without<PooledBullet>)>,
+    base_camera: Query<&Transform, (With<BaseCamera>, Without<Player>, Without<PooledBullet>)>,
+) {
+    let Ok(player_transform) = player_query.get_single() else {
+        return;
+    };
+
+    let Ok(base_camera_transform) = base_camera.get_single() else {
+        return;
+    };
+
+    let player_position = Vec2::new(
+        player_transform.translation.x + base_camera_transform.translation.x,
+        player_transform.translation.y + base_camera_transform.translation.y,
+    );
+
+    let limit_x = BACKGROUND_TEXTURE_RESOLUTION.x_px;
+    let limit_y = BACKGROUND_TEXTURE_RESOLUTION.y_px;
+
+    for (entity, mut transform, mut bullet, mut visibility) in bullets.iter_mut() {
+        if !bullet.active {
+            continue;
+        }
+
+        bullet.lifetime.tick(time.delta());
+        let position = transform.translation.truncate();
+        let velocity = bullet.velocity;
+        let progress = bullet.lifetime.fraction();
+
+        match &mut bullet.behaviour {
+            BulletBehaviour::Straight => {}
+            BulletBehaviour::Homing { delay, turn_rate } => {
+                delay.tick(time.delta());
+
+                if delay.finished() {
+                    let desired_angle = (player_position - position).to_angle();
+                    let current_angle = velocity.to_angle();
+                    let max_turn = *turn_rate * time.delta_seconds();
+                    let turn = (desired_angle - current_angle + PI).rem_euclid(TAU) - PI;
+
+                    bullet.velocity =
+                        Vec2::from_angle(current_angle + turn.clamp(-max_turn, max_turn))
+                            * velocity.length();
+                }
+            }
+            BulletBehaviour::Lobbed { start, target, .. } => {
+                // Lobbed bullets land when the lifetime ends and are then
+                // handled by the collision check, so they are not released here.
+                let height = (progress * PI).sin();
+
+                transform.translation = start.lerp(*target, progress).extend(CHAR_Z_INDEX + 0.5);
+                transform.scale = Vec3::splat(1.0 + height * (BULLET_LOBBED_MAX_SCALE - 1.0));
+                continue;
+            }
+        }
+
+        let new_position = position + bullet.velocity * time.delta_seconds();
+        let off_map = !(-limit_x..=limit_x).contains(&new_position.x)
+            || !(-limit_y..=limit_y).contains(&new_position.y);
+        let hits_solid = tilemap
+            .as_ref()
+            .is_some_and(|tilemap| tilemap.is_solid(new_position));
+
+        if off_map || hits_solid || bullet.lifetime.finished() {
+            pool.release(&mut commands, entity, &mut bullet, &mut visibility);
+            continue;
+        }
+
+        transform.translation.x = new_position.x;
+        transform.translation.y = new_position.y;
+    }
+}
@@ -1,4 +1,10 @@
-use crate::prelude::*;
+use bevy::{
+    render::camera::{ScalingMode, Viewport},
+    window::PrimaryWindow,
+};
+use rand::Rng;
+
+use crate::{prelude::*, Boss, Enemy, Settings};
 
 #[derive(Component, Clone)]
 pub struct BaseCamera;
@@ -12,6 +18,59 @@ pub struct OverlayCamera;
 #[derive(Component, Clone)]
 pub struct MenuCamera;
 
+/// Draws nothing, it only clears the bars around the other cameras when the
+/// window has another aspect ratio than the virtual resolution.
+#[derive(Component, Clone)]
+pub struct LetterboxCamera;
+
+/// What the cameras show on top of where the base camera is. Gameplay keeps
+/// using the base camera transform as the offset of the map, so the follow,
+/// shake and zoom only change the projection of every camera, all of them
+/// the same way.
+#[derive(Resource)]
+pub struct CameraEffects {
+    // Where the cameras are looking at, lagging behind the base camera
+    position: Option<Vec2>,
+    velocity: Vec2,
+    // The base camera can move this much before the cameras follow it
+    pub dead_zone: Vec2,
+    // Roughly how long the cameras take to catch up with the base camera
+    pub follow_seconds: f32,
+    // From 0 to 1. The shake grows with its square so small hits are subtle.
+    trauma: f32,
+    zoom: f32,
+    zoom_out: Option<(f32, Timer)>,
+}
+
+impl Default for CameraEffects {
+    fn default() -> Self {
+        Self {
+            position: None,
+            velocity: Vec2::ZERO,
+            dead_zone: CAMERA_DEAD_ZONE,
+            follow_seconds: CAMERA_FOLLOW_SECONDS,
+            trauma: 0.,
+            zoom: 1.,
+            zoom_out: None,
+        }
+    }
+}
+
+impl CameraEffects {
+    pub fn add_trauma(&mut self, trauma: f32) {
+        self.trauma = (self.trauma + trauma).clamp(0., 1.);
+    }
+
+    /// Shows more of the map for a while. A bigger zoom shows more.
+    pub fn zoom_out(&mut self, zoom: f32, seconds: f32) {
+        self.zoom_out = Some((zoom, Timer::from_seconds(seconds, TimerMode::Once)));
+    }
+}
+
+pub fn setup_letterbox_camera(mut commands: Commands) {
+    spawn_letterbox_camera(&mut commands);
+}
+
 pub fn setup_base_camera(mut commands: Commands) {
     spawn_base_camera(&mut commands);
 }
@@ -28,6 +87,23 @@ pub fn setup_menu_camera(mut commands: Commands) {
     spawn_menu_camera(&mut commands);
 }
 
+pub fn spawn_letterbox_camera(commands: &mut Commands) -> Entity {
+    commands
+        .spawn((
+            Camera2dBundle {
+                camera: Camera {
+                    order: -1,
+                    clear_color: ClearColorConfig::Custom(Color::BLACK),
+                    ..default()
+                },
+                ..default()
+            },
+            LetterboxCamera,
+            RenderLayers::none(),
+        ))
+        .id()
+}
+
 pub fn spawn_base_camera(commands: &mut Commands) -> Entity {
     commands
         .spawn((
@@ -72,7 +148,7 @@ pub fn spawn_overlay_camera(commands: &mut Commands) -> Entity {
                 },
                 ..default()
             },
-            MenuCamera,
+            OverlayCamera,
             OVERLAY_LAYER,
         ))
         .id()
@@ -94,3 +170,190 @@ pub fn spawn_menu_camera(commands: &mut Commands) -> Entity {
         ))
         .id()
 }
+
+/// Critically damped spring, it gets to the target as fast as it can without
+/// going past it.
+fn smooth_damp(current: Vec2, target: Vec2, velocity: &mut Vec2, seconds: f32, delta: f32) -> Vec2 {
+    let omega = 2. / seconds.max(0.0001);
+    let x = omega * delta;
+    let exp = 1. / (1. + x + 0.48 * x * x + 0.235 * x * x * x);
+    let change = current - target;
+    let temp = (*velocity + omega * change) * delta;
+
+    *velocity = (*velocity - omega * temp) * exp;
+    target + (change + temp) * exp
+}
+
+/// Moves the cameras towards the base camera once it leaves the dead-zone.
+pub fn follow_base_camera(
+    time: Res<Time>,
+    mut camera_effects: ResMut<CameraEffects>,
+    base_camera: Query<&Transform, With<BaseCamera>>,
+) {
+    let Ok(base_camera_transform) = base_camera.get_single() else {
+        return;
+    };
+
+    let target = base_camera_transform.translation.truncate();
+    let position = camera_effects.position.unwrap_or(target);
+
+    // Too far away to follow, like after a restart
+    if position.distance(target) > CAMERA_SNAP_DISTANCE {
+        camera_effects.position = Some(target);
+        camera_effects.velocity = Vec2::ZERO;
+        return;
+    }
+
+    let dead_zone = camera_effects.dead_zone;
+    let distance = target - position;
+    let follow_target = Vec2::new(
+        if distance.x.abs() > dead_zone.x {
+            target.x - dead_zone.x * distance.x.signum()
+        } else {
+            position.x
+        },
+        if distance.y.abs() > dead_zone.y {
+            target.y - dead_zone.y * distance.y.signum()
+        } else {
+            position.y
+        },
+    );
+
+    let follow_seconds = camera_effects.follow_seconds;
+    let mut velocity = camera_effects.velocity;
+    let position = smooth_damp(
+        position,
+        follow_target,
+        &mut velocity,
+        follow_seconds,
+        time.delta_seconds(),
+    );
+
+    camera_effects.position = Some(position);
+    camera_effects.velocity = velocity;
+}
+
+/// Shakes and zooms every camera, and moves them where the follow is.
+pub fn apply_camera_effects(
+    time: Res<Time>,
+    settings: Res<Settings>,
+    mut camera_effects: ResMut<CameraEffects>,
+    base_camera: Query<&Transform, With<BaseCamera>>,
+    mut cameras: Query<&mut OrthographicProjection, With<Camera>>,
+    enemies: Query<(), With<Enemy>>,
+    bosses: Query<(), With<Boss>>,
+) {
+    let Ok(base_camera_transform) = base_camera.get_single() else {
+        return;
+    };
+
+    // Shake
+    camera_effects.trauma =
+        (camera_effects.trauma - CAMERA_TRAUMA_DECAY * time.delta_seconds()).max(0.);
+    let shake = camera_effects.trauma * camera_effects.trauma;
+
+    let mut rng = rand::thread_rng();
+    let shake_offset = Vec2::new(rng.gen_range(-1.0..1.0), rng.gen_range(-1.0..1.0))
+        * CAMERA_MAX_SHAKE_OFFSET
+        * shake
+        * settings.screen_shake;
+
+    // Zoom
+    let mut target_zoom = 1.;
+    if let Some((zoom, timer)) = camera_effects.zoom_out.as_mut() {
+        timer.tick(time.delta());
+        target_zoom = *zoom;
+
+        if timer.finished() {
+            camera_effects.zoom_out = None;
+            target_zoom = 1.;
+        }
+    }
+    if !bosses.is_empty() || enemies.iter().len() >= CAMERA_BIG_FIGHT_ENEMIES {
+        target_zoom = f32::max(target_zoom, CAMERA_BIG_FIGHT_ZOOM);
+    }
+
+    let zoom_step = 1. - (-CAMERA_ZOOM_SPEED * time.delta_seconds()).exp();
+    camera_effects.zoom += (target_zoom - camera_effects.zoom) * zoom_step;
+
+    let offset = camera_effects.position.unwrap_or_default()
+        - base_camera_transform.translation.truncate()
+        + shake_offset;
+
+    for mut projection in cameras.iter_mut() {
+        projection.scale = camera_effects.zoom;
+
+        // Moving the origin moves what is drawn without moving the camera
+        let size = projection.area.size();
+        if size.x > 0. && size.y > 0. {
+            projection.viewport_origin = Vec2::splat(0.5) - offset / size;
+        }
+    }
+}
+
+/// Makes every camera show the virtual resolution on the biggest area of the
+/// window with the same aspect ratio, and scales the UI the same way.
+pub fn fit_cameras_to_window(
+    windows: Query<&Window, (With<PrimaryWindow>, Changed<Window>)>,
+    mut ui_scale: ResMut<UiScale>,
+    mut cameras: Query<(&mut Camera, &mut OrthographicProjection), Without<LetterboxCamera>>,
+) {
+    let Ok(window) = windows.get_single() else {
+        return;
+    };
+
+    let window_size = Vec2::new(
+        window.physical_width() as f32,
+        window.physical_height() as f32,
+    );
+    let virtual_size = Vec2::new(VIRTUAL_RESOLUTION.x_px, VIRTUAL_RESOLUTION.y_px);
+
+    // Physical pixels for each pixel of the virtual resolution
+    let scale = (window_size / virtual_size).min_element();
+    if scale <= 0. {
+        return;
+    }
+
+    let viewport_size = (virtual_size * scale).round().as_uvec2().max(UVec2::ONE);
+    let viewport_position = (window_size.as_uvec2().saturating_sub(viewport_size)) / 2;
+
+    for (mut camera, mut projection) in cameras.iter_mut() {
+        let viewport = Viewport {
+            physical_position: viewport_position,
+            physical_size: viewport_size,
+            ..default()
+        };
+
+        if camera
+            .viewport
+            .as_ref()
+            .map(|current| (current.physical_position, current.physical_size))
+            != Some((viewport.physical_position, viewport.physical_size))
+        {
+            camera.viewport = Some(viewport);
+        }
+
+        if !matches!(projection.scaling_mode, ScalingMode::Fixed { .. }) {
+            projection.scaling_mode = ScalingMode::Fixed {
+                width: virtual_size.x,
+                height: virtual_size.y,
+            };
+        }
+    }
+
+    // The UI is made for the virtual resolution in logical pixels
+    let new_ui_scale = scale / window.scale_factor();
+    if ui_scale.0 != new_ui_scale {
+        ui_scale.0 = new_ui_scale;
+    }
+}
+
+/// The window mode is changed when the settings are applied.
+pub fn toggle_fullscreen(
+    keyboard_input: Res<ButtonInput<KeyCode>>,
+    mut settings: ResMut<Settings>,
+) {
+    if keyboard_input.just_pressed(settings.key_bindings.fullscreen) {
+        settings.fullscreen = !settings.fullscreen;
+    }
+}
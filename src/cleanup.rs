@@ -1,7 +1,7 @@
 use crate::{
-    prelude::*, ChangeBackgroundTexture, CurrentBoss, CurrentGameLevelChanged,
-    CurrentGameLevelUI, CurrentScore, CurrentTime, CurrentTimeUI, CurrentWave, CurrentWaveUI,
-    PlayerHealthChanged, PlayerProfileUISet, ScoreChanged, SetupNewTime,
+    prelude::*, ChangeBackgroundTexture, CurrentBoss, CurrentGameLevelChanged, CurrentGameLevelUI,
+    CurrentScore, CurrentTime, CurrentTimeUI, CurrentWave, CurrentWaveUI, PlayerHealthChanged,
+    PlayerProfileUISet, RunSeed, ScoreChanged, SetupNewTime,
 };
 
 #[derive(Component, Clone)]
@@ -19,6 +19,7 @@ pub fn reset_initial_state(
     mut current_wave: ResMut<CurrentWave>,
     mut current_time: ResMut<CurrentTime>,
     mut current_score: ResMut<CurrentScore>,
+    mut run_seed: ResMut<RunSeed>,
     mut current_wave_ui: Query<
         (&mut Text, &CurrentWaveUI),
         (Without<CurrentTimeUI>, Without<CurrentGameLevelUI>),
@@ -26,6 +27,10 @@ pub fn reset_initial_state(
 ) {
     current_boss.0 = None;
 
+    // Every run gets new arenas, unless the seed is set
+    *run_seed = RunSeed::new_run();
+    info!("Run seed: {}", run_seed.0);
+
     // update current game level and its UI
     commands.trigger(CurrentGameLevelChanged(1));
 
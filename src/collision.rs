@@ -3,24 +3,41 @@ use crate::{
     audio::{hit_enemy_audio, hit_item_audio, player_hit_audio},
     enemy::Enemy,
     events::PlayerHealthChanged,
+    get_luck,
     item::Item,
+    open_chest, open_reward_chest,
     player::Player,
     prelude::*,
+    shatter_shield,
     util::EquippedTypeEnum,
-    AllEnemiesDied, Armor, BaseCamera, Buff, BuffAdded, BuffBundle, BuffGroup, BuffGroupBundle,
-    Damage, EnemyHealthChanged, GameOver, Health, ItemTypeEnum, Laser, Mana, MaybeSpawnHealthPack,
-    MaybeSpawnManaPack, PlayerHitAudioTimeout, PlayerManaChanged, Power, ScoreChanged, Speed,
-    SpritesResources, UpdateAliveEnemiesUI, Weapon, WeaponFound,
+    AllEnemiesDied, Armor, BaseCamera, BossLaser, Buff, BuffAdded, BuffBundle, BuffGroup,
+    BuffGroupBundle, BulletBehaviour, BulletPool, Chest, ChestKind, CurrentGameLevel, Damage,
+    Direction, Elite, EliteExplosion, EnemyDied, EnemyHealthChanged, GameOver, Health,
+    Invulnerable, ItemTypeEnum, Luck, Mana, MaxHealth, MaxMana, PendingChestReward, Perception,
+    PlayerDealtDamage, PlayerHitAudioTimeout, PlayerManaChanged, PooledBullet, PowerInstance,
+    PowerRegistry, Prop, PropBroken, ScoreChanged, ScreenShake, ShieldDurability, SpritesResources,
+    StatKind, StatModifier, StatModifierOp, StatModifierSource, StatModifiers, Stealthed, TileKind,
+    Tilemap, UpdateAliveEnemiesUI, Weapon, WeaponFound,
 };
-use bevy::math::bounding::BoundingVolume;
+use bevy::math::bounding::BoundingCircle;
 
 pub fn check_for_offensive_buff_collisions_with_enemy(
     mut commands: Commands,
-    mut enemies: Query<(Entity, &Transform, &mut Health, &Damage, &Enemy), With<Enemy>>,
+    mut enemies: Query<
+        (
+            Entity,
+            &Transform,
+            &mut Health,
+            &Damage,
+            &Enemy,
+            Option<&mut Elite>,
+        ),
+        With<Enemy>,
+    >,
 
     player_query: Query<(&Transform, &Children), With<Player>>,
     player_buff_group_query: Query<(&Children, &BuffGroup)>,
-    player_buff_query: Query<(&Transform, &Buff)>,
+    player_buff_query: Query<(&Transform, &Buff, Option<&ShieldDurability>)>,
 
     base_camera: Query<(&Transform, &BaseCamera), Without<Player>>,
 ) {
@@ -43,19 +60,25 @@ pub fn check_for_offensive_buff_collisions_with_enemy(
         };
 
         for &player_buff_group_child in player_buff_group_children {
-            let Ok((player_buff_transform, player_buff)) =
+            let Ok((player_buff_transform, player_buff, shield_durability)) =
                 player_buff_query.get(player_buff_group_child)
             else {
                 continue;
             };
 
+            // Shattered shields do not hurt until they grow back
+            if shield_durability.is_some_and(|durability| durability.is_broken()) {
+                continue;
+            }
+
             match &player_buff.item {
                 // Speed and armor do not deal damage to the enemies
                 ItemTypeEnum::Speed(_)
                 | ItemTypeEnum::Armor(_)
                 | ItemTypeEnum::Health(_)
                 | ItemTypeEnum::Invisibility(_)
-                | ItemTypeEnum::Mana(_) => continue,
+                | ItemTypeEnum::Mana(_)
+                | ItemTypeEnum::Coins(_) => continue,
                 ItemTypeEnum::Shield(shield) => {
                     if shield.offensive == 0. {
                         continue;
@@ -74,8 +97,14 @@ pub fn check_for_offensive_buff_collisions_with_enemy(
                     let buff_collider =
                         Aabb2d::new(buff_center, Vec2::splat(BUFF_SPRITE_SIZE as f32 / 2.));
 
-                    for (enemy_entity, enemy_transform, mut enemy_health, enemy_damage, enemy) in
-                        enemies.iter_mut()
+                    for (
+                        enemy_entity,
+                        enemy_transform,
+                        mut enemy_health,
+                        enemy_damage,
+                        enemy,
+                        mut elite,
+                    ) in enemies.iter_mut()
                     {
                         let enemy_collider = Aabb2d::new(
                             enemy_transform.translation.truncate(),
@@ -94,6 +123,7 @@ pub fn check_for_offensive_buff_collisions_with_enemy(
                                 damage,
                                 enemy_damage,
                                 enemy.max_health,
+                                elite.as_deref_mut(),
                             );
                             continue;
                         }
@@ -108,9 +138,20 @@ pub fn check_for_ammo_collisions_with_enemy(
     mut commands: Commands,
     asset_server: Res<AssetServer>,
     ammos_query: Query<(Entity, &Transform, &Ammo), With<Ammo>>,
-    mut enemies: Query<(Entity, &Transform, &mut Health, &Damage, &Enemy), With<Enemy>>,
+    mut enemies: Query<
+        (
+            Entity,
+            &Transform,
+            &mut Health,
+            &Damage,
+            &Enemy,
+            Option<&mut Perception>,
+            Option<&mut Elite>,
+        ),
+        With<Enemy>,
+    >,
 
-    player_query: Query<&Children, With<Player>>,
+    player_query: Query<(&Children, Has<Stealthed>), With<Player>>,
     player_weapon_query: Query<(&Children, &Weapon, &Damage)>,
     player_ammo_query: Query<(Entity, &Ammo)>,
 ) {
@@ -120,7 +161,7 @@ pub fn check_for_ammo_collisions_with_enemy(
         return;
     }
 
-    let Ok(player_children) = player_query.get_single() else {
+    let Ok((player_children, player_stealthed)) = player_query.get_single() else {
         return;
     };
 
@@ -142,7 +183,15 @@ pub fn check_for_ammo_collisions_with_enemy(
     };
     let player_weapon_damage = player_weapon.2;
 
-    for (enemy_entity, enemy_transform, mut enemy_health, enemy_damage, enemy) in enemies.iter_mut()
+    for (
+        enemy_entity,
+        enemy_transform,
+        mut enemy_health,
+        enemy_damage,
+        enemy,
+        mut perception,
+        mut elite,
+    ) in enemies.iter_mut()
     {
         let enemy_collider = Aabb2d::new(
             enemy_transform.translation.truncate(),
@@ -176,14 +225,21 @@ pub fn check_for_ammo_collisions_with_enemy(
 
             if ammo_collider.intersects(&enemy_collider) {
                 hit_enemy_audio(&asset_server, &mut commands);
+                let damage = match perception.as_deref_mut() {
+                    Some(perception) => {
+                        perception.damage_taken(player_weapon_damage.0, player_stealthed)
+                    }
+                    None => player_weapon_damage.0,
+                };
                 damage_enemy_from_ammo_or_power(
                     &mut commands,
                     Some(ammo_entity),
                     enemy_entity,
                     &mut enemy_health,
-                    player_weapon_damage.0,
+                    damage,
                     enemy_damage,
                     enemy.max_health,
+                    elite.as_deref_mut(),
                 );
                 continue;
             }
@@ -191,11 +247,61 @@ pub fn check_for_ammo_collisions_with_enemy(
     }
 }
 
+pub fn check_for_ammo_collisions_with_props(
+    mut commands: Commands,
+    asset_server: Res<AssetServer>,
+    ammos_query: Query<(Entity, &Transform, &Ammo, &Damage, &Direction)>,
+    mut props: Query<(Entity, &Transform, &Prop, &mut Health)>,
+) {
+    for (ammo_entity, ammo_transform, ammo, ammo_damage, ammo_direction) in ammos_query.iter() {
+        // Only the ammo the player shot breaks props
+        if ammo.equipped_type != EquippedTypeEnum::Player
+            || ammo_direction.0.truncate() == Vec2::ZERO
+        {
+            continue;
+        }
+
+        let ammo_collider = Aabb2d::new(
+            ammo_transform.translation.truncate(),
+            Vec2::splat(AMMO_SPRITE_SIZE as f32 / 2.),
+        );
+
+        for (prop_entity, prop_transform, prop, mut prop_health) in props.iter_mut() {
+            if prop_health.0 <= 0. {
+                continue;
+            }
+
+            let prop_collider = Aabb2d::new(prop_transform.translation.truncate(), prop.half_size);
+            if !ammo_collider.intersects(&prop_collider) {
+                continue;
+            }
+
+            hit_enemy_audio(&asset_server, &mut commands);
+            commands.entity(ammo_entity).despawn_recursive();
+            prop_health.0 -= ammo_damage.0;
+
+            if prop_health.0 <= 0. {
+                commands.trigger(PropBroken {
+                    kind: prop.kind,
+                    position: prop_transform.translation.truncate(),
+                });
+                commands.entity(prop_entity).despawn();
+            }
+
+            break;
+        }
+    }
+}
+
 pub fn check_for_ammo_collisions_with_player(
     mut commands: Commands,
     asset_server: Res<AssetServer>,
     ammos_query: Query<(Entity, &Transform, &Ammo, &Damage), With<Ammo>>,
-    mut player_query: Query<(Entity, &Transform, &mut Health, &Armor), With<Player>>,
+    // Invulnerable players are ignored, e.g. while dashing
+    mut player_query: Query<
+        (Entity, &Transform, &mut Health, &Armor),
+        (With<Player>, Without<Invulnerable>),
+    >,
     base_camera: Query<(&Transform, &BaseCamera), Without<Player>>,
 ) {
     let Ok((player_entity, player_transform, mut player_health, player_armor)) =
@@ -245,13 +351,22 @@ pub fn check_for_player_collisions_to_enemy(
     asset_server: Res<AssetServer>,
     time: Res<Time>,
     mut audio_timeout: ResMut<PlayerHitAudioTimeout>,
-    mut enemies: Query<(&Transform, &Damage, &Enemy), With<Enemy>>,
-    mut player: Query<(&Transform, &Sprite, &mut Health, &Armor), With<Player>>,
+    mut enemies: Query<
+        (
+            Entity,
+            &Transform,
+            &Damage,
+            &Enemy,
+            &mut Health,
+            &Perception,
+            Option<&Elite>,
+        ),
+        (With<Enemy>, Without<Player>),
+    >,
+    mut player: Query<(&Transform, &mut Health, &Armor), (With<Player>, Without<Invulnerable>)>,
     base_camera: Query<(&Transform, &BaseCamera), Without<Player>>,
 ) {
-    let Ok((player_transform, player_sprite, mut player_health, player_armor)) =
-        player.get_single_mut()
-    else {
+    let Ok((player_transform, mut player_health, player_armor)) = player.get_single_mut() else {
         return;
     };
 
@@ -259,10 +374,6 @@ pub fn check_for_player_collisions_to_enemy(
         return;
     };
 
-    if !player_sprite.color.is_fully_opaque() {
-        return;
-    }
-
     // This gets the current player position on the world based on his
     // screen position.
     let player_center = Vec2::new(
@@ -271,7 +382,14 @@ pub fn check_for_player_collisions_to_enemy(
     );
     let player_collider = Aabb2d::new(player_center, Vec2::splat(PLAYER_SPRITE_SIZE as f32 / 2.));
 
-    for (enemy_transform, enemy_damage, enemy) in enemies.iter_mut() {
+    for (enemy_entity, enemy_transform, enemy_damage, enemy, mut enemy_health, perception, elite) in
+        enemies.iter_mut()
+    {
+        // Enemies can't attack a player they can't see
+        if !perception.can_see_player {
+            continue;
+        }
+
         let enemy_collider = Aabb2d::new(
             enemy_transform.translation.truncate(),
             Vec2::new(enemy.width_collider / 2., enemy.height_collider / 2.),
@@ -287,10 +405,321 @@ pub fn check_for_player_collisions_to_enemy(
                 player_armor.0,
                 enemy_damage.0,
             );
+
+            if elite.is_some_and(|elite| elite.has(EliteAffixEnum::Vampiric)) {
+                enemy_health.0 = (enemy_health.0 + enemy_damage.0 * ELITE_VAMPIRIC_LIFE_STEAL)
+                    .min(enemy.max_health);
+
+                commands.trigger(EnemyHealthChanged {
+                    health: enemy_health.0,
+                    max_health: enemy.max_health,
+                    entity: enemy_entity,
+                });
+            }
         }
     }
 }
 
+pub fn check_for_pooled_bullet_collisions_with_player(
+    mut commands: Commands,
+    asset_server: Res<AssetServer>,
+    mut pool: ResMut<BulletPool>,
+    mut bullets: Query<(Entity, &Transform, &mut PooledBullet, &mut Visibility)>,
+    // Invulnerable players are not hurt, but the bullets still land
+    mut player_query: Query<(&Transform, &mut Health, &Armor, Has<Invulnerable>), With<Player>>,
+    base_camera: Query<(&Transform, &BaseCamera), Without<Player>>,
+) {
+    let Ok((player_transform, mut player_health, player_armor, invulnerable)) =
+        player_query.get_single_mut()
+    else {
+        return;
+    };
+
+    let Ok((base_camera_transform, _)) = base_camera.get_single() else {
+        return;
+    };
+
+    let player_center = Vec2::new(
+        player_transform.translation.x + base_camera_transform.translation.x,
+        player_transform.translation.y + base_camera_transform.translation.y,
+    );
+    let player_collider = Aabb2d::new(player_center, Vec2::splat(PLAYER_SPRITE_SIZE as f32 / 2.));
+
+    for (bullet_entity, bullet_transform, mut bullet, mut visibility) in bullets.iter_mut() {
+        if !bullet.active {
+            continue;
+        }
+
+        let bullet_center = bullet_transform.translation.truncate();
+
+        let hit = match bullet.behaviour {
+            // Lobbed bullets only hurt when they land
+            BulletBehaviour::Lobbed { target, radius, .. } => {
+                if !bullet.lifetime.finished() {
+                    continue;
+                }
+
+                player_collider.closest_point(target).distance(target) <= radius
+            }
+            _ => BoundingCircle::new(bullet_center, BULLET_RADIUS).intersects(&player_collider),
+        };
+
+        if hit && !invulnerable {
+            hit_enemy_audio(&asset_server, &mut commands);
+            damage_player(
+                &mut commands,
+                &mut player_health,
+                player_armor.0,
+                bullet.damage,
+            );
+        }
+
+        if hit || bullet.lifetime.finished() {
+            pool.release(&mut commands, bullet_entity, &mut bullet, &mut visibility);
+        }
+    }
+}
+
+/// Orbiting shields stop the enemy projectiles they touch, losing as much
+/// durability as the damage they block.
+pub fn check_for_enemy_projectile_collisions_with_shields(
+    mut commands: Commands,
+    asset_server: Res<AssetServer>,
+    mut pool: ResMut<BulletPool>,
+    ammos_query: Query<(Entity, &Transform, &Ammo, &Damage), With<Ammo>>,
+    mut bullets: Query<(Entity, &Transform, &mut PooledBullet, &mut Visibility), Without<Buff>>,
+    player_query: Query<(Entity, &Transform, &Children), With<Player>>,
+    player_buff_group_query: Query<&Children, With<BuffGroup>>,
+    mut shields: Query<(&Transform, &mut ShieldDurability, &mut Visibility), With<Buff>>,
+    base_camera: Query<(&Transform, &BaseCamera), Without<Player>>,
+) {
+    let Ok((player_entity, player_transform, player_children)) = player_query.get_single() else {
+        return;
+    };
+
+    let Ok((base_camera_transform, _)) = base_camera.get_single() else {
+        return;
+    };
+
+    let mut blocked_ammos = vec![];
+
+    for &child in player_children {
+        let Ok(player_buff_group_children) = player_buff_group_query.get(child) else {
+            continue;
+        };
+
+        for &player_buff_group_child in player_buff_group_children {
+            let Ok((shield_transform, mut shield_durability, mut shield_visibility)) =
+                shields.get_mut(player_buff_group_child)
+            else {
+                continue;
+            };
+
+            if shield_durability.is_broken() {
+                continue;
+            }
+
+            // This gets the current shield position on the world based on
+            // its screen position.
+            let shield_center = player_transform.translation.truncate()
+                + shield_transform.translation.truncate()
+                + base_camera_transform.translation.truncate();
+            let shield_collider =
+                Aabb2d::new(shield_center, Vec2::splat(BUFF_SPRITE_SIZE as f32 / 2.));
+
+            let mut blocked_damage = 0.;
+
+            for (ammo_entity, ammo_transform, ammo, ammo_damage) in ammos_query.iter() {
+                if ammo.equipped_by == player_entity || blocked_ammos.contains(&ammo_entity) {
+                    continue;
+                }
+
+                let ammo_collider = Aabb2d::new(
+                    ammo_transform.translation.truncate(),
+                    Vec2::splat(AMMO_SPRITE_SIZE as f32 / 2.),
+                );
+
+                if ammo_collider.intersects(&shield_collider) {
+                    blocked_damage += ammo_damage.0;
+                    blocked_ammos.push(ammo_entity);
+                    commands.entity(ammo_entity).despawn_recursive();
+                }
+            }
+
+            for (bullet_entity, bullet_transform, mut bullet, mut visibility) in bullets.iter_mut()
+            {
+                // Lobbed bullets fly over the shields
+                if !bullet.active || matches!(bullet.behaviour, BulletBehaviour::Lobbed { .. }) {
+                    continue;
+                }
+
+                let bullet_collider =
+                    BoundingCircle::new(bullet_transform.translation.truncate(), BULLET_RADIUS);
+
+                if bullet_collider.intersects(&shield_collider) {
+                    blocked_damage += bullet.damage;
+                    pool.release(&mut commands, bullet_entity, &mut bullet, &mut visibility);
+                }
+            }
+
+            if blocked_damage == 0. {
+                continue;
+            }
+
+            hit_item_audio(&asset_server, &mut commands);
+            shield_durability.durability -= blocked_damage;
+
+            if shield_durability.is_broken() {
+                shatter_shield(
+                    &mut commands,
+                    &mut shield_visibility,
+                    &mut shield_durability,
+                    shield_center,
+                );
+            }
+        }
+    }
+}
+
+pub fn check_for_hazard_tiles_under_player(
+    mut commands: Commands,
+    tilemap: Option<Res<Tilemap>>,
+    // Only hurts, so invulnerable players are skipped
+    mut player_query: Query<
+        (&Transform, &mut Health, &Armor),
+        (With<Player>, Without<Invulnerable>),
+    >,
+    base_camera: Query<(&Transform, &BaseCamera), Without<Player>>,
+) {
+    let Some(tilemap) = tilemap else {
+        return;
+    };
+
+    let Ok((player_transform, mut player_health, player_armor)) = player_query.get_single_mut()
+    else {
+        return;
+    };
+
+    let Ok((base_camera_transform, _)) = base_camera.get_single() else {
+        return;
+    };
+
+    let player_center =
+        player_transform.translation.truncate() + base_camera_transform.translation.truncate();
+
+    if tilemap.tile_at(player_center) == TileKind::Hazard {
+        damage_player(
+            &mut commands,
+            &mut player_health,
+            player_armor.0,
+            HAZARD_TILE_DAMAGE,
+        );
+    }
+}
+
+pub fn check_for_elite_explosions_with_player(
+    mut commands: Commands,
+    time: Res<Time>,
+    mut explosions: Query<(Entity, &Transform, &mut EliteExplosion)>,
+    // Invulnerable players are not hurt, but the explosions still go off
+    mut player_query: Query<(&Transform, &mut Health, &Armor, Has<Invulnerable>), With<Player>>,
+    base_camera: Query<(&Transform, &BaseCamera), Without<Player>>,
+) {
+    let Ok((player_transform, mut player_health, player_armor, invulnerable)) =
+        player_query.get_single_mut()
+    else {
+        return;
+    };
+
+    let Ok((base_camera_transform, _)) = base_camera.get_single() else {
+        return;
+    };
+
+    let player_center = Vec2::new(
+        player_transform.translation.x + base_camera_transform.translation.x,
+        player_transform.translation.y + base_camera_transform.translation.y,
+    );
+    let player_collider = Aabb2d::new(player_center, Vec2::splat(PLAYER_SPRITE_SIZE as f32 / 2.));
+
+    for (explosion_entity, explosion_transform, mut explosion) in explosions.iter_mut() {
+        // The explosion only hurts once, then it stays a bit on the screen
+        if !explosion.exploded {
+            explosion.exploded = true;
+            commands.trigger(ScreenShake {
+                trauma: CAMERA_EXPLOSION_TRAUMA,
+            });
+
+            let explosion_collider = BoundingCircle::new(
+                explosion_transform.translation.truncate(),
+                ELITE_EXPLOSION_RADIUS,
+            );
+
+            if !invulnerable && explosion_collider.intersects(&player_collider) {
+                damage_player(
+                    &mut commands,
+                    &mut player_health,
+                    player_armor.0,
+                    explosion.damage,
+                );
+            }
+        }
+
+        explosion.timer.tick(time.delta());
+        if explosion.timer.finished() {
+            commands.entity(explosion_entity).despawn();
+        }
+    }
+}
+
+pub fn check_for_boss_laser_collisions_with_player(
+    mut commands: Commands,
+    time: Res<Time>,
+    lasers: Query<&BossLaser>,
+    // Only hurts, so invulnerable players are skipped
+    mut player_query: Query<
+        (&Transform, &mut Health, &Armor),
+        (With<Player>, Without<Invulnerable>),
+    >,
+    base_camera: Query<(&Transform, &BaseCamera), Without<Player>>,
+) {
+    let Ok((player_transform, mut player_health, player_armor)) = player_query.get_single_mut()
+    else {
+        return;
+    };
+
+    let Ok((base_camera_transform, _)) = base_camera.get_single() else {
+        return;
+    };
+
+    // This gets the current player position on the world based on his
+    // screen position.
+    let player_center = Vec2::new(
+        player_transform.translation.x + base_camera_transform.translation.x,
+        player_transform.translation.y + base_camera_transform.translation.y,
+    );
+
+    for laser in lasers.iter() {
+        // Distance from the player to the laser segment
+        let laser_direction = Vec2::from_angle(laser.angle);
+        let projection = (player_center - laser.origin)
+            .dot(laser_direction)
+            .clamp(0., BOSS_LASER_LENGTH);
+        let closest_point = laser.origin + laser_direction * projection;
+
+        let hit_distance = PLAYER_SPRITE_SIZE as f32 / 2. + BOSS_LASER_WIDTH / 2.;
+        if closest_point.distance(player_center) > hit_distance {
+            continue;
+        }
+
+        damage_player(
+            &mut commands,
+            &mut player_health,
+            player_armor.0,
+            laser.damage_per_second * time.delta_seconds(),
+        );
+    }
+}
+
 /// Item collision with the player
 pub fn check_for_item_collisions(
     mut commands: Commands,
@@ -301,11 +730,12 @@ pub fn check_for_item_collisions(
     mut player: Query<
         (
             &Transform,
-            &mut Speed,
-            &mut Armor,
+            &mut StatModifiers,
             Entity,
             &mut Health,
             &mut Mana,
+            &MaxHealth,
+            &MaxMana,
             &mut Sprite,
         ),
         With<Player>,
@@ -319,11 +749,12 @@ pub fn check_for_item_collisions(
 
     let Ok((
         player_transform,
-        mut player_speed,
-        mut player_armor,
+        mut player_stat_modifiers,
         player_entity,
         mut player_health,
         mut player_mana,
+        player_max_health,
+        player_max_mana,
         mut player_sprite,
     )) = player.get_single_mut()
     else {
@@ -347,16 +778,24 @@ pub fn check_for_item_collisions(
         if player_collider.intersects(&item_collider) {
             match &item.item_type {
                 ItemTypeEnum::Speed(speed) => {
-                    player_speed.0 += speed.0;
+                    player_stat_modifiers.add(StatModifier::new(
+                        StatKind::Speed,
+                        StatModifierOp::Add(speed.0),
+                        "Speed item",
+                    ));
                 }
                 ItemTypeEnum::Armor(armor) => {
-                    player_armor.0 += armor.0;
+                    player_stat_modifiers.add(StatModifier::new(
+                        StatKind::Armor,
+                        StatModifierOp::Add(armor.0),
+                        "Armor item",
+                    ));
                 }
                 ItemTypeEnum::Health(health) => {
                     let mut new_health = player_health.0 + health.0;
 
-                    if new_health > PLAYER_HEALTH {
-                        new_health = PLAYER_HEALTH;
+                    if new_health > player_max_health.0 {
+                        new_health = player_max_health.0;
                     }
 
                     player_health.0 = new_health;
@@ -368,8 +807,8 @@ pub fn check_for_item_collisions(
                 ItemTypeEnum::Mana(mana) => {
                     let mut new_mana = player_mana.0 + mana.0;
 
-                    if new_mana > PLAYER_MANA {
-                        new_mana = PLAYER_MANA;
+                    if new_mana > player_max_mana.0 {
+                        new_mana = player_max_mana.0;
                     }
 
                     player_mana.0 = new_mana;
@@ -379,23 +818,21 @@ pub fn check_for_item_collisions(
                     });
                 }
                 ItemTypeEnum::Shield(shield) => {
-                    // TODO: check for shield type (magical vs physical)
-                    if shield.defensive > 0. {
-                        player_armor.0 += shield.defensive * NUMBER_OF_BUFF_ITEMS as f32;
-                    }
-
                     // Add new buff to player
                     let layer = PLAYER_LAYER;
                     let scale = Vec3::splat(0.5);
-                    let pos = Vec3::new(RADIUS_FROM_PLAYER, RADIUS_FROM_PLAYER, 0.0);
+                    let pos = Vec3::new(shield.radius, shield.radius, 0.0);
                     let visibility = Visibility::Visible;
 
                     let buff_group_bundle =
                         BuffGroupBundle::new(item.item_type.clone(), layer.clone());
 
+                    let mut buff_group_entity = None;
                     commands.entity(player_entity).with_children(|parent| {
-                        parent.spawn(buff_group_bundle).with_children(|parent| {
-                            for _ in 0..NUMBER_OF_BUFF_ITEMS {
+                        let mut buff_group = parent.spawn(buff_group_bundle);
+                        buff_group_entity = Some(buff_group.id());
+                        buff_group.with_children(|parent| {
+                            for _ in 0..shield.count {
                                 let buff_bundle = BuffBundle::new(
                                     &mut texture_atlas_layout,
                                     &sprites,
@@ -406,12 +843,32 @@ pub fn check_for_item_collisions(
                                     layer.clone(),
                                     visibility,
                                 );
-                                parent.spawn(buff_bundle);
+                                parent.spawn((buff_bundle, ShieldDurability::new(shield)));
                             }
                         });
                     });
+
+                    // TODO: check for shield type (magical vs physical)
+                    // The armor is removed with the buff group
+                    if let Some(buff_group_entity) =
+                        buff_group_entity.filter(|_| shield.defensive > 0.)
+                    {
+                        player_stat_modifiers.add(StatModifier::new(
+                            StatKind::Armor,
+                            StatModifierOp::Add(shield.defensive * shield.count as f32),
+                            StatModifierSource::Shield(buff_group_entity),
+                        ));
+                    }
+                }
+                ItemTypeEnum::Coins(coins) => {
+                    commands.trigger(ScoreChanged {
+                        score: coins.0 as f32 * COIN_SCORE,
+                    });
                 }
                 ItemTypeEnum::Invisibility(_invisibility) => {
+                    // Enemies need to be much closer to see the player
+                    commands.entity(player_entity).insert(Stealthed);
+
                     // make player "invisible"
                     let mut player_color = player_sprite.color.to_srgba();
                     player_color.alpha = 0.5;
@@ -461,6 +918,63 @@ pub fn check_for_item_collisions(
     }
 }
 
+pub fn check_for_chest_collisions(
+    mut commands: Commands,
+    asset_server: Res<AssetServer>,
+    mut texture_atlas_layout: ResMut<Assets<TextureAtlasLayout>>,
+    sprites: Res<SpritesResources>,
+    current_game_level: Res<CurrentGameLevel>,
+    pending_reward: Option<Res<PendingChestReward>>,
+    player_query: Query<(&Transform, &Luck), With<Player>>,
+    chests: Query<(Entity, &Transform, &Chest), Without<Player>>,
+    base_camera: Query<(&Transform, &BaseCamera), (Without<Player>, Without<Chest>)>,
+) {
+    let Ok((player_transform, player_luck)) = player_query.get_single() else {
+        return;
+    };
+
+    let Ok((base_camera_transform, _)) = base_camera.get_single() else {
+        return;
+    };
+
+    let player_center =
+        player_transform.translation.truncate() + base_camera_transform.translation.truncate();
+    let player_collider = Aabb2d::new(player_center, Vec2::splat(PLAYER_SPRITE_SIZE as f32 / 2.));
+    let mut is_choosing_reward = pending_reward.is_some();
+
+    for (chest_entity, chest_transform, chest) in chests.iter() {
+        let chest_collider = Aabb2d::new(
+            chest_transform.translation.truncate(),
+            Vec2::splat(ITEM_SPRITE_SIZE as f32 / 2.),
+        );
+
+        if !player_collider.intersects(&chest_collider) {
+            continue;
+        }
+
+        match chest.kind {
+            ChestKind::Loot => open_chest(
+                &mut commands,
+                &mut texture_atlas_layout,
+                &sprites,
+                &asset_server,
+                chest_transform.translation.truncate(),
+                current_game_level.0,
+                get_luck(player_luck.0, current_game_level.0),
+            ),
+            // Only one reward is chosen at a time, the chest waits
+            ChestKind::Reward if is_choosing_reward => continue,
+            ChestKind::Reward => {
+                open_reward_chest(&mut commands, &asset_server);
+                is_choosing_reward = true;
+            }
+        }
+
+        hit_item_audio(&asset_server, &mut commands);
+        commands.entity(chest_entity).despawn();
+    }
+}
+
 /// Player with weapon
 pub fn check_for_weapon_collisions(
     mut commands: Commands,
@@ -548,97 +1062,91 @@ pub fn check_for_weapon_collisions(
 
 pub fn check_for_power_collisions_with_enemy(
     mut commands: Commands,
-    mut enemies: Query<(Entity, &Transform, &mut Health, &Damage, &Enemy), With<Enemy>>,
-
-    player_query: Query<(&Children, &Player)>,
-    player_powers_query: Query<(Entity, &Power)>,
+    mut enemies: Query<
+        (
+            Entity,
+            &Transform,
+            &mut Health,
+            &Damage,
+            &Enemy,
+            Option<&mut Perception>,
+            Option<&mut Elite>,
+        ),
+        With<Enemy>,
+    >,
 
-    powers_query: Query<(Entity, &Transform, &Damage), With<Power>>,
-    laser_query: Query<&Laser, With<Laser>>,
+    mut powers_query: Query<(&Transform, &mut PowerInstance)>,
+    player_query: Query<Has<Stealthed>, With<Player>>,
+    registry: Res<PowerRegistry>,
 ) {
     let number_of_enemies = enemies.iter().len();
     if number_of_enemies == 0 {
         return;
     }
 
-    let Ok((player_children, _)) = player_query.get_single() else {
-        return;
-    };
+    let player_stealthed = player_query.get_single().unwrap_or(false);
 
-    let mut current_player_powers_entity: Vec<Entity> = vec![];
-    for &child in player_children {
-        if let Ok(player_powers) = player_powers_query.get(child) {
-            current_player_powers_entity.push(player_powers.0);
-        }
-    }
+    for (power_transform, mut power) in powers_query.iter_mut() {
+        let Some(behaviour) = registry.get(power.power_type) else {
+            continue;
+        };
 
-    for (enemy_entity, enemy_transform, mut enemy_health, enemy_damage, enemy) in enemies.iter_mut()
-    {
-        let enemy_collider = Aabb2d::new(
-            enemy_transform.translation.truncate(),
-            Vec2::new(enemy.width_collider / 2., enemy.height_collider / 2.),
-        );
+        for (
+            enemy_entity,
+            enemy_transform,
+            mut enemy_health,
+            enemy_damage,
+            enemy,
+            mut perception,
+            mut elite,
+        ) in enemies.iter_mut()
+        {
+            // It will be despawned by `expire_powers`
+            if power.has_expired() {
+                break;
+            }
 
-        // Check for collision for the laser power
-        let cos_45 = 2.0_f32.sqrt() / 2.;
-        let sin_45 = 2.0_f32.sqrt() / 2.;
-        for laser in laser_query.iter() {
-            let Laser {
-                center_position,
-                damage,
-                ..
-            } = laser;
+            if power.hit_each_enemy_once && power.enemies_hit.contains(&enemy_entity) {
+                continue;
+            }
 
-            let mut laser_collider = Aabb2d::new(
-                Vec2::ZERO,
-                Vec2::new(LASER_POWER_WIDTH / 2., LASER_POWER_HEIGHT / 2.),
+            let enemy_collider = Aabb2d::new(
+                enemy_transform.translation.truncate(),
+                Vec2::new(enemy.width_collider / 2., enemy.height_collider / 2.),
             );
-            let rotation_matrix = Rot2::from_sin_cos(sin_45, cos_45);
-            let translation_matrix = center_position.truncate();
 
-            laser_collider.transform_by(translation_matrix, rotation_matrix);
+            if !behaviour.collides(&power, power_transform, &enemy_collider) {
+                continue;
+            }
 
-            if laser_collider.intersects(&enemy_collider) {
-                damage_enemy_from_ammo_or_power(
-                    &mut commands,
-                    None,
-                    enemy_entity,
-                    &mut enemy_health,
-                    *damage,
-                    enemy_damage,
-                    enemy.max_health,
-                );
+            power.hits += 1;
+            if power.hit_each_enemy_once {
+                power.enemies_hit.push(enemy_entity);
             }
-        }
 
-        // Check for collision for the explosions power
-        for (power_entity, power_transform, power_damage) in powers_query.iter() {
-            // if current power is from player, do not collide it
-            if current_player_powers_entity.contains(&power_entity) {
+            let Some(damage) = behaviour.on_hit(&power) else {
                 continue;
-            }
+            };
 
-            let power_collider = Aabb2d::new(
-                power_transform.translation.truncate(),
-                Vec2::new(
-                    (POWER_SPRITE_SIZE / 2) as f32,
-                    (POWER_SPRITE_SIZE / 2) as f32,
-                ),
+            let damage = match perception.as_deref_mut() {
+                Some(perception) => perception.damage_taken(damage, player_stealthed),
+                None => damage,
+            };
+            damage_enemy_from_ammo_or_power(
+                &mut commands,
+                None,
+                enemy_entity,
+                &mut enemy_health,
+                damage,
+                enemy_damage,
+                enemy.max_health,
+                // Piercing powers go through the shields
+                if power.pierce {
+                    None
+                } else {
+                    elite.as_deref_mut()
+                },
             );
-
-            let collides = power_collider.intersects(&enemy_collider);
-
-            if collides {
-                damage_enemy_from_ammo_or_power(
-                    &mut commands,
-                    Some(power_entity),
-                    enemy_entity,
-                    &mut enemy_health,
-                    power_damage.0,
-                    enemy_damage,
-                    enemy.max_health,
-                );
-            }
         }
     }
 }
@@ -651,6 +1159,7 @@ pub(crate) fn damage_enemy_from_ammo_or_power(
     damage: f32,
     enemy_damage: &Damage,
     enemy_max_health: f32,
+    elite: Option<&mut Elite>,
 ) {
     if let Some(entity) = ammo_or_power_entity {
         commands.entity(entity).despawn();
@@ -662,6 +1171,7 @@ pub(crate) fn damage_enemy_from_ammo_or_power(
         damage,
         enemy_damage,
         enemy_max_health,
+        elite,
     );
 }
 
@@ -672,10 +1182,26 @@ fn damage_enemy(
     damage: f32,
     enemy_damage: &Damage,
     enemy_max_health: f32,
+    elite: Option<&mut Elite>,
 ) {
+    let damage = match elite {
+        Some(elite) => elite.absorb_damage(damage),
+        None => damage,
+    };
+
+    // Only what the enemy had left counts for the life steal
+    commands.trigger(PlayerDealtDamage {
+        damage: damage.min(enemy_health.0.max(0.)),
+        entity: enemy_entity,
+    });
+
     enemy_health.0 -= damage;
 
     if enemy_health.0 <= 0. {
+        // Must go before the despawn so the observers can read the enemy
+        commands.trigger(EnemyDied {
+            entity: enemy_entity,
+        });
         commands.entity(enemy_entity).despawn_recursive();
         // INFO: we use the damage of the enemy to how much points the player
         // will get
@@ -683,8 +1209,6 @@ fn damage_enemy(
             score: enemy_damage.0,
         });
 
-        commands.trigger(MaybeSpawnHealthPack);
-        commands.trigger(MaybeSpawnManaPack);
         commands.trigger(UpdateAliveEnemiesUI);
     }
 
@@ -715,6 +1239,9 @@ fn damage_player(
 
     player_health.0 = new_player_health;
 
+    commands.trigger(ScreenShake {
+        trauma: (new_damage * CAMERA_HIT_TRAUMA_PER_DAMAGE).min(CAMERA_HIT_MAX_TRAUMA),
+    });
     commands.trigger(PlayerHealthChanged {
         health: player_health.0,
     });
@@ -0,0 +1,306 @@
+// Time the boss waits after spawning before its first attack
+pub(crate) const BOSS_FIRST_ATTACK_DELAY_SECONDS: f32 = 3.0;
+// Once enraged, the cooldown between attacks is multiplied by this
+pub(crate) const BOSS_ENRAGE_COOLDOWN_MULTIPLIER: f32 = 0.5;
+pub(crate) const BOSS_TELEGRAPH_RADIUS: f32 = 80.0;
+pub(crate) const BOSS_LASER_LENGTH: f32 = 600.0;
+pub(crate) const BOSS_LASER_WIDTH: f32 = 6.0;
+// Each ring is rotated by this (in radians) so two rings in a row do not
+// leave the same gaps
+pub(crate) const BOSS_PROJECTILE_RING_ROTATION_OFFSET: f32 = 0.15;
+
+// Creeps summoned by the bosses
+pub(crate) const BOSS_SUMMON_DAMAGE: f32 = 25.0;
+pub(crate) const BOSS_SUMMON_SCALE: f32 = 2.5;
+
+// Intro: the camera goes to the boss, stays there while the title card is
+// shown and then comes back to the player.
+pub(crate) const BOSS_INTRO_PAN_SECONDS: f32 = 0.8;
+pub(crate) const BOSS_INTRO_HOLD_SECONDS: f32 = 1.6;
+// The cameras zoom out to show the boss and the player together
+pub(crate) const BOSS_INTRO_CAMERA_ZOOM: f32 = 1.4;
+
+// Screen-wide health bar
+pub(crate) const BOSS_HEALTH_BAR_HEIGHT_PX: f32 = 18.0;
+pub(crate) const BOSS_HEALTH_BAR_WIDTH_PERCENT: f32 = 60.0;
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum BossAttackEnum {
+    // Shoots `count` projectiles evenly spaced around the boss
+    ProjectileRing {
+        count: u32,
+    },
+    // Summons `quantity` orcs around the boss
+    SummonWave {
+        quantity: u32,
+    },
+    // Moves the boss to a random place of the map
+    Teleport,
+    // Rotates a laser around the boss starting aimed at the player
+    LaserSweep {
+        duration_seconds: f32,
+        arc_degrees: f32,
+    },
+    // Fires one of the bullet patterns, chosen by name
+    BulletPattern {
+        name: &'static str,
+    },
+}
+
+#[derive(Debug, Clone, Copy)]
+pub struct BossAttack {
+    pub attack: BossAttackEnum,
+    // How much of the boss base damage this attack deals
+    pub damage_multiplier: f32,
+    // For how long the attack is shown before it actually happens
+    pub telegraph_seconds: f32,
+    // Time to wait after this attack before starting the next one
+    pub cooldown_seconds: f32,
+}
+
+#[derive(Debug, Clone, Copy)]
+pub struct BossPhase {
+    // The phase starts once the boss health goes below this fraction of
+    // its max health. The first phase should always be 1.0.
+    pub health_threshold: f32,
+    // Attacks are done in order and start again once the last one is done
+    pub attacks: &'static [BossAttack],
+}
+
+#[derive(Debug, Clone, Copy)]
+pub struct BossMoveset {
+    pub name: &'static str,
+    pub phases: &'static [BossPhase],
+    // After this time the boss gets angry: more damage and less cooldown
+    pub enrage_seconds: f32,
+    pub enrage_damage_multiplier: f32,
+}
+
+pub(crate) const BOSS_ORC_MOVESET: BossMoveset = BossMoveset {
+    name: "Orc Warlord",
+    phases: &[
+        BossPhase {
+            health_threshold: 1.0,
+            attacks: &[
+                BossAttack {
+                    attack: BossAttackEnum::SummonWave { quantity: 2 },
+                    damage_multiplier: 0.0,
+                    telegraph_seconds: 1.0,
+                    cooldown_seconds: 4.0,
+                },
+                BossAttack {
+                    attack: BossAttackEnum::ProjectileRing { count: 8 },
+                    damage_multiplier: 0.5,
+                    telegraph_seconds: 0.8,
+                    cooldown_seconds: 4.0,
+                },
+            ],
+        },
+        BossPhase {
+            health_threshold: 0.5,
+            attacks: &[
+                BossAttack {
+                    attack: BossAttackEnum::SummonWave { quantity: 4 },
+                    damage_multiplier: 0.0,
+                    telegraph_seconds: 1.0,
+                    cooldown_seconds: 3.0,
+                },
+                BossAttack {
+                    attack: BossAttackEnum::ProjectileRing { count: 12 },
+                    damage_multiplier: 0.5,
+                    telegraph_seconds: 0.6,
+                    cooldown_seconds: 1.0,
+                },
+                BossAttack {
+                    attack: BossAttackEnum::ProjectileRing { count: 12 },
+                    damage_multiplier: 0.5,
+                    telegraph_seconds: 0.4,
+                    cooldown_seconds: 3.0,
+                },
+                BossAttack {
+                    attack: BossAttackEnum::BulletPattern { name: "mortar" },
+                    damage_multiplier: 1.0,
+                    telegraph_seconds: 0.6,
+                    cooldown_seconds: 2.5,
+                },
+            ],
+        },
+    ],
+    enrage_seconds: 90.0,
+    enrage_damage_multiplier: 1.5,
+};
+
+pub(crate) const BOSS_MAGE_MOVESET: BossMoveset = BossMoveset {
+    name: "Archmage",
+    phases: &[
+        BossPhase {
+            health_threshold: 1.0,
+            attacks: &[
+                BossAttack {
+                    attack: BossAttackEnum::ProjectileRing { count: 12 },
+                    damage_multiplier: 0.4,
+                    telegraph_seconds: 0.8,
+                    cooldown_seconds: 2.5,
+                },
+                BossAttack {
+                    attack: BossAttackEnum::Teleport,
+                    damage_multiplier: 0.0,
+                    telegraph_seconds: 1.2,
+                    cooldown_seconds: 2.5,
+                },
+            ],
+        },
+        BossPhase {
+            health_threshold: 0.6,
+            attacks: &[
+                BossAttack {
+                    attack: BossAttackEnum::LaserSweep {
+                        duration_seconds: 2.0,
+                        arc_degrees: 90.0,
+                    },
+                    damage_multiplier: 1.0,
+                    telegraph_seconds: 1.0,
+                    cooldown_seconds: 2.0,
+                },
+                BossAttack {
+                    attack: BossAttackEnum::ProjectileRing { count: 16 },
+                    damage_multiplier: 0.4,
+                    telegraph_seconds: 0.6,
+                    cooldown_seconds: 2.0,
+                },
+                BossAttack {
+                    attack: BossAttackEnum::Teleport,
+                    damage_multiplier: 0.0,
+                    telegraph_seconds: 1.0,
+                    cooldown_seconds: 2.0,
+                },
+            ],
+        },
+        BossPhase {
+            health_threshold: 0.25,
+            attacks: &[
+                BossAttack {
+                    attack: BossAttackEnum::Teleport,
+                    damage_multiplier: 0.0,
+                    telegraph_seconds: 0.6,
+                    cooldown_seconds: 0.5,
+                },
+                BossAttack {
+                    attack: BossAttackEnum::LaserSweep {
+                        duration_seconds: 3.0,
+                        arc_degrees: 360.0,
+                    },
+                    damage_multiplier: 1.0,
+                    telegraph_seconds: 1.0,
+                    cooldown_seconds: 1.5,
+                },
+                BossAttack {
+                    attack: BossAttackEnum::ProjectileRing { count: 24 },
+                    damage_multiplier: 0.4,
+                    telegraph_seconds: 0.5,
+                    cooldown_seconds: 2.0,
+                },
+                BossAttack {
+                    attack: BossAttackEnum::BulletPattern { name: "spiral" },
+                    damage_multiplier: 1.0,
+                    telegraph_seconds: 0.6,
+                    cooldown_seconds: 1.5,
+                },
+            ],
+        },
+    ],
+    enrage_seconds: 120.0,
+    enrage_damage_multiplier: 1.5,
+};
+
+pub(crate) const BOSS_ALIEN_MOVESET: BossMoveset = BossMoveset {
+    name: "Grey Overseer",
+    phases: &[
+        BossPhase {
+            health_threshold: 1.0,
+            attacks: &[
+                BossAttack {
+                    attack: BossAttackEnum::LaserSweep {
+                        duration_seconds: 2.0,
+                        arc_degrees: 120.0,
+                    },
+                    damage_multiplier: 1.0,
+                    telegraph_seconds: 1.0,
+                    cooldown_seconds: 2.0,
+                },
+                BossAttack {
+                    attack: BossAttackEnum::ProjectileRing { count: 10 },
+                    damage_multiplier: 0.5,
+                    telegraph_seconds: 0.8,
+                    cooldown_seconds: 2.5,
+                },
+            ],
+        },
+        BossPhase {
+            health_threshold: 0.66,
+            attacks: &[
+                BossAttack {
+                    attack: BossAttackEnum::Teleport,
+                    damage_multiplier: 0.0,
+                    telegraph_seconds: 1.0,
+                    cooldown_seconds: 0.5,
+                },
+                BossAttack {
+                    attack: BossAttackEnum::LaserSweep {
+                        duration_seconds: 2.5,
+                        arc_degrees: 180.0,
+                    },
+                    damage_multiplier: 1.0,
+                    telegraph_seconds: 1.0,
+                    cooldown_seconds: 1.5,
+                },
+                BossAttack {
+                    attack: BossAttackEnum::SummonWave { quantity: 3 },
+                    damage_multiplier: 0.0,
+                    telegraph_seconds: 1.0,
+                    cooldown_seconds: 2.5,
+                },
+            ],
+        },
+        BossPhase {
+            health_threshold: 0.33,
+            attacks: &[
+                BossAttack {
+                    attack: BossAttackEnum::Teleport,
+                    damage_multiplier: 0.0,
+                    telegraph_seconds: 0.6,
+                    cooldown_seconds: 0.3,
+                },
+                BossAttack {
+                    attack: BossAttackEnum::ProjectileRing { count: 20 },
+                    damage_multiplier: 0.5,
+                    telegraph_seconds: 0.5,
+                    cooldown_seconds: 1.0,
+                },
+                BossAttack {
+                    attack: BossAttackEnum::LaserSweep {
+                        duration_seconds: 3.0,
+                        arc_degrees: 360.0,
+                    },
+                    damage_multiplier: 1.2,
+                    telegraph_seconds: 1.0,
+                    cooldown_seconds: 1.0,
+                },
+                BossAttack {
+                    attack: BossAttackEnum::SummonWave { quantity: 5 },
+                    damage_multiplier: 0.0,
+                    telegraph_seconds: 1.0,
+                    cooldown_seconds: 2.5,
+                },
+                BossAttack {
+                    attack: BossAttackEnum::BulletPattern { name: "mortar" },
+                    damage_multiplier: 1.0,
+                    telegraph_seconds: 0.6,
+                    cooldown_seconds: 2.0,
+                },
+            ],
+        },
+    ],
+    enrage_seconds: 150.0,
+    enrage_damage_multiplier: 2.0,
+};
@@ -1,3 +1,14 @@
 pub(crate) const RADIUS_FROM_PLAYER: f32 = 35.;
-pub(crate) const NUMBER_OF_BUFF_ITEMS: usize = 12;
+pub(crate) const NUMBER_OF_BUFF_ITEMS: u32 = 12;
 pub(crate) const BUFF_SPRITE_SIZE: u8 = 16;
+
+// Degrees per second
+pub(crate) const SHIELD_ORBIT_SPEED: f32 = 60.;
+pub(crate) const SHIELD_DURABILITY: f32 = 20.;
+pub(crate) const SHIELD_REGROW_SECONDS: f32 = 6.;
+
+// Pieces left when a shield shatters
+pub(crate) const SHIELD_SHARDS: u32 = 5;
+pub(crate) const SHIELD_SHARD_SIZE: f32 = 4.;
+pub(crate) const SHIELD_SHARD_SPEED: f32 = 120.;
+pub(crate) const SHIELD_SHARD_SECONDS: f32 = 0.4;
@@ -0,0 +1,148 @@
+// Bullets that are created when the game starts. The pool grows if a pattern
+// needs more than this.
+pub(crate) const BULLET_POOL_INITIAL_SIZE: usize = 256;
+pub(crate) const BULLET_RADIUS: f32 = 5.0;
+// Bullets that do not hit anything go back to the pool after this
+pub(crate) const BULLET_LIFETIME_SECONDS: f32 = 6.0;
+// How much bigger a lobbed bullet looks at the top of its arc
+pub(crate) const BULLET_LOBBED_MAX_SCALE: f32 = 2.5;
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum BulletPatternEnum {
+    // `count` bullets evenly spread on an arc aimed at the player
+    SpreadFan {
+        count: u32,
+        arc_degrees: f32,
+    },
+    // `count` bullets evenly spaced around the shooter
+    Ring {
+        count: u32,
+    },
+    // Like a ring, but each volley is rotated a bit more than the last one
+    Spiral {
+        arms: u32,
+        rotation_per_volley_degrees: f32,
+    },
+    // One bullet aimed at the player per volley
+    AimedBurst,
+    // Slow orbs that start chasing the player after `delay_seconds`
+    HomingOrb {
+        count: u32,
+        delay_seconds: f32,
+        turn_rate_degrees: f32,
+    },
+    // Shells that land where the player was, hurting everything in `radius`.
+    // The landing spot is shown on the ground while they are flying.
+    LobbedAoe {
+        count: u32,
+        radius: f32,
+        flight_seconds: f32,
+    },
+}
+
+#[derive(Debug, Clone, Copy)]
+pub struct BulletPattern {
+    // Enemies and bosses choose the pattern by this name
+    pub name: &'static str,
+    pub pattern: BulletPatternEnum,
+    pub speed: f32,
+    // How much of the shooter damage each bullet deals
+    pub damage_multiplier: f32,
+    // A pattern is made of `volleys` shots, one every `volley_interval_seconds`
+    pub volleys: u32,
+    pub volley_interval_seconds: f32,
+    // Time to wait after the last volley to start the pattern again
+    pub cooldown_seconds: f32,
+}
+
+pub(crate) const BULLET_PATTERNS: [BulletPattern; 8] = [
+    BulletPattern {
+        name: "aimed_shot",
+        pattern: BulletPatternEnum::AimedBurst,
+        speed: 250.0,
+        damage_multiplier: 1.0,
+        volleys: 1,
+        volley_interval_seconds: 0.0,
+        cooldown_seconds: 2.0,
+    },
+    BulletPattern {
+        name: "aimed_burst",
+        pattern: BulletPatternEnum::AimedBurst,
+        speed: 300.0,
+        damage_multiplier: 0.6,
+        volleys: 4,
+        volley_interval_seconds: 0.15,
+        cooldown_seconds: 2.5,
+    },
+    BulletPattern {
+        name: "spread_fan",
+        pattern: BulletPatternEnum::SpreadFan {
+            count: 5,
+            arc_degrees: 60.0,
+        },
+        speed: 220.0,
+        damage_multiplier: 0.5,
+        volleys: 1,
+        volley_interval_seconds: 0.0,
+        cooldown_seconds: 2.0,
+    },
+    BulletPattern {
+        name: "double_fan",
+        pattern: BulletPatternEnum::SpreadFan {
+            count: 7,
+            arc_degrees: 90.0,
+        },
+        speed: 240.0,
+        damage_multiplier: 0.5,
+        volleys: 2,
+        volley_interval_seconds: 0.4,
+        cooldown_seconds: 2.5,
+    },
+    BulletPattern {
+        name: "ring",
+        pattern: BulletPatternEnum::Ring { count: 16 },
+        speed: 180.0,
+        damage_multiplier: 0.5,
+        volleys: 1,
+        volley_interval_seconds: 0.0,
+        cooldown_seconds: 3.0,
+    },
+    BulletPattern {
+        name: "spiral",
+        pattern: BulletPatternEnum::Spiral {
+            arms: 4,
+            rotation_per_volley_degrees: 12.0,
+        },
+        speed: 200.0,
+        damage_multiplier: 0.4,
+        volleys: 24,
+        volley_interval_seconds: 0.1,
+        cooldown_seconds: 3.0,
+    },
+    BulletPattern {
+        name: "homing_orbs",
+        pattern: BulletPatternEnum::HomingOrb {
+            count: 3,
+            delay_seconds: 0.8,
+            turn_rate_degrees: 90.0,
+        },
+        speed: 140.0,
+        damage_multiplier: 1.0,
+        volleys: 1,
+        volley_interval_seconds: 0.0,
+        cooldown_seconds: 4.0,
+    },
+    BulletPattern {
+        name: "mortar",
+        pattern: BulletPatternEnum::LobbedAoe {
+            count: 3,
+            radius: 60.0,
+            flight_seconds: 1.2,
+        },
+        speed: 0.0,
+        damage_multiplier: 1.5,
+        volleys: 1,
+        volley_interval_seconds: 0.0,
+        cooldown_seconds: 4.0,
+    },
+];
@@ -0,0 +1,110 @@
+use super::*;
+
+// Chance of a creep being an elite: base + per level, up to the max
+pub(crate) const ELITE_BASE_CHANCE: f32 = 0.03;
+pub(crate) const ELITE_CHANCE_PER_LEVEL: f32 = 0.02;
+pub(crate) const ELITE_MAX_CHANCE: f32 = 0.25;
+// Elites get one more affix every this many levels
+pub(crate) const ELITE_LEVELS_PER_EXTRA_AFFIX: u16 = 3;
+pub(crate) const ELITE_MAX_AFFIXES: usize = 3;
+pub(crate) const ELITE_HEALTH_MULTIPLIER: f32 = 2.0;
+pub(crate) const ELITE_OUTLINE_SCALE: f32 = 1.2;
+pub(crate) const ELITE_LABEL_FONT_SIZE: f32 = 8.0;
+
+// Drops
+pub(crate) const ELITE_SCORE_MULTIPLIER: f32 = 3.0;
+
+// Affixes
+pub(crate) const ELITE_FAST_SPEED_MULTIPLIER: f32 = 1.6;
+pub(crate) const ELITE_ARMORED_DAMAGE_TAKEN_MULTIPLIER: f32 = 0.5;
+// Fraction of the damage dealt to the player that heals the elite
+pub(crate) const ELITE_VAMPIRIC_LIFE_STEAL: f32 = 0.5;
+pub(crate) const ELITE_SPLIT_QUANTITY: u32 = 2;
+pub(crate) const ELITE_SPLIT_HEALTH_MULTIPLIER: f32 = 0.4;
+pub(crate) const ELITE_SPLIT_SCALE_MULTIPLIER: f32 = 0.7;
+pub(crate) const ELITE_EXPLOSION_RADIUS: f32 = 90.0;
+pub(crate) const ELITE_EXPLOSION_DAMAGE_MULTIPLIER: f32 = 3.0;
+pub(crate) const ELITE_EXPLOSION_SECONDS: f32 = 0.3;
+// The shield absorbs this fraction of the max health before breaking
+pub(crate) const ELITE_SHIELD_HEALTH_FRACTION: f32 = 0.5;
+pub(crate) const ELITE_TELEPORT_COOLDOWN_SECONDS: f32 = 4.0;
+pub(crate) const ELITE_TELEPORT_DISTANCE: f32 = 150.0;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum EliteAffixEnum {
+    Fast,
+    Armored,
+    // Heals when hitting the player
+    Vampiric,
+    // Splits into smaller orcs when it dies
+    Splitting,
+    // Explodes when it dies, hurting the player if close
+    Explosive,
+    // Has a shield that must be broken before it takes damage
+    Shielded,
+    // Teleports close to the player from time to time
+    Teleporting,
+}
+
+#[derive(Debug, Clone, Copy)]
+pub struct EliteAffix {
+    pub affix: EliteAffixEnum,
+    pub name_prefix: &'static str,
+    // Higher weight means the affix is chosen more often
+    pub weight: u32,
+    // First game level in which the affix can be chosen
+    pub min_level: u16,
+    pub color: Color,
+}
+
+pub(crate) const ELITE_AFFIXES: [EliteAffix; 7] = [
+    EliteAffix {
+        affix: EliteAffixEnum::Fast,
+        name_prefix: "Fast",
+        weight: 10,
+        min_level: 1,
+        color: Color::srgb(0.2, 1.0, 1.0),
+    },
+    EliteAffix {
+        affix: EliteAffixEnum::Armored,
+        name_prefix: "Armored",
+        weight: 10,
+        min_level: 1,
+        color: Color::srgb(0.7, 0.7, 0.7),
+    },
+    EliteAffix {
+        affix: EliteAffixEnum::Vampiric,
+        name_prefix: "Vampiric",
+        weight: 6,
+        min_level: 2,
+        color: Color::srgb(0.8, 0.0, 0.2),
+    },
+    EliteAffix {
+        affix: EliteAffixEnum::Splitting,
+        name_prefix: "Splitting",
+        weight: 6,
+        min_level: 2,
+        color: Color::srgb(0.3, 1.0, 0.3),
+    },
+    EliteAffix {
+        affix: EliteAffixEnum::Explosive,
+        name_prefix: "Explosive",
+        weight: 5,
+        min_level: 3,
+        color: Color::srgb(1.0, 0.5, 0.0),
+    },
+    EliteAffix {
+        affix: EliteAffixEnum::Shielded,
+        name_prefix: "Shielded",
+        weight: 5,
+        min_level: 3,
+        color: Color::srgb(0.3, 0.5, 1.0),
+    },
+    EliteAffix {
+        affix: EliteAffixEnum::Teleporting,
+        name_prefix: "Teleporting",
+        weight: 4,
+        min_level: 4,
+        color: Color::srgb(0.8, 0.3, 1.0),
+    },
+];
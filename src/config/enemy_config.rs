@@ -13,6 +13,32 @@ pub(crate) const BOSS_SCALE: f32 = 5.0;
 // Each level the base damage of all enemies is updated
 pub(crate) const ENEMY_BASE_DAMAGE_MULTIPLIER_BASED_ON_LEVEL: f32 = 0.1;
 
+// Flocking
+// Size of each cell of the spatial grid used to look for nearby enemies.
+// It must be at least as big as the biggest flocking radius so we only need
+// to look at the neighbouring cells.
+pub(crate) const FLOCKING_CELL_SIZE: f32 = 64.0;
+pub(crate) const FLOCKING_NEIGHBOUR_RADIUS: f32 = 64.0;
+pub(crate) const FLOCKING_SEPARATION_RADIUS: f32 = 28.0;
+pub(crate) const FLOCKING_SEPARATION_WEIGHT: f32 = 2.5;
+pub(crate) const FLOCKING_ALIGNMENT_WEIGHT: f32 = 0.3;
+pub(crate) const FLOCKING_COHESION_WEIGHT: f32 = 0.2;
+// Enemies charging the player will spread around him on a ring of this radius
+// before closing in.
+pub(crate) const ENEMY_SURROUND_RADIUS: f32 = 90.0;
+// Used to spread the surround angle of each enemy evenly around the player.
+pub(crate) const GOLDEN_ANGLE: f32 = 2.399_963;
+
+// Perception
+pub(crate) const ENEMY_SIGHT_RADIUS: f32 = 800.0;
+// An invisible player can only be seen from much closer
+pub(crate) const STEALTH_SIGHT_MULTIPLIER: f32 = 0.2;
+// Attacks on enemies that can not see the player
+pub(crate) const STEALTH_ATTACK_DAMAGE_MULTIPLIER: f32 = 1.5;
+// How long enemies look for the player where they last saw him
+pub(crate) const ENEMY_SEARCH_SECONDS: f32 = 4.0;
+pub(crate) const ENEMY_SEARCH_RADIUS: f32 = 60.0;
+
 #[derive(Debug, Clone, PartialEq, Eq)]
 pub enum EnemyClassEnum {
     Orc,
@@ -28,6 +54,8 @@ pub struct EnemyType {
     pub health: f32,
     pub scale: Vec3,
     pub class: EnemyClassEnum,
+    // Name of the bullet pattern used by enemies that carry a weapon
+    pub bullet_pattern: Option<&'static str>,
 }
 
 #[derive(Debug, Clone)]
@@ -42,6 +70,7 @@ pub(crate) const BOSS_LVL_1: EnemyType = EnemyType {
     health: ENEMY_HEALTH * BOSS_SCALE,
     scale: Vec3::splat(BOSS_SCALE),
     class: EnemyClassEnum::BossOrc,
+    bullet_pattern: Some("double_fan"),
 };
 
 pub(crate) const BOSS_LVL_2: EnemyType = EnemyType {
@@ -49,6 +78,7 @@ pub(crate) const BOSS_LVL_2: EnemyType = EnemyType {
     health: ENEMY_HEALTH * BOSS_SCALE * 2.,
     scale: Vec3::splat(BOSS_SCALE),
     class: EnemyClassEnum::BossMage,
+    bullet_pattern: Some("homing_orbs"),
 };
 
 pub(crate) const BOSS_LVL_3: EnemyType = EnemyType {
@@ -56,6 +86,7 @@ pub(crate) const BOSS_LVL_3: EnemyType = EnemyType {
     health: ENEMY_HEALTH * BOSS_SCALE * 3.,
     scale: Vec3::splat(BOSS_SCALE),
     class: EnemyClassEnum::BossAlien,
+    bullet_pattern: Some("spiral"),
 };
 
 const ENEMY_WAVE_1: EnemyType = EnemyType {
@@ -63,30 +94,35 @@ const ENEMY_WAVE_1: EnemyType = EnemyType {
     health: ENEMY_HEALTH,
     scale: Vec3::splat(2.0),
     class: EnemyClassEnum::Mage,
+    bullet_pattern: Some("aimed_burst"),
 };
 const ENEMY_WAVE_2: EnemyType = EnemyType {
     base_damage: 10.0,
     health: ENEMY_HEALTH,
     scale: Vec3::new(1.2, 1.2, 1.0),
     class: EnemyClassEnum::Orc,
+    bullet_pattern: None,
 };
 const ENEMY_WAVE_3: EnemyType = EnemyType {
     base_damage: 15.0,
     health: ENEMY_HEALTH,
     scale: Vec3::new(1.4, 1.4, 1.0),
     class: EnemyClassEnum::Orc,
+    bullet_pattern: None,
 };
 const ENEMY_WAVE_4: EnemyType = EnemyType {
     base_damage: 20.0,
     health: ENEMY_HEALTH,
     scale: Vec3::new(1.6, 1.6, 1.0),
     class: EnemyClassEnum::Orc,
+    bullet_pattern: None,
 };
 const ENEMY_WAVE_5: EnemyType = EnemyType {
     base_damage: 25.0,
     health: ENEMY_HEALTH,
     scale: Vec3::new(1.8, 1.8, 1.0),
     class: EnemyClassEnum::Orc,
+    bullet_pattern: None,
 };
 
 pub const ENEMIES_PER_WAVE: [EnemyByWave; NUMBER_OF_WAVES] = [
@@ -4,8 +4,6 @@ use super::*;
 
 pub(crate) const ITEM_RANDOM_SEED: u64 = 1937836746771;
 pub(crate) const ITEM_SPRITE_SIZE: u8 = 32;
-pub(crate) const CHANCE_TO_SPAWN_HEALTH_POINTS_PACK: f32 = 0.4;
-pub(crate) const CHANCE_TO_SPAWN_MANA_POINTS_PACK: f32 = 0.4;
 pub(crate) const ITEM_BASE_MULTIPLIER_BASED_ON_LEVEL: f32 = 0.2;
 
 #[cfg_attr(
@@ -63,6 +61,11 @@ const ITEM_WAVE_4: ItemType = ItemType {
         defensive: 10.0,
         shield_type: crate::ShieldType::Physical,
         duration_seconds: Some(20),
+        count: NUMBER_OF_BUFF_ITEMS,
+        radius: RADIUS_FROM_PLAYER,
+        orbit_speed: SHIELD_ORBIT_SPEED,
+        durability: SHIELD_DURABILITY,
+        regrow_seconds: SHIELD_REGROW_SECONDS,
     }),
     item_stats_type: ItemStatsType::Shield,
 };
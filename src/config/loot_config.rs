@@ -0,0 +1,245 @@
+use crate::StatKind;
+
+use super::*;
+
+// Score given for each coin picked up
+pub(crate) const COIN_SCORE: f32 = 5.0;
+pub(crate) const COINS_PER_DROP: u32 = 3;
+pub(crate) const LOOT_HEALTH_PACK_HEALTH: f32 = 10.0;
+pub(crate) const LOOT_MANA_PACK_MANA: f32 = 10.0;
+// How far from each other the drops of a single kill land
+pub(crate) const LOOT_DROP_SPREAD: f32 = 30.0;
+// Luck makes every drop but `Nothing` more likely. Each level gives a bit of
+// luck on top of the one of the player.
+pub(crate) const LOOT_LUCK_PER_LEVEL: f32 = 0.1;
+// Chests spill this many rolls of the loot table when opened. Chests never
+// drop from a chest.
+pub(crate) const CHEST_LOOT_ROLLS: u32 = 3;
+pub(crate) const CHEST_SCALE: f32 = 0.6;
+pub(crate) const CHEST_COLOR: Color = Color::srgb(1.0, 0.8, 0.3);
+pub(crate) const REWARD_CHEST_SCALE: f32 = 0.9;
+pub(crate) const REWARD_CHEST_COLOR: Color = Color::srgb(0.7, 0.4, 1.0);
+// How far from the chest its loot lands
+pub(crate) const CHEST_LOOT_SPREAD: f32 = 40.0;
+// Rewards to choose from when a reward chest is opened
+pub(crate) const CHEST_REWARD_CHOICES: usize = 3;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum LootDropEnum {
+    Nothing,
+    HealthPack,
+    ManaPack,
+    Coins,
+    // Spills more loot when opened
+    Chest,
+    // Opens into a choice of rewards
+    RewardChest,
+}
+
+#[derive(Debug, Clone, Copy)]
+pub struct LootEntry {
+    pub drop: LootDropEnum,
+    // Higher weight means the drop is chosen more often
+    pub weight: u32,
+}
+
+#[derive(Debug, Clone, Copy)]
+pub struct LootTable {
+    pub entries: &'static [LootEntry],
+    // Always dropped, on top of the rolls
+    pub guaranteed: &'static [LootDropEnum],
+    // Times the entries are rolled
+    pub rolls: u32,
+}
+
+pub const ORC_LOOT_TABLE: LootTable = LootTable {
+    entries: &[
+        LootEntry {
+            drop: LootDropEnum::Nothing,
+            weight: 35,
+        },
+        LootEntry {
+            drop: LootDropEnum::HealthPack,
+            weight: 25,
+        },
+        LootEntry {
+            drop: LootDropEnum::ManaPack,
+            weight: 25,
+        },
+        LootEntry {
+            drop: LootDropEnum::Coins,
+            weight: 15,
+        },
+    ],
+    guaranteed: &[],
+    rolls: 1,
+};
+
+// Mages give back more mana
+pub const MAGE_LOOT_TABLE: LootTable = LootTable {
+    entries: &[
+        LootEntry {
+            drop: LootDropEnum::Nothing,
+            weight: 30,
+        },
+        LootEntry {
+            drop: LootDropEnum::HealthPack,
+            weight: 15,
+        },
+        LootEntry {
+            drop: LootDropEnum::ManaPack,
+            weight: 35,
+        },
+        LootEntry {
+            drop: LootDropEnum::Coins,
+            weight: 20,
+        },
+    ],
+    guaranteed: &[],
+    rolls: 1,
+};
+
+// Rolled on top of the table of the class of the elite
+pub const ELITE_LOOT_TABLE: LootTable = LootTable {
+    entries: &[
+        LootEntry {
+            drop: LootDropEnum::Nothing,
+            weight: 10,
+        },
+        LootEntry {
+            drop: LootDropEnum::HealthPack,
+            weight: 30,
+        },
+        LootEntry {
+            drop: LootDropEnum::ManaPack,
+            weight: 30,
+        },
+        LootEntry {
+            drop: LootDropEnum::Coins,
+            weight: 25,
+        },
+        LootEntry {
+            drop: LootDropEnum::Chest,
+            weight: 5,
+        },
+    ],
+    guaranteed: &[LootDropEnum::Coins],
+    rolls: 3,
+};
+
+pub const BOSS_LOOT_TABLE: LootTable = LootTable {
+    entries: &[
+        LootEntry {
+            drop: LootDropEnum::HealthPack,
+            weight: 30,
+        },
+        LootEntry {
+            drop: LootDropEnum::ManaPack,
+            weight: 30,
+        },
+        LootEntry {
+            drop: LootDropEnum::Coins,
+            weight: 40,
+        },
+    ],
+    guaranteed: &[
+        LootDropEnum::RewardChest,
+        LootDropEnum::HealthPack,
+        LootDropEnum::ManaPack,
+    ],
+    rolls: 4,
+};
+
+pub const PROP_LOOT_TABLE: LootTable = LootTable {
+    entries: &[
+        LootEntry {
+            drop: LootDropEnum::Nothing,
+            weight: 30,
+        },
+        LootEntry {
+            drop: LootDropEnum::HealthPack,
+            weight: 20,
+        },
+        LootEntry {
+            drop: LootDropEnum::ManaPack,
+            weight: 20,
+        },
+        LootEntry {
+            drop: LootDropEnum::Coins,
+            weight: 27,
+        },
+        LootEntry {
+            drop: LootDropEnum::Chest,
+            weight: 3,
+        },
+    ],
+    guaranteed: &[],
+    rolls: 1,
+};
+
+#[derive(Debug, Clone, Copy)]
+pub enum ChestRewardEnum {
+    // Permanent bonus to a stat of the player
+    Stat(StatKind, f32),
+    Coins(u32),
+}
+
+#[derive(Debug, Clone, Copy)]
+pub struct ChestReward {
+    pub name: &'static str,
+    pub description: &'static str,
+    pub reward: ChestRewardEnum,
+}
+
+pub const CHEST_REWARDS: [ChestReward; 10] = [
+    ChestReward {
+        name: "Vitality",
+        description: "+50 max health",
+        reward: ChestRewardEnum::Stat(StatKind::MaxHealth, 50.0),
+    },
+    ChestReward {
+        name: "Focus",
+        description: "+30 max mana",
+        reward: ChestRewardEnum::Stat(StatKind::MaxMana, 30.0),
+    },
+    ChestReward {
+        name: "Swiftness",
+        description: "+20 speed",
+        reward: ChestRewardEnum::Stat(StatKind::Speed, 20.0),
+    },
+    ChestReward {
+        name: "Recovery",
+        description: "+1 health every second",
+        reward: ChestRewardEnum::Stat(StatKind::HealthRegen, 1.0),
+    },
+    ChestReward {
+        name: "Clarity",
+        description: "+1 mana every second",
+        reward: ChestRewardEnum::Stat(StatKind::ManaRegen, 1.0),
+    },
+    ChestReward {
+        name: "Thirst",
+        description: "+5% life steal",
+        reward: ChestRewardEnum::Stat(StatKind::LifeSteal, 0.05),
+    },
+    ChestReward {
+        name: "Fortune",
+        description: "+50% luck",
+        reward: ChestRewardEnum::Stat(StatKind::Luck, 0.5),
+    },
+    ChestReward {
+        name: "Agility",
+        description: "+1 dash charge",
+        reward: ChestRewardEnum::Stat(StatKind::DashCharges, 1.0),
+    },
+    ChestReward {
+        name: "Stride",
+        description: "+40 dash distance",
+        reward: ChestRewardEnum::Stat(StatKind::DashDistance, 40.0),
+    },
+    ChestReward {
+        name: "Treasure",
+        description: "+30 coins",
+        reward: ChestRewardEnum::Coins(30),
+    },
+];
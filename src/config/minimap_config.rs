@@ -0,0 +1,22 @@
+use super::*;
+
+pub(crate) const MINIMAP_WIDTH_PX: f32 = 200.;
+pub(crate) const MINIMAP_HEIGHT_PX: f32 = 113.;
+pub(crate) const MINIMAP_BACKGROUND_COLOR: Color = Color::srgba(0.0, 0.0, 0.0, 0.5);
+pub const MINIMAP_UPDATE_MILLIS: u64 = 100;
+
+// Dots
+pub(crate) const MINIMAP_DOT_SIZE_PX: f32 = 4.;
+pub(crate) const MINIMAP_PLAYER_DOT_SIZE_PX: f32 = 6.;
+pub(crate) const MINIMAP_BOSS_DOT_SIZE_PX: f32 = 10.;
+pub(crate) const MINIMAP_PLAYER_COLOR: Color = Color::srgb(0.2, 1.0, 0.2);
+pub(crate) const MINIMAP_ENEMY_COLOR: Color = Color::srgb(1.0, 0.2, 0.2);
+pub(crate) const MINIMAP_BOSS_COLOR: Color = Color::srgb(0.8, 0.2, 1.0);
+pub(crate) const MINIMAP_ITEM_COLOR: Color = Color::srgb(1.0, 1.0, 0.3);
+pub(crate) const MINIMAP_WEAPON_COLOR: Color = Color::srgb(0.3, 0.9, 1.0);
+
+// Arrows on the edges of the screen pointing at what is outside of it
+pub(crate) const OFFSCREEN_INDICATOR_SIZE: f32 = 10.;
+// Distance from the edge of the screen
+pub(crate) const OFFSCREEN_INDICATOR_MARGIN: f32 = 24.;
+pub(crate) const OFFSCREEN_INDICATOR_ALPHA: f32 = 0.8;
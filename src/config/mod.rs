@@ -2,17 +2,33 @@ use bevy::prelude::*;
 pub use bevy::render::view::RenderLayers;
 
 // config modules
+pub mod boss_config;
 pub mod buff_config;
+pub mod bullet_pattern_config;
+pub mod elite_config;
 pub mod enemy_config;
 pub mod item_config;
+pub mod loot_config;
+pub mod minimap_config;
 pub mod power_config;
+pub mod prop_config;
+pub mod settings_config;
+pub mod tilemap_config;
 pub mod weapon_config;
 
 // re-export all config modules
+pub use boss_config::*;
 pub(crate) use buff_config::*;
+pub use bullet_pattern_config::*;
+pub use elite_config::*;
 pub use enemy_config::*;
 pub use item_config::*;
+pub use loot_config::*;
+pub use minimap_config::*;
 pub use power_config::*;
+pub(crate) use prop_config::*;
+pub(crate) use settings_config::*;
+pub use tilemap_config::*;
 pub use weapon_config::*;
 
 // Layers control which entities should be rendered by cameras
@@ -29,8 +45,25 @@ pub(crate) const PLAYER_MOVE_SPEED: f32 = 150.0;
 pub(crate) const PLAYER_ARMOR: f32 = 0.0;
 pub(crate) const PLAYER_HEALTH: f32 = 1000.;
 pub(crate) const PLAYER_MANA: f32 = 100.;
+// Given back every second
+pub(crate) const PLAYER_HEALTH_REGEN: f32 = 1.;
+pub(crate) const PLAYER_MANA_REGEN: f32 = 1.;
+pub(crate) const PLAYER_LIFE_STEAL: f32 = 0.;
+pub(crate) const PLAYER_LUCK: f32 = 0.;
 pub(crate) const PLAYER_SPRITE_SIZE: u8 = 32;
 
+// Dash
+pub(crate) const PLAYER_DASH_DISTANCE: f32 = 120.;
+pub(crate) const PLAYER_DASH_CHARGES: u32 = 2;
+pub(crate) const DASH_SECONDS: f32 = 0.15;
+// Time to get a charge back after using it
+pub(crate) const DASH_COOLDOWN_SECONDS: f32 = 1.5;
+// A bit longer than the dash itself, so the player is safe where they land
+pub(crate) const DASH_INVULNERABILITY_SECONDS: f32 = 0.25;
+pub(crate) const DASH_TRAIL_INTERVAL_SECONDS: f32 = 0.03;
+pub(crate) const DASH_TRAIL_FADE_SECONDS: f32 = 0.25;
+pub(crate) const DASH_TRAIL_ALPHA: f32 = 0.5;
+
 // These are margins so when we move the player
 // it doesn't show only his half on the screen
 // (remember that the point of translation is at the center of the player,
@@ -39,6 +72,24 @@ pub(crate) const PLAYER_SPRITE_SIZE: u8 = 32;
 pub(crate) const PLAYER_X_MARGIN: f32 = 20.;
 pub(crate) const PLAYER_Y_MARGIN: f32 = 40.;
 
+// Camera
+// How far the player can move before the cameras follow
+pub(crate) const CAMERA_DEAD_ZONE: Vec2 = Vec2::new(40., 30.);
+pub(crate) const CAMERA_FOLLOW_SECONDS: f32 = 0.2;
+// Further than this the cameras jump instead of following
+pub(crate) const CAMERA_SNAP_DISTANCE: f32 = 600.;
+pub(crate) const CAMERA_MAX_SHAKE_OFFSET: f32 = 12.;
+// Trauma lost every second
+pub(crate) const CAMERA_TRAUMA_DECAY: f32 = 1.5;
+pub(crate) const CAMERA_HIT_TRAUMA_PER_DAMAGE: f32 = 0.01;
+pub(crate) const CAMERA_HIT_MAX_TRAUMA: f32 = 0.4;
+pub(crate) const CAMERA_EXPLOSION_TRAUMA: f32 = 0.5;
+pub(crate) const CAMERA_BOSS_ATTACK_TRAUMA: f32 = 0.6;
+// Enemies alive at once to zoom out
+pub(crate) const CAMERA_BIG_FIGHT_ENEMIES: usize = 15;
+pub(crate) const CAMERA_BIG_FIGHT_ZOOM: f32 = 1.25;
+pub(crate) const CAMERA_ZOOM_SPEED: f32 = 3.;
+
 pub(crate) const AMMO_MOVE_SPEED: f32 = 500.0;
 pub(crate) const AMMO_DAMAGE: f32 = 10.0;
 
@@ -59,6 +110,14 @@ pub const BACKGROUND_TEXTURE_RESOLUTION: CustomResolution = CustomResolution {
     y_px: 1080.0,
 };
 
+// This is how much of the world the cameras show, whatever the size of the
+// window. The window is letterboxed to keep its aspect ratio and the UI is
+// scaled with it.
+pub const VIRTUAL_RESOLUTION: CustomResolution = CustomResolution {
+    x_px: 1600.0,
+    y_px: 900.0,
+};
+
 // This is the initial window resolution when the application opens.
 pub const INITIAL_WINDOW_RESOLUTION: CustomResolution = CustomResolution {
     x_px: 1600.0,
@@ -1,93 +1,288 @@
 use super::*;
 
-pub(crate) const POWER_RANDOM_SEED: u64 = 1242436746771;
 pub(crate) const POWER_SPRITE_SIZE: u8 = 32;
 pub(crate) const POWER_MOVE_SPEED: f32 = 100.0;
 
-pub(crate) const NUMBER_OF_POWERS: usize = 3;
+// A key press is remembered for this long, so pressing it right before the
+// power is ready still casts it
+pub(crate) const POWER_INPUT_BUFFER_SECONDS: f32 = 0.15;
 
 // Laser
 pub(crate) const LASER_POWER_WIDTH: f32 = 300.;
 pub(crate) const LASER_POWER_HEIGHT: f32 = 2.;
 pub(crate) const LASER_MOVE_SPEED: f32 = 800.0;
 
-#[cfg_attr(
-    not(feature = "web"),
-    derive(Reflect, Component, Debug, Clone, PartialEq)
-)]
-#[cfg_attr(not(feature = "web"), reflect(Component))]
-#[cfg_attr(feature = "web", derive(Component, Debug, Clone, PartialEq))]
-pub enum PowerTypeEnum {
-    Explosions,
-    CircleOfDeath,
-    Laser,
+// Ground targeted powers
+pub(crate) const POWER_TARGET_PREVIEW_COLOR: Color = Color::srgba(1.0, 1.0, 1.0, 0.2);
+
+// Circle of death
+pub(crate) const CIRCLE_OF_DEATH_INNER_RADIUS: f32 = 40.;
+pub(crate) const CIRCLE_OF_DEATH_OUTER_RADIUS: f32 = 50.;
+// Every interval the outer radius grows by this fraction
+pub(crate) const CIRCLE_OF_DEATH_GROWTH: f32 = 0.2;
+pub(crate) const CIRCLE_OF_DEATH_GROWTH_INTERVAL_MILLIS: u64 = 50;
+
+// Mine bomb
+// Distance between the mines dropped behind the player
+pub(crate) const MINE_BOMB_DROP_DISTANCE: f32 = 30.;
+pub(crate) const MINE_BOMB_ARMING_SECONDS: f32 = 1.0;
+// Scale of the mine while it is not armed yet
+pub(crate) const MINE_BOMB_ARMING_SCALE: f32 = 0.6;
+pub(crate) const MINE_BOMB_TRIGGER_RADIUS: f32 = 25.;
+pub(crate) const MINE_BOMB_EXPLOSION_RADIUS: f32 = 80.;
+// Enemies at the center of the explosion are pushed this far, the ones at
+// the border are not pushed at all
+pub(crate) const MINE_BOMB_KNOCKBACK_DISTANCE: f32 = 60.;
+pub(crate) const MINE_BOMB_EXPLOSION_SECONDS: f32 = 0.3;
+pub(crate) const MINE_BOMB_MAX_ACTIVE: usize = 10;
+
+// Upgrades
+// Angle between the extra beams of the laser
+pub(crate) const LASER_EXTRA_BEAM_SPREAD_DEGREES: f32 = 15.;
+pub(crate) const BURNING_RING_INNER_RADIUS: f32 = 90.;
+pub(crate) const BURNING_RING_OUTER_RADIUS: f32 = 120.;
+pub(crate) const BURNING_RING_SECONDS: f32 = 4.;
+pub(crate) const BURNING_RING_TICK_SECONDS: f32 = 0.5;
+// Fraction of the damage of the power dealt on each tick
+pub(crate) const BURNING_RING_DAMAGE_FRACTION: f32 = 0.2;
+// How far a chained explosion looks for its next enemy
+pub(crate) const CHAIN_EXPLOSION_RANGE: f32 = 200.;
+
+// Where a power is aimed when it is cast
+#[cfg_attr(not(feature = "web"), derive(Reflect, Debug, Clone, Copy, PartialEq))]
+#[cfg_attr(feature = "web", derive(Debug, Clone, Copy, PartialEq))]
+pub enum PowerTargeting {
+    // On the player, towards where they are moving
+    Player,
+    // Towards the mouse cursor
+    Cursor,
+    // Towards the closest enemy, or the cursor if there are none
+    NearestEnemy,
+    // Where the cursor is when the key is released. The area is shown on
+    // the ground while the key is held.
+    GroundTarget { radius: f32 },
+    // A cone of `angle_degrees` towards the cursor
+    Cone { angle_degrees: f32 },
 }
 
-#[cfg_attr(
-    not(feature = "web"),
-    derive(Reflect, Component, Default, Debug, Clone)
-)]
-#[cfg_attr(not(feature = "web"), reflect(Component))]
-#[cfg_attr(feature = "web", derive(Component, Default, Debug, Clone))]
+// Each power decides when its instances go away
+#[cfg_attr(not(feature = "web"), derive(Reflect, Debug, Clone, PartialEq))]
+#[cfg_attr(feature = "web", derive(Debug, Clone, PartialEq))]
 pub enum StoppingCondition {
-    #[default]
-    Instances,
-    Limit,
-    ScreenBounces,
+    // After hitting this many enemies
+    Hits(u32),
+    // After bouncing on the edges of the map more than this many times
+    ScreenBounces(u32),
+    // Once the radius of the power is bigger than this
+    RadiusLimit(f32),
 }
 
+// What choosing an upgrade does to the power
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum PowerUpgradeEnum {
+    // Multiplies the damage
+    Damage(f32),
+    // Multiplies the mana needed to cast it
+    ManaCost(f32),
+    // Adds to `max_value`: bounces, explosions, mines...
+    MaxValue(u32),
+    // Laser: more beams, spread around the aimed one
+    ExtraBeams(u32),
+    // Laser: goes through the shields of the elites
+    Pierce,
+    // Circle of death: leaves a burning ring around where it was cast
+    BurningRing,
+    // Circle of death: the cone gets this many degrees wider
+    WiderCone(f32),
+    // Explosions: each one jumps this many times to the closest enemy
+    ChainExplosions(u32),
+    // Multiplies the radius of the mine explosions
+    BiggerArea(f32),
+    // Changes where the power is aimed
+    Targeting(PowerTargeting),
+}
+
+#[derive(Debug)]
+pub struct PowerUpgrade {
+    pub name: &'static str,
+    pub description: &'static str,
+    pub upgrades: &'static [PowerUpgradeEnum],
+    // Choices offered the next time the power levels up
+    pub next: &'static [PowerUpgrade],
+}
+
+// Every time the player finds a power they already have, they go one step
+// down its tree. When there are several choices the player picks one,
+// otherwise it is applied right away.
+pub(crate) static LASER_UPGRADES: &[PowerUpgrade] = &[
+    PowerUpgrade {
+        name: "Split Beam",
+        description: "Two extra beams",
+        upgrades: &[
+            PowerUpgradeEnum::ExtraBeams(2),
+            PowerUpgradeEnum::ManaCost(1.2),
+        ],
+        next: &[PowerUpgrade {
+            name: "Prism",
+            description: "Two more beams, aimed at the closest enemy",
+            upgrades: &[
+                PowerUpgradeEnum::ExtraBeams(2),
+                PowerUpgradeEnum::Targeting(PowerTargeting::NearestEnemy),
+            ],
+            next: &[],
+        }],
+    },
+    PowerUpgrade {
+        name: "Piercing Beam",
+        description: "Goes through elite shields and hits harder",
+        upgrades: &[PowerUpgradeEnum::Pierce, PowerUpgradeEnum::Damage(1.5)],
+        next: &[PowerUpgrade {
+            name: "Overcharge",
+            description: "More damage and bounces",
+            upgrades: &[PowerUpgradeEnum::Damage(1.5), PowerUpgradeEnum::MaxValue(3)],
+            next: &[],
+        }],
+    },
+];
+
+pub(crate) static CIRCLE_OF_DEATH_UPGRADES: &[PowerUpgrade] = &[
+    PowerUpgrade {
+        name: "Burning Ring",
+        description: "Leaves a ring of fire around you",
+        upgrades: &[PowerUpgradeEnum::BurningRing],
+        next: &[PowerUpgrade {
+            name: "Wildfire",
+            description: "More damage",
+            upgrades: &[PowerUpgradeEnum::Damage(1.3)],
+            next: &[],
+        }],
+    },
+    PowerUpgrade {
+        name: "Full Circle",
+        description: "The wave goes all around you",
+        upgrades: &[PowerUpgradeEnum::WiderCone(240.)],
+        next: &[PowerUpgrade {
+            name: "Shockwave",
+            description: "More damage",
+            upgrades: &[PowerUpgradeEnum::Damage(1.5)],
+            next: &[],
+        }],
+    },
+];
+
+pub(crate) static EXPLOSIONS_UPGRADES: &[PowerUpgrade] = &[
+    PowerUpgrade {
+        name: "Chain Reaction",
+        description: "Explosions jump to nearby enemies",
+        upgrades: &[PowerUpgradeEnum::ChainExplosions(2)],
+        next: &[PowerUpgrade {
+            name: "Domino",
+            description: "Two more jumps",
+            upgrades: &[PowerUpgradeEnum::ChainExplosions(2)],
+            next: &[],
+        }],
+    },
+    PowerUpgrade {
+        name: "Carpet Bombing",
+        description: "Five more explosions",
+        upgrades: &[PowerUpgradeEnum::MaxValue(5), PowerUpgradeEnum::Damage(1.2)],
+        next: &[PowerUpgrade {
+            name: "Saturation",
+            description: "Five more explosions",
+            upgrades: &[PowerUpgradeEnum::MaxValue(5)],
+            next: &[],
+        }],
+    },
+];
+
+pub(crate) static MINE_BOMB_UPGRADES: &[PowerUpgrade] = &[
+    PowerUpgrade {
+        name: "Minefield",
+        description: "Two more mines",
+        upgrades: &[PowerUpgradeEnum::MaxValue(2)],
+        next: &[PowerUpgrade {
+            name: "No Man's Land",
+            description: "Two more mines",
+            upgrades: &[PowerUpgradeEnum::MaxValue(2)],
+            next: &[],
+        }],
+    },
+    PowerUpgrade {
+        name: "Shaped Charge",
+        description: "Bigger and stronger explosions",
+        upgrades: &[
+            PowerUpgradeEnum::BiggerArea(1.5),
+            PowerUpgradeEnum::Damage(1.3),
+        ],
+        next: &[PowerUpgrade {
+            name: "Demolition",
+            description: "Even bigger explosions",
+            upgrades: &[PowerUpgradeEnum::BiggerArea(1.3)],
+            next: &[],
+        }],
+    },
+];
+
 pub struct PowerType {
     pub damage: f32,
     pub mana_needed: f32,
-    pub power_type: PowerTypeEnum,
-    pub stopping_condition: StoppingCondition,
+    // Meaning depends on the power: bounces for the laser, number of
+    // explosions, ...
     pub max_value: u32,
+    // How many of them should be spawned
+    pub quantity: u32,
+    // Time to get a charge back after using it
+    pub cooldown_seconds: f32,
+    // How many times it can be cast in a row
+    pub charges: u32,
+    pub targeting: PowerTargeting,
+    // Bound to the power until the player changes it on the settings
+    pub default_key: KeyCode,
 }
 
-const POWER_LVL_1: PowerType = PowerType {
+pub(crate) const LASER_POWER: PowerType = PowerType {
     damage: 0.1,
     mana_needed: 10.0,
-    power_type: PowerTypeEnum::Laser,
-    stopping_condition: StoppingCondition::ScreenBounces,
     max_value: 5,
+    quantity: 1,
+    cooldown_seconds: 1.0,
+    charges: 2,
+    targeting: PowerTargeting::Cursor,
+    default_key: KeyCode::KeyH,
 };
 
-const POWER_LVL_2: PowerType = PowerType {
+pub(crate) const CIRCLE_OF_DEATH_POWER: PowerType = PowerType {
     damage: 5.0,
     mana_needed: 10.0,
-    power_type: PowerTypeEnum::CircleOfDeath,
-    stopping_condition: StoppingCondition::Limit,
     max_value: 0,
+    quantity: 1,
+    cooldown_seconds: 8.0,
+    charges: 1,
+    targeting: PowerTargeting::Cone {
+        angle_degrees: 120.0,
+    },
+    default_key: KeyCode::KeyJ,
 };
 
-const POWER_LVL_3: PowerType = PowerType {
+pub(crate) const EXPLOSIONS_POWER: PowerType = PowerType {
     damage: 10.0,
     mana_needed: 10.0,
-    power_type: PowerTypeEnum::Explosions,
-    stopping_condition: StoppingCondition::Instances,
     max_value: 5,
+    quantity: 1,
+    cooldown_seconds: 4.0,
+    charges: 1,
+    targeting: PowerTargeting::GroundTarget { radius: 150.0 },
+    default_key: KeyCode::KeyL,
 };
 
-pub struct PowerByLevel {
-    pub level: usize,
-    pub power: PowerType,
-    pub quantity: u32,
-}
-
-pub const POWERS_PER_LEVEL: [PowerByLevel; NUMBER_OF_POWERS] = [
-    PowerByLevel {
-        level: 1,
-        power: POWER_LVL_1,
-        quantity: 1,
-    },
-    PowerByLevel {
-        level: 2,
-        power: POWER_LVL_2,
-        quantity: 1,
-    },
-    PowerByLevel {
-        level: 3,
-        power: POWER_LVL_3,
-        quantity: 1,
-    },
-];
+pub(crate) const MINE_BOMB_POWER: PowerType = PowerType {
+    damage: 15.0,
+    mana_needed: 15.0,
+    // Mines dropped each time
+    max_value: 3,
+    quantity: 1,
+    cooldown_seconds: 3.0,
+    charges: 3,
+    targeting: PowerTargeting::Player,
+    default_key: KeyCode::KeyG,
+};
@@ -0,0 +1,14 @@
+pub(crate) const ENERGY_PACK_PROP_HEALTH: f32 = 20.0;
+pub(crate) const BOULDER_PROP_HEALTH: f32 = 60.0;
+pub(crate) const ENERGY_PACK_PROP_SCALE: f32 = 2.0;
+pub(crate) const BOULDER_PROP_SCALE: f32 = 0.6;
+// Seconds each frame of the break animation is shown
+pub(crate) const PROP_BREAK_FRAME_SECONDS: f32 = 0.06;
+pub(crate) const PROP_BREAK_SCALE: f32 = 2.5;
+pub(crate) const PROP_SHARDS: u32 = 6;
+pub(crate) const PROP_SHARD_SCALE: f32 = 1.0;
+pub(crate) const PROP_SHARD_SPEED: f32 = 120.0;
+pub(crate) const PROP_SHARD_SECONDS: f32 = 0.5;
+// Broken props come back when a new wave starts. Otherwise they only come
+// back with the next level.
+pub(crate) const PROPS_RESET_EACH_WAVE: bool = true;
@@ -0,0 +1,35 @@
+use super::*;
+
+// Next to the executable when running natively, on the local storage of
+// the browser on the web
+#[cfg(not(target_arch = "wasm32"))]
+pub(crate) const SETTINGS_FILE_NAME: &str = "settings.ron";
+#[cfg(target_arch = "wasm32")]
+pub(crate) const SETTINGS_STORAGE_KEY: &str = "area_25_5_settings";
+
+// Each click on an option goes to the next value, after the last one it
+// goes back to the first
+pub(crate) const SETTINGS_VOLUME_STEP: f32 = 0.1;
+pub(crate) const SETTINGS_RESOLUTION_SCALES: [f32; 5] = [0.5, 0.75, 1.0, 1.25, 1.5];
+pub(crate) const SETTINGS_SCREEN_SHAKE_STEP: f32 = 0.25;
+pub(crate) const SETTINGS_MAX_SCREEN_SHAKE: f32 = 2.0;
+
+// Used to pick the rewards and upgrades, they can not be bound to an action
+pub(crate) const SETTINGS_RESERVED_KEYS: [KeyCode; 4] = [
+    KeyCode::Digit1,
+    KeyCode::Digit2,
+    KeyCode::Digit3,
+    KeyCode::Digit4,
+];
+
+// Actions without a key, like a new power whose default key is already
+// taken
+pub(crate) const SETTINGS_UNBOUND_KEY: KeyCode =
+    KeyCode::Unidentified(bevy::input::keyboard::NativeKeyCode::Unidentified);
+
+// Damage numbers shown over the enemies
+pub(crate) const DAMAGE_NUMBER_FONT_SIZE: f32 = 14.0;
+pub(crate) const DAMAGE_NUMBER_SECONDS: f32 = 0.6;
+pub(crate) const DAMAGE_NUMBER_RISE_SPEED: f32 = 40.0;
+// Up to this far from the center of the enemy, so hits do not overlap
+pub(crate) const DAMAGE_NUMBER_SPREAD: f32 = 10.0;
@@ -0,0 +1,54 @@
+// Size of each tile on the tileset texture
+pub(crate) const TILE_SIZE_PX: u32 = 32;
+pub(crate) const TILESET_COLUMNS: u32 = 13;
+pub(crate) const TILESET_ROWS: u32 = 6;
+
+// Where each kind of tile is on the tileset. Walkable tiles are not drawn,
+// the level background is seen through them.
+pub(crate) const SOLID_TILE_INDEX: usize = 14;
+pub(crate) const HAZARD_TILE_INDEX: usize = 19;
+
+pub(crate) const HAZARD_TILE_DAMAGE: f32 = 4.;
+pub const HAZARD_TILE_DAMAGE_INTERVAL_MILLIS: u64 = 500;
+
+// One character per tile: '.' walkable, '#' solid and '~' hazard. Obstacles
+// go on their top left tile: 'R' rock, 'C' crate and 'M' digging machine.
+// Breakable props: 'E' energy pack and 'B' boulder
+pub(crate) const LEVEL_LAYOUTS: [&str; 3] = [
+    include_str!("../../assets/levels/level_1.txt"),
+    include_str!("../../assets/levels/level_2.txt"),
+    include_str!("../../assets/levels/level_3.txt"),
+];
+
+// Procedural arenas, used by the levels without a layout. Same size as the
+// layouts.
+pub(crate) const ARENA_COLUMNS: usize = 60;
+pub(crate) const ARENA_ROWS: usize = 34;
+// Spreads the seeds of each level of a run
+pub(crate) const ARENA_LEVEL_SEED_STEP: u64 = 7_919;
+// Set it to a run seed to play the same arenas again
+pub(crate) const ARENA_RUN_SEED_ENV_VAR: &str = "AREA_25_5_RUN_SEED";
+// Open field around the player start, in tiles
+pub(crate) const ARENA_START_CLEARING_RADIUS: f32 = 6.;
+// Tiles between the map edges and anything generated
+pub(crate) const ARENA_MARGIN: usize = 3;
+pub(crate) const ARENA_OBSTACLE_CLUSTERS: u32 = 6;
+pub(crate) const ARENA_CLUSTER_MIN_TILES: u32 = 5;
+pub(crate) const ARENA_CLUSTER_MAX_TILES: u32 = 14;
+// Walls with a small gap the enemies have to squeeze through
+pub(crate) const ARENA_CHOKEPOINT_WALLS: u32 = 2;
+pub(crate) const ARENA_CHOKEPOINT_GAP: usize = 3;
+pub(crate) const ARENA_HAZARD_POOLS: u32 = 1;
+// Rocks, crates and machines on top of the clusters
+pub(crate) const ARENA_OBSTACLES: u32 = 8;
+pub(crate) const ARENA_PROPS: u32 = 10;
+// Where the enemies come from, as far as possible from the player start
+pub(crate) const ARENA_SPAWN_ZONES: usize = 4;
+pub(crate) const ARENA_SPAWN_ZONE_SIZE: usize = 3;
+pub(crate) const ARENA_SPAWN_ZONE_MIN_DISTANCE: f32 = 18.;
+
+// Pathfinding. Diagonal steps cost about sqrt(2) times a straight one and
+// the enemies go around the hazard tiles unless it is a much longer way.
+pub(crate) const NAV_TILE_COST: u32 = 10;
+pub(crate) const NAV_DIAGONAL_COST: u32 = 14;
+pub(crate) const NAV_HAZARD_TILE_COST: u32 = 50;
@@ -0,0 +1,210 @@
+use crate::{
+    prelude::*, BaseCamera, CleanupWhenPlayerDies, DashCharges, DashDistance, Player, Settings,
+};
+
+/// Short burst of movement along the WASD direction. While dashing, and a
+/// bit after, the player can not be hurt.
+#[cfg_attr(not(feature = "web"), derive(Reflect, Component, Debug, Clone))]
+#[cfg_attr(not(feature = "web"), reflect(Component))]
+#[cfg_attr(feature = "web", derive(Component, Debug, Clone))]
+pub struct Dash {
+    pub charges: u32,
+    // Last value of the `DashCharges` stat, to notice when it changes
+    pub max_charges: u32,
+    // Gives back one charge every time it finishes
+    pub cooldown: Timer,
+    pub direction: Vec2,
+    // Running while the player is dashing
+    pub timer: Timer,
+    pub trail_timer: Timer,
+}
+
+impl Default for Dash {
+    fn default() -> Self {
+        let mut timer = Timer::from_seconds(DASH_SECONDS, TimerMode::Once);
+        // Not dashing when the player spawns
+        timer.tick(timer.duration());
+
+        Self {
+            charges: PLAYER_DASH_CHARGES,
+            max_charges: PLAYER_DASH_CHARGES,
+            cooldown: Timer::from_seconds(DASH_COOLDOWN_SECONDS, TimerMode::Repeating),
+            direction: Vec2::ZERO,
+            timer,
+            trail_timer: Timer::from_seconds(DASH_TRAIL_INTERVAL_SECONDS, TimerMode::Repeating),
+        }
+    }
+}
+
+impl Dash {
+    pub fn is_dashing(&self) -> bool {
+        !self.timer.finished()
+    }
+
+    /// How fast the player moves so they cover the whole distance during the
+    /// dash.
+    pub fn speed(&self, distance: &DashDistance) -> f32 {
+        distance.0 / DASH_SECONDS
+    }
+}
+
+/// I-frames. Nothing hurts the player while they have it.
+#[derive(Component, Clone)]
+pub struct Invulnerable {
+    pub timer: Timer,
+}
+
+/// Faded copy of the player left behind while dashing.
+#[derive(Component, Clone)]
+pub struct DashTrail {
+    pub timer: Timer,
+}
+
+pub fn start_dash(
+    mut commands: Commands,
+    keyboard_input: Res<ButtonInput<KeyCode>>,
+    settings: Res<Settings>,
+    mut player_query: Query<(Entity, &mut Dash), With<Player>>,
+) {
+    if !keyboard_input.just_pressed(settings.key_bindings.dash) {
+        return;
+    }
+
+    let Ok((player_entity, mut dash)) = player_query.get_single_mut() else {
+        return;
+    };
+
+    if dash.charges == 0 || dash.is_dashing() {
+        return;
+    }
+
+    // There is no direction to dash to when standing still
+    let direction = settings.key_bindings.movement_direction(&keyboard_input);
+    let Some(direction) = direction.try_normalize() else {
+        return;
+    };
+
+    dash.charges -= 1;
+    dash.direction = direction;
+    dash.timer.reset();
+    dash.trail_timer.reset();
+
+    commands.entity(player_entity).insert(Invulnerable {
+        timer: Timer::from_seconds(DASH_INVULNERABILITY_SECONDS, TimerMode::Once),
+    });
+}
+
+pub fn tick_dash(
+    mut commands: Commands,
+    time: Res<Time>,
+    mut player_query: Query<
+        (
+            Entity,
+            &mut Dash,
+            &DashCharges,
+            &Transform,
+            &Sprite,
+            &Handle<Image>,
+            &TextureAtlas,
+            Option<&mut Invulnerable>,
+        ),
+        With<Player>,
+    >,
+    base_camera: Query<&Transform, (With<BaseCamera>, Without<Player>)>,
+) {
+    let Ok((
+        player_entity,
+        mut dash,
+        dash_charges,
+        player_transform,
+        player_sprite,
+        player_texture,
+        player_atlas,
+        invulnerable,
+    )) = player_query.get_single_mut()
+    else {
+        return;
+    };
+
+    if let Some(mut invulnerable) = invulnerable {
+        invulnerable.timer.tick(time.delta());
+        if invulnerable.timer.finished() {
+            commands.entity(player_entity).remove::<Invulnerable>();
+        }
+    }
+
+    // A new charge is ready right away, a lost one is gone
+    if dash.max_charges != dash_charges.0 {
+        let gained = dash_charges.0.saturating_sub(dash.max_charges);
+        dash.charges = (dash.charges + gained).min(dash_charges.0);
+        dash.max_charges = dash_charges.0;
+    }
+
+    if dash.charges < dash_charges.0 {
+        dash.cooldown.tick(time.delta());
+        if dash.cooldown.just_finished() {
+            dash.charges += 1;
+        }
+    }
+
+    if !dash.is_dashing() {
+        return;
+    }
+
+    dash.timer.tick(time.delta());
+    dash.trail_timer.tick(time.delta());
+    if !dash.trail_timer.just_finished() {
+        return;
+    }
+
+    let Ok(base_camera_transform) = base_camera.get_single() else {
+        return;
+    };
+
+    // The player is drawn by its own camera, so the trail is placed on the
+    // map where the player is
+    let mut trail_color = player_sprite.color.to_srgba();
+    trail_color.alpha *= DASH_TRAIL_ALPHA;
+
+    commands.spawn((
+        SpriteBundle {
+            texture: player_texture.clone(),
+            sprite: Sprite {
+                color: trail_color.into(),
+                ..player_sprite.clone()
+            },
+            transform: Transform {
+                translation: (player_transform.translation.truncate()
+                    + base_camera_transform.translation.truncate())
+                .extend(CHAR_Z_INDEX),
+                ..*player_transform
+            },
+            ..default()
+        },
+        player_atlas.clone(),
+        DashTrail {
+            timer: Timer::from_seconds(DASH_TRAIL_FADE_SECONDS, TimerMode::Once),
+        },
+        Name::new("DashTrail"),
+        BASE_LAYER,
+        CleanupWhenPlayerDies,
+    ));
+}
+
+pub fn fade_dash_trails(
+    mut commands: Commands,
+    time: Res<Time>,
+    mut trails: Query<(Entity, &mut Sprite, &mut DashTrail)>,
+) {
+    for (trail_entity, mut trail_sprite, mut trail) in trails.iter_mut() {
+        trail.timer.tick(time.delta());
+        if trail.timer.finished() {
+            commands.entity(trail_entity).despawn();
+            continue;
+        }
+
+        trail_sprite
+            .color
+            .set_alpha(DASH_TRAIL_ALPHA * trail.timer.fraction_remaining());
+    }
+}
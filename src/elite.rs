@@ -0,0 +1,278 @@
+use rand::Rng;
+
+use crate::{
+    prelude::*, AnimationIndices, AnimationTimer, BaseCamera, Boss, CurrentGameLevel, Enemy,
+    EnemyHealthChanged, Health, Player, Tilemap,
+};
+
+#[derive(Component, Debug, Clone)]
+pub struct Elite {
+    pub affixes: Vec<EliteAffixEnum>,
+    // Damage the shield can still absorb
+    pub shield: f32,
+    pub teleport_timer: Timer,
+}
+
+impl Elite {
+    pub fn has(&self, affix: EliteAffixEnum) -> bool {
+        self.affixes.contains(&affix)
+    }
+
+    pub fn speed_multiplier(&self) -> f32 {
+        if self.has(EliteAffixEnum::Fast) {
+            ELITE_FAST_SPEED_MULTIPLIER
+        } else {
+            1.0
+        }
+    }
+
+    /// Applies the defensive affixes and returns the damage that goes
+    /// through to the health of the elite.
+    pub fn absorb_damage(&mut self, damage: f32) -> f32 {
+        let mut damage = damage;
+
+        if self.has(EliteAffixEnum::Armored) {
+            damage *= ELITE_ARMORED_DAMAGE_TAKEN_MULTIPLIER;
+        }
+
+        let absorbed = damage.min(self.shield);
+        self.shield -= absorbed;
+
+        damage - absorbed
+    }
+}
+
+/// Orcs spawned when a splitting elite dies. They never become elites.
+#[derive(Component, Clone)]
+pub struct SplitFromElite;
+
+#[derive(Component, Clone)]
+pub struct EliteOutline;
+
+#[derive(Component, Clone)]
+pub struct EliteExplosion {
+    pub damage: f32,
+    pub exploded: bool,
+    pub timer: Timer,
+}
+
+/// Creeps have a chance to become elites when they spawn. The chance and the
+/// number of affixes grow with the game level.
+pub fn roll_elite_affixes(
+    mut commands: Commands,
+    asset_server: Res<AssetServer>,
+    current_game_level: Res<CurrentGameLevel>,
+    mut enemies: Query<
+        (
+            Entity,
+            &mut Enemy,
+            &mut Health,
+            &mut Name,
+            &Handle<Image>,
+            &TextureAtlas,
+            &AnimationIndices,
+            &AnimationTimer,
+        ),
+        (Added<Enemy>, Without<Boss>, Without<SplitFromElite>),
+    >,
+) {
+    let level = current_game_level.0;
+    let chance = (ELITE_BASE_CHANCE + ELITE_CHANCE_PER_LEVEL * level as f32).min(ELITE_MAX_CHANCE);
+    let mut rng = rand::thread_rng();
+
+    for (
+        enemy_entity,
+        mut enemy,
+        mut enemy_health,
+        mut enemy_name,
+        texture,
+        atlas,
+        animation_indices,
+        animation_timer,
+    ) in enemies.iter_mut()
+    {
+        if rng.gen::<f32>() >= chance {
+            continue;
+        }
+
+        let number_of_affixes =
+            (1 + (level / ELITE_LEVELS_PER_EXTRA_AFFIX) as usize).min(ELITE_MAX_AFFIXES);
+        let affixes = choose_elite_affixes(&mut rng, level, number_of_affixes);
+        let Some(first_affix) = affixes.first() else {
+            continue;
+        };
+
+        enemy.max_health *= ELITE_HEALTH_MULTIPLIER;
+        enemy_health.0 *= ELITE_HEALTH_MULTIPLIER;
+
+        let prefix = affixes
+            .iter()
+            .map(|affix| affix.name_prefix)
+            .collect::<Vec<_>>()
+            .join(" ");
+        let name = format!("{} {:?}", prefix, enemy.class);
+        *enemy_name = Name::new(name.clone());
+
+        let affixes: Vec<EliteAffixEnum> = affixes.iter().map(|affix| affix.affix).collect();
+        let shield = if affixes.contains(&EliteAffixEnum::Shielded) {
+            enemy.max_health * ELITE_SHIELD_HEALTH_FRACTION
+        } else {
+            0.
+        };
+
+        // The outline is a bigger copy of the sprite behind the elite
+        let outline = commands
+            .spawn((
+                SpriteBundle {
+                    texture: texture.clone(),
+                    sprite: Sprite {
+                        color: first_affix.color,
+                        ..default()
+                    },
+                    transform: Transform {
+                        translation: Vec3::new(0., 0., -0.1),
+                        scale: Vec3::splat(ELITE_OUTLINE_SCALE),
+                        ..default()
+                    },
+                    ..default()
+                },
+                atlas.clone(),
+                animation_indices.clone(),
+                animation_timer.clone(),
+                EliteOutline,
+                BASE_LAYER,
+            ))
+            .id();
+
+        let label = commands
+            .spawn((
+                Text2dBundle {
+                    text: Text::from_section(
+                        name,
+                        TextStyle {
+                            font: asset_server.load("fonts/FiraSans-Bold.ttf"),
+                            font_size: ELITE_LABEL_FONT_SIZE,
+                            color: first_affix.color,
+                        },
+                    ),
+                    transform: Transform::from_xyz(0., 22., 0.1),
+                    ..default()
+                },
+                BASE_LAYER,
+            ))
+            .id();
+
+        commands
+            .entity(enemy_entity)
+            .insert(Elite {
+                affixes,
+                shield,
+                teleport_timer: Timer::from_seconds(
+                    ELITE_TELEPORT_COOLDOWN_SECONDS,
+                    TimerMode::Repeating,
+                ),
+            })
+            .push_children(&[outline, label]);
+
+        commands.trigger(EnemyHealthChanged {
+            health: enemy_health.0,
+            max_health: enemy.max_health,
+            entity: enemy_entity,
+        });
+    }
+}
+
+/// Weighted random choice, without repeating, of the affixes available at
+/// the current level.
+fn choose_elite_affixes(
+    rng: &mut impl Rng,
+    level: u16,
+    number_of_affixes: usize,
+) -> Vec<EliteAffix> {
+    let mut available: Vec<EliteAffix> = ELITE_AFFIXES
+        .iter()
+        .filter(|affix| affix.min_level <= level)
+        .copied()
+        .collect();
+    let mut chosen = vec![];
+
+    while chosen.len() < number_of_affixes && !available.is_empty() {
+        let total_weight: u32 = available.iter().map(|affix| affix.weight).sum();
+        let mut roll = rng.gen_range(0..total_weight.max(1));
+
+        let idx = available
+            .iter()
+            .position(|affix| {
+                if roll < affix.weight {
+                    return true;
+                }
+                roll -= affix.weight;
+                false
+            })
+            .unwrap_or(0);
+
+        chosen.push(available.remove(idx));
+    }
+
+    chosen
+}
+
+pub fn teleport_elites(
+    time: Res<Time>,
+    mut elites: Query<(&mut Transform, &mut Elite), Without<Player>>,
+    player_query: Query<&Transform, (With<Player>, Without<Elite>)>,
+    base_camera: Query<&Transform, (With<BaseCamera>, Without<Player>, Without<Elite>)>,
+    tilemap: Option<Res<Tilemap>>,
+) {
+    let Ok(player_transform) = player_query.get_single() else {
+        return;
+    };
+
+    let Ok(base_camera_transform) = base_camera.get_single() else {
+        return;
+    };
+
+    let player_position = Vec2::new(
+        player_transform.translation.x + base_camera_transform.translation.x,
+        player_transform.translation.y + base_camera_transform.translation.y,
+    );
+
+    let limit_x_left =
+        (-BACKGROUND_TEXTURE_RESOLUTION.x_px * BACKGROUND_TEXTURE_SCALE + PLAYER_X_MARGIN) / 2.0;
+    let limit_x_right =
+        (BACKGROUND_TEXTURE_RESOLUTION.x_px * BACKGROUND_TEXTURE_SCALE - PLAYER_X_MARGIN) / 2.0;
+    let limit_y_bottom =
+        (-BACKGROUND_TEXTURE_RESOLUTION.y_px * BACKGROUND_TEXTURE_SCALE + PLAYER_Y_MARGIN) / 2.0;
+    let limit_y_top =
+        (BACKGROUND_TEXTURE_RESOLUTION.y_px * BACKGROUND_TEXTURE_SCALE - PLAYER_Y_MARGIN) / 2.0;
+
+    let mut rng = rand::thread_rng();
+
+    for (mut transform, mut elite) in elites.iter_mut() {
+        if !elite.has(EliteAffixEnum::Teleporting) {
+            continue;
+        }
+
+        elite.teleport_timer.tick(time.delta());
+        if !elite.teleport_timer.just_finished() {
+            continue;
+        }
+
+        let angle = rng.gen_range(0.0..std::f32::consts::TAU);
+        let target = player_position + Vec2::from_angle(angle) * ELITE_TELEPORT_DISTANCE;
+        let mut target = Vec2::new(
+            target.x.clamp(limit_x_left, limit_x_right),
+            target.y.clamp(limit_y_bottom, limit_y_top),
+        );
+
+        // Never inside a wall
+        if let Some(tilemap) = &tilemap {
+            if tilemap.is_solid(target) {
+                target = tilemap.closest_walkable_position(target).unwrap_or(target);
+            }
+        }
+
+        transform.translation.x = target.x;
+        transform.translation.y = target.y;
+    }
+}
@@ -1,9 +1,12 @@
 use crate::{
     prelude::*,
-    spawn_health_bar,
-    util::{get_enemy_sprite_based_on_enemy_class, get_random_vec3},
-    AmmoBundle, AnimationIndices, AnimationTimer, CleanupWhenPlayerDies, Damage, Health,
-    SpritesResources, WeaponBundle,
+    spawn_bullet_pattern_emitter, spawn_health_bar,
+    util::{
+        get_boss_moveset_based_on_enemy_class, get_enemy_sprite_based_on_enemy_class,
+        get_random_vec3,
+    },
+    AmmoBundle, AnimationIndices, AnimationTimer, Boss, BossSpawned, CleanupWhenPlayerDies, Damage,
+    Health, NavPath, Perception, SpritesResources, WeaponBundle,
 };
 
 #[derive(Component, Clone)]
@@ -14,6 +17,8 @@ pub struct Enemy {
     pub max_health: f32,
     pub width_collider: f32,
     pub height_collider: f32,
+    // Last velocity the enemy moved with. Used by the flocking steering.
+    pub velocity: Vec2,
 }
 
 #[derive(Bundle, Clone)]
@@ -21,6 +26,8 @@ pub(crate) struct EnemyBundle {
     pub(crate) marker: Enemy,
     pub(crate) health: Health,
     pub(crate) damage: Damage,
+    pub(crate) perception: Perception,
+    pub(crate) nav_path: NavPath,
     pub(crate) sprite: SpriteBundle,
     pub(crate) atlas: TextureAtlas,
     pub(crate) animation_indices: AnimationIndices,
@@ -84,10 +91,13 @@ impl EnemyBundle {
                 max_health,
                 width_collider,
                 height_collider,
+                velocity: Vec2::ZERO,
             },
             name: Name::new("Enemy"),
             health: Health(health),
             damage: Damage(damage),
+            perception: Perception::default(),
+            nav_path: NavPath::default(),
             sprite: SpriteBundle {
                 texture: asset_server.load(enemy_sprite.source),
                 transform: Transform {
@@ -124,22 +134,25 @@ pub fn spawn_enemy(
     let health_bar_translation = Vec3::new(2.0, 15.0, 0.0);
     let enemy_class = enemy_by_level.enemy.class.clone();
     let quantity = enemy_by_level.quantity;
+    let bullet_pattern = enemy_by_level.enemy.bullet_pattern;
 
     match enemy_class {
-        EnemyClassEnum::Orc => spawn_orc_enemy(
-            commands,
-            asset_server,
-            sprites,
-            texture_atlas_layout,
-            meshes,
-            materials,
-            health,
-            damage,
-            scale,
-            health_bar_translation,
-            quantity,
-            None,
-        ),
+        EnemyClassEnum::Orc => {
+            spawn_orc_enemy(
+                commands,
+                asset_server,
+                sprites,
+                texture_atlas_layout,
+                meshes,
+                materials,
+                health,
+                damage,
+                scale,
+                health_bar_translation,
+                quantity,
+                None,
+            );
+        }
         EnemyClassEnum::Mage => spawn_mage_enemy(
             commands,
             asset_server,
@@ -152,6 +165,7 @@ pub fn spawn_enemy(
             scale,
             health_bar_translation,
             quantity,
+            bullet_pattern,
         ),
         _ => (),
     }
@@ -171,7 +185,9 @@ pub(crate) fn spawn_orc_enemy(
     health_bar_translation: Vec3,
     quantity: u32,
     spawning_position: Option<Vec3>,
-) {
+) -> Vec<Entity> {
+    let mut enemies = vec![];
+
     for idx in 1..=quantity as usize {
         let spawning_pos = spawning_position.unwrap_or(get_random_vec3(idx as u64, None));
 
@@ -200,8 +216,11 @@ pub(crate) fn spawn_orc_enemy(
             health_bar_translation,
             BASE_LAYER,
         );
-        commands.spawn(bundle).push_children(&[health_bar]);
+        let enemy_entity = commands.spawn(bundle).push_children(&[health_bar]).id();
+        enemies.push(enemy_entity);
     }
+
+    enemies
 }
 
 fn spawn_mage_enemy(
@@ -217,6 +236,7 @@ fn spawn_mage_enemy(
     scale: Vec3,
     health_bar_translation: Vec3,
     quantity: u32,
+    bullet_pattern: Option<&'static str>,
 ) {
     let weapon_direction = Vec3::ZERO;
     let weapon_pos = Vec3::new(8.0, 0.0, CHAR_Z_INDEX);
@@ -293,6 +313,10 @@ fn spawn_mage_enemy(
                 });
             })
             .push_children(&[health_bar_entity]);
+
+        if let Some(pattern_name) = bullet_pattern {
+            spawn_bullet_pattern_emitter(commands, enemy_mage_entity, pattern_name, 1.0, true);
+        }
     }
 }
 
@@ -301,15 +325,14 @@ pub(crate) fn spawn_boss(
     asset_server: &Res<AssetServer>,
     sprites: &Res<SpritesResources>,
     texture_atlas_layout: &mut ResMut<Assets<TextureAtlasLayout>>,
-    meshes: &mut ResMut<Assets<Mesh>>,
-    materials: &mut ResMut<Assets<ColorMaterial>>,
 
     health: f32,
     damage: f32,
     scale: Vec3,
-    health_bar_translation: Vec3,
     quantity: u32,
     boss_class: EnemyClassEnum,
+    bullet_pattern: Option<&'static str>,
+    spawning_position: Option<Vec3>,
 ) {
     let weapon_direction = Vec3::ZERO;
     let weapon_pos = Vec3::new(8.0, 0.0, CHAR_Z_INDEX);
@@ -321,13 +344,13 @@ pub(crate) fn spawn_boss(
     let ammo_rotation = Quat::default();
 
     for idx in 1..=quantity as usize {
-        let random_spawning_pos = get_random_vec3(idx as u64, None);
+        let spawning_pos = spawning_position.unwrap_or(get_random_vec3(idx as u64, None));
 
         let bundle = EnemyBundle::idle(
             texture_atlas_layout,
             asset_server,
             sprites,
-            random_spawning_pos,
+            spawning_pos,
             health,
             damage,
             scale,
@@ -337,7 +360,11 @@ pub(crate) fn spawn_boss(
             ENEMY_COLLISION_BOX_HEIGHT * BOSS_SCALE,
         );
 
-        let enemy_mage_entity = commands.spawn(bundle).id();
+        let Some(moveset) = get_boss_moveset_based_on_enemy_class(boss_class.clone()) else {
+            return;
+        };
+
+        let enemy_mage_entity = commands.spawn((bundle, Boss::new(&moveset))).id();
 
         let weapon_bundle = WeaponBundle::new(
             texture_atlas_layout,
@@ -368,23 +395,19 @@ pub(crate) fn spawn_boss(
             crate::util::EquippedTypeEnum::Enemy,
         );
 
-        let health_bar_entity = spawn_health_bar(
-            commands,
-            meshes,
-            materials,
-            health,
-            health,
-            health_bar_translation,
-            layer.clone(),
-        );
+        // Bosses use the screen-wide health bar instead of a small one
+        commands.entity(enemy_mage_entity).with_children(|parent| {
+            parent.spawn(weapon_bundle.clone()).with_children(|parent| {
+                parent.spawn(ammo_bundle.clone());
+            });
+        });
 
-        commands
-            .entity(enemy_mage_entity)
-            .with_children(|parent| {
-                parent.spawn(weapon_bundle.clone()).with_children(|parent| {
-                    parent.spawn(ammo_bundle.clone());
-                });
-            })
-            .push_children(&[health_bar_entity]);
+        if let Some(pattern_name) = bullet_pattern {
+            spawn_bullet_pattern_emitter(commands, enemy_mage_entity, pattern_name, 1.0, true);
+        }
+
+        commands.trigger(BossSpawned {
+            entity: enemy_mage_entity,
+        });
     }
 }
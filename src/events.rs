@@ -1,29 +1,34 @@
 use chrono::Utc;
 
-use bevy::{sprite::Mesh2dHandle, window::WindowResized};
+use bevy::sprite::{MaterialMesh2dBundle, Mesh2dHandle};
 
 use crate::{
     audio::hit_weapon_audio,
-    equip_player_with_power,
+    drop_loot, equip_player_with_power,
     game_actions::shoot_at_enemies,
+    get_luck, get_random_spawn_position,
     player::Player,
     prelude::*,
-    render_background_texture, spawn_boss,
+    render_background_texture, spawn_boss, spawn_boss_health_bar_ui, spawn_damage_number,
     spawn_enemy, spawn_health_bar, spawn_health_ui_bar, spawn_item, spawn_mana_ui_bar,
-    spawn_power_ui, spawn_profile_ui, spawn_weapon, spawn_weapon_ui,
+    spawn_orc_enemy, spawn_power_ui, spawn_power_upgrade_choice_ui, spawn_profile_ui,
+    spawn_prop_break_effects, spawn_weapon, spawn_weapon_ui,
     ui::HealthBar,
     util::{
-        get_boss_type_based_on_game_level, get_item_sprite_based_on_item_type,
-        get_key_code_based_on_power_type, get_power_sprite_based_on_power_type, get_random_chance,
+        get_boss_moveset_based_on_enemy_class, get_boss_type_based_on_game_level,
+        get_item_sprite_based_on_item_type, get_loot_table_based_on_enemy_class,
         get_weapon_sprite_based_on_weapon_type,
     },
-    AmmoBundle, Armor, BaseCamera, Buff, BuffGroup, BuffsUI, CircleOfDeath, CleanupWhenPlayerDies,
-    ContainerBuffsUI, CurrentBoss, CurrentGameLevel, CurrentGameLevelUI, CurrentScore, CurrentTime,
-    CurrentTimeUI, CurrentWave, CurrentWaveUI, Damage, EnemiesLeftUI, Enemy, EnemyWaves, GameState, Health, HealthBarUI, Item, ItemTypeEnum, ItemWaves,
-    Mana, ManaBarUI, PlayerProfileUI, PlayerProfileUIBarsRootNode, Power,
-    PowerLevelUI, PowerLevels, PowerSpriteUI, PowerUI, PowerUIRootNode, ScoreUI, Speed,
-    SpritesResources, TileBackground, Weapon, WeaponBundle, WeaponUI, WeaponWaves,
-    WindowResolutionResource,
+    AmmoBundle, BaseCamera, BossHealthBarFillUI, BossHealthBarUI, BossIntro, BossNameUI,
+    BossPhaseUI, Buff, BuffGroup, BuffsUI, CameraEffects, CleanupWhenPlayerDies, ContainerBuffsUI,
+    CurrentBoss, CurrentGameLevel, CurrentGameLevelUI, CurrentScore, CurrentTime, CurrentTimeUI,
+    CurrentWave, CurrentWaveUI, Damage, Elite, EliteExplosion, EnemiesLeftUI, Enemy, EnemyWaves,
+    GameState, Health, HealthBarUI, HealthRegen, Item, ItemTypeEnum, ItemWaves, KeyActionEnum,
+    LifeSteal, Luck, Mana, ManaBarUI, ManaRegen, MaxHealth, MaxMana, PendingPowerUpgrade,
+    PlayerProfileUI, PlayerProfileUIBarsRootNode, Power, PowerBranchUI, PowerCooldownMaterial,
+    PowerLevelUI, PowerRegistry, PowerSpriteUI, PowerUI, PowerUIRootNode, PropKind, RunSeed,
+    ScoreUI, Settings, SplitFromElite, SpritesResources, StatModifierSource, StatModifiers,
+    Stealthed, TileBackground, Tilemap, Weapon, WeaponBundle, WeaponUI, WeaponWaves,
 };
 
 #[derive(Event)]
@@ -41,6 +46,13 @@ pub struct PlayerManaChanged {
     pub mana: f32,
 }
 
+/// The player hurt an enemy, with their weapon, a power or a buff.
+#[derive(Event)]
+pub struct PlayerDealtDamage {
+    pub damage: f32,
+    pub entity: Entity,
+}
+
 #[derive(Event)]
 pub struct PlayerSpawned {
     pub player_entity_id: Entity,
@@ -56,9 +68,32 @@ pub struct EnemyHealthChanged {
 #[derive(Event)]
 pub struct AllEnemiesDied;
 
+/// Triggered right before a dead enemy is despawned, so observers can still
+/// read its components.
+#[derive(Event)]
+pub struct EnemyDied {
+    pub entity: Entity,
+}
+
 #[derive(Event)]
 pub struct CurrentWaveChanged(pub u16);
 
+#[derive(Event)]
+pub struct BossSpawned {
+    pub entity: Entity,
+}
+
+#[derive(Event)]
+pub struct BossPhaseChanged {
+    pub entity: Entity,
+    pub phase: usize,
+}
+
+#[derive(Event)]
+pub struct BossEnraged {
+    pub entity: Entity,
+}
+
 #[derive(Event)]
 pub struct SpawnEntitiesForNewWave;
 
@@ -102,23 +137,32 @@ pub struct WeaponFound {
 #[derive(Event)]
 pub struct PowerFound;
 
-// Used for instances of Power (like CircleOfDeath)
-// that need to be despawned once the entity (like the Annulus)
-// is despawned
 #[derive(Event)]
-pub struct DespawnPower(pub PowerTypeEnum);
+pub struct OnUpdatePowerUI {
+    power_type: &'static str,
+    key_action: KeyActionEnum,
+    level: u32,
+    branch: Option<&'static str>,
+}
 
 #[derive(Event)]
-pub struct OnUpdatePowerUI {
-    power_type: PowerTypeEnum,
-    keycode: KeyCode,
+pub struct PowerUpgradeChosen {
+    pub power_entity: Entity,
+    pub choice: usize,
 }
 
+/// Shakes the cameras, more trauma makes a stronger shake.
 #[derive(Event)]
-pub struct MaybeSpawnHealthPack;
+pub struct ScreenShake {
+    pub trauma: f32,
+}
 
+/// Triggered when a prop is destroyed, right where it was.
 #[derive(Event)]
-pub struct MaybeSpawnManaPack;
+pub struct PropBroken {
+    pub kind: PropKind,
+    pub position: Vec2,
+}
 
 #[derive(Event)]
 pub struct GameOver;
@@ -172,6 +216,7 @@ fn modify_above_player_health(
     player_health_bar_query: Query<Entity, With<HealthBar>>,
 
     health: f32,
+    max_health: f32,
 ) {
     let health_bar_translation = Vec3::new(2.0, 12.0, 0.0);
 
@@ -193,7 +238,7 @@ fn modify_above_player_health(
             &mut meshes,
             &mut materials,
             health,
-            PLAYER_HEALTH,
+            max_health,
             health_bar_translation,
             PLAYER_LAYER,
         );
@@ -215,6 +260,7 @@ fn modify_player_profile_ui_health(
     player_health_ui_query: Query<(Entity, &HealthBarUI)>,
 
     health: f32,
+    max_health: f32,
 ) {
     let Ok((_, player_profile_children, _)) = player_profile_ui_query.get_single() else {
         return;
@@ -239,7 +285,7 @@ fn modify_player_profile_ui_health(
                 &mut player_bar_ui_root_node_query,
                 &player_health_ui_query,
                 health,
-                PLAYER_HEALTH,
+                max_health,
             );
 
             break;
@@ -255,6 +301,7 @@ pub fn on_player_health_changed(
 
     // Just above player bar
     player_query: Query<(Entity, &Children), With<Player>>,
+    player_max_health_query: Query<&MaxHealth, With<Player>>,
     player_health_bar_query: Query<Entity, With<HealthBar>>,
 
     // Top-left UI
@@ -264,6 +311,10 @@ pub fn on_player_health_changed(
 ) {
     let event = trigger.event();
     let health = event.health;
+    // The player is not there yet when the game is restarted
+    let max_health = player_max_health_query
+        .get_single()
+        .map_or(PLAYER_HEALTH, |max_health| max_health.0);
 
     modify_above_player_health(
         &mut commands,
@@ -272,6 +323,7 @@ pub fn on_player_health_changed(
         player_query,
         player_health_bar_query,
         health,
+        max_health,
     );
 
     modify_player_profile_ui_health(
@@ -280,6 +332,7 @@ pub fn on_player_health_changed(
         player_bar_ui_root_node_query,
         player_health_ui_query,
         health,
+        max_health,
     );
 }
 
@@ -291,6 +344,7 @@ pub fn on_player_mana_changed(
     // This is the container of the mana and health bars
     mut player_bar_ui_root_node_query: Query<(Entity, &Children, &PlayerProfileUIBarsRootNode)>,
     player_mana_ui_query: Query<(Entity, &ManaBarUI)>,
+    player_max_mana_query: Query<&MaxMana, With<Player>>,
 ) {
     let Ok((_, player_profile_children, _)) = player_profile_ui_query.get_single() else {
         return;
@@ -298,6 +352,9 @@ pub fn on_player_mana_changed(
 
     let event = trigger.event();
     let mana = event.mana;
+    let max_mana = player_max_mana_query
+        .get_single()
+        .map_or(PLAYER_MANA, |max_mana| max_mana.0);
 
     for &child in player_profile_children.iter() {
         let Ok((_, root_node_bar_children, _)) = player_bar_ui_root_node_query.get(child) else {
@@ -318,7 +375,7 @@ pub fn on_player_mana_changed(
                 &mut player_bar_ui_root_node_query,
                 &player_mana_ui_query,
                 mana,
-                PLAYER_MANA,
+                max_mana,
             );
 
             break;
@@ -464,8 +521,6 @@ pub fn on_all_enemies_died(
     mut current_boss: ResMut<CurrentBoss>,
     mut next_state: ResMut<NextState<GameState>>,
     mut texture_atlas_layout: ResMut<Assets<TextureAtlasLayout>>,
-    mut meshes: ResMut<Assets<Mesh>>,
-    mut materials: ResMut<Assets<ColorMaterial>>,
 
     current_wave: Res<CurrentWave>,
     current_time: Res<CurrentTime>,
@@ -473,6 +528,7 @@ pub fn on_all_enemies_died(
     player_state: Res<State<GameState>>,
     asset_server: Res<AssetServer>,
     sprites: Res<SpritesResources>,
+    tilemap: Option<Res<Tilemap>>,
 ) {
     // TODO: change this to be inside the event handler
     // Add multiplier to score based on the time left
@@ -526,22 +582,25 @@ pub fn on_all_enemies_died(
     }
 
     let boss = get_boss_type_based_on_game_level(current_game_level.0);
-    let health_bar_translation = Vec3::new(2.0, 15.0, 0.0);
     let quantity = 1;
+    // Placed right away, the boss intro shows where it is
+    let spawning_position = tilemap
+        .as_deref()
+        .and_then(get_random_spawn_position)
+        .map(|position| position.extend(CHAR_Z_INDEX));
 
     spawn_boss(
         &mut commands,
         &asset_server,
         &sprites,
         &mut texture_atlas_layout,
-        &mut meshes,
-        &mut materials,
         boss.health,
         boss.base_damage,
         boss.scale,
-        health_bar_translation,
         quantity,
         boss.class,
+        boss.bullet_pattern,
+        spawning_position,
     );
 
     // update current boss
@@ -551,6 +610,161 @@ pub fn on_all_enemies_died(
     commands.trigger(UpdateAliveEnemiesUI);
 }
 
+pub fn on_elite_died(
+    trigger: Trigger<EnemyDied>,
+    mut commands: Commands,
+    mut texture_atlas_layout: ResMut<Assets<TextureAtlasLayout>>,
+    mut meshes: ResMut<Assets<Mesh>>,
+    mut materials: ResMut<Assets<ColorMaterial>>,
+    asset_server: Res<AssetServer>,
+    sprites: Res<SpritesResources>,
+    elites: Query<(&Transform, &Enemy, &Damage, &Elite)>,
+) {
+    let Ok((elite_transform, enemy, elite_damage, elite)) = elites.get(trigger.event().entity)
+    else {
+        return;
+    };
+
+    // Better drops. The normal score for the kill was already given.
+    commands.trigger(ScoreChanged {
+        score: elite_damage.0 * (ELITE_SCORE_MULTIPLIER - 1.0),
+    });
+
+    if elite.has(EliteAffixEnum::Splitting) {
+        let health_bar_translation = Vec3::new(2.0, 15.0, 0.0);
+        let health = enemy.max_health / ELITE_HEALTH_MULTIPLIER * ELITE_SPLIT_HEALTH_MULTIPLIER;
+
+        let split_enemies = spawn_orc_enemy(
+            &mut commands,
+            &asset_server,
+            &sprites,
+            &mut texture_atlas_layout,
+            &mut meshes,
+            &mut materials,
+            health,
+            elite_damage.0,
+            elite_transform.scale * ELITE_SPLIT_SCALE_MULTIPLIER,
+            health_bar_translation,
+            ELITE_SPLIT_QUANTITY,
+            Some(elite_transform.translation),
+        );
+
+        for split_enemy in split_enemies {
+            commands.entity(split_enemy).insert(SplitFromElite);
+        }
+
+        commands.trigger(UpdateAliveEnemiesUI);
+    }
+
+    if elite.has(EliteAffixEnum::Explosive) {
+        commands.spawn((
+            MaterialMesh2dBundle {
+                mesh: Mesh2dHandle(meshes.add(Circle::new(ELITE_EXPLOSION_RADIUS))),
+                material: materials.add(Color::srgba(1.0, 0.5, 0.0, 0.5)),
+                transform: Transform::from_translation(
+                    elite_transform
+                        .translation
+                        .truncate()
+                        .extend(CHAR_Z_INDEX + 0.5),
+                ),
+                ..default()
+            },
+            EliteExplosion {
+                damage: elite_damage.0 * ELITE_EXPLOSION_DAMAGE_MULTIPLIER,
+                exploded: false,
+                timer: Timer::from_seconds(ELITE_EXPLOSION_SECONDS, TimerMode::Once),
+            },
+            BASE_LAYER,
+            CleanupWhenPlayerDies,
+        ));
+    }
+}
+
+pub fn on_boss_spawned(
+    trigger: Trigger<BossSpawned>,
+    mut commands: Commands,
+    mut next_state: ResMut<NextState<GameState>>,
+    asset_server: Res<AssetServer>,
+    bosses: Query<&Enemy>,
+    base_camera: Query<&Transform, With<BaseCamera>>,
+) {
+    let boss_entity = trigger.event().entity;
+
+    let Ok(enemy) = bosses.get(boss_entity) else {
+        return;
+    };
+
+    let Some(moveset) = get_boss_moveset_based_on_enemy_class(enemy.class.clone()) else {
+        return;
+    };
+
+    let Ok(base_camera_transform) = base_camera.get_single() else {
+        return;
+    };
+
+    spawn_boss_health_bar_ui(&mut commands, &asset_server, boss_entity, &moveset);
+
+    commands.insert_resource(BossIntro::new(
+        boss_entity,
+        moveset.name,
+        base_camera_transform.translation.truncate(),
+    ));
+    next_state.set(GameState::BossIntro);
+}
+
+pub fn on_boss_health_changed(
+    trigger: Trigger<EnemyHealthChanged>,
+    mut commands: Commands,
+    boss_health_bars: Query<(Entity, &BossHealthBarUI)>,
+    mut boss_health_bar_fills: Query<(&mut Style, &BossHealthBarFillUI)>,
+) {
+    let event = trigger.event();
+
+    for (mut style, fill) in boss_health_bar_fills.iter_mut() {
+        if fill.boss_entity == event.entity {
+            let health_fraction = (event.health / event.max_health).clamp(0.0, 1.0);
+            style.width = Val::Percent(health_fraction * 100.);
+        }
+    }
+
+    // The boss is dead, the level transition goes on without the bar
+    if event.health <= 0.0 {
+        for (bar_entity, bar) in boss_health_bars.iter() {
+            if bar.boss_entity == event.entity {
+                commands.entity(bar_entity).despawn_recursive();
+            }
+        }
+    }
+}
+
+pub fn on_boss_phase_changed(
+    trigger: Trigger<BossPhaseChanged>,
+    mut boss_phase_ui: Query<(&mut Text, &BossPhaseUI)>,
+) {
+    let event = trigger.event();
+
+    for (mut text, phase_ui) in boss_phase_ui.iter_mut() {
+        if phase_ui.boss_entity == event.entity {
+            text.sections[0].value =
+                format!("Phase {}/{}", event.phase + 1, phase_ui.number_of_phases);
+        }
+    }
+}
+
+pub fn on_boss_enraged(
+    trigger: Trigger<BossEnraged>,
+    mut boss_name_ui: Query<(&mut Text, &BossNameUI)>,
+) {
+    let event = trigger.event();
+
+    for (mut text, name_ui) in boss_name_ui.iter_mut() {
+        if name_ui.boss_entity == event.entity {
+            text.sections[0].value = format!("{} (ENRAGED)", text.sections[0].value);
+            text.sections[0].style.color = Color::srgb(1.0, 0.2, 0.2);
+        }
+    }
+}
+
 pub fn spawn_entities_for_new_wave(
     _trigger: Trigger<SpawnEntitiesForNewWave>,
     mut commands: Commands,
@@ -772,16 +986,16 @@ pub fn tick_timer(mut commands: Commands, mut current_time: ResMut<CurrentTime>)
 
 pub fn remove_outdated_buffs(
     mut commands: Commands,
-    mut player: Query<(&mut Speed, &mut Armor, &mut Sprite, &Children), With<Player>>,
+    mut player: Query<(Entity, &mut Sprite, &mut StatModifiers, &Children), With<Player>>,
     player_buff_group_query: Query<(Entity, &BuffGroup)>,
 ) {
-    let Ok((_, mut player_armor, mut player_sprite, player_children)) = player.get_single_mut()
+    let Ok((player_entity, mut player_sprite, mut player_stat_modifiers, player_children)) =
+        player.get_single_mut()
     else {
         return;
     };
 
     let should_be_despawned = |buff_group: BuffGroup,
-                               player_armor: &mut Armor,
                                player_sprite: &mut Sprite,
                                commands: &mut Commands,
                                buff_ui_despawned: Option<ItemTypeEnum>|
@@ -790,7 +1004,8 @@ pub fn remove_outdated_buffs(
             crate::ItemTypeEnum::Speed(_)
             | crate::ItemTypeEnum::Armor(_)
             | crate::ItemTypeEnum::Health(_)
-            | crate::ItemTypeEnum::Mana(_) => false,
+            | crate::ItemTypeEnum::Mana(_)
+            | crate::ItemTypeEnum::Coins(_) => false,
             crate::ItemTypeEnum::Shield(shield) => {
                 if shield.duration_seconds.is_none() {
                     return false;
@@ -803,17 +1018,11 @@ pub fn remove_outdated_buffs(
                 let has_passed =
                     diff.num_seconds() > shield.duration_seconds.unwrap().try_into().unwrap();
 
-                if has_passed {
-                    // update player armor
-                    // TODO: check for shield type (magical vs physical)
-                    if shield.defensive > 0. {
-                        player_armor.0 -= shield.defensive * NUMBER_OF_BUFF_ITEMS as f32;
-                    }
-                    if buff_ui_despawned.is_none() {
-                        commands.trigger(BuffUIRemove {
-                            item_type: buff_group.item.clone(),
-                        });
-                    }
+                // The armor of the shield is removed with its buff group
+                if has_passed && buff_ui_despawned.is_none() {
+                    commands.trigger(BuffUIRemove {
+                        item_type: buff_group.item.clone(),
+                    });
                 }
 
                 has_passed
@@ -827,6 +1036,8 @@ pub fn remove_outdated_buffs(
                     diff.num_seconds() > invisibility.duration_seconds.try_into().unwrap();
 
                 if has_passed {
+                    commands.entity(player_entity).remove::<Stealthed>();
+
                     // update player transparency
                     let mut player_color = player_sprite.color.to_srgba();
                     player_color.alpha = 1.0;
@@ -859,7 +1070,6 @@ pub fn remove_outdated_buffs(
 
         if should_be_despawned(
             player_buff_group.clone(),
-            &mut player_armor,
             &mut player_sprite,
             &mut commands,
             buff_group_ui_despawned.clone(),
@@ -868,6 +1078,8 @@ pub fn remove_outdated_buffs(
                 buff_group_ui_despawned = Some(player_buff_group.item.clone());
             }
 
+            player_stat_modifiers
+                .remove_source(StatModifierSource::Shield(player_buff_group_entity));
             commands
                 .entity(player_buff_group_entity)
                 .despawn_recursive();
@@ -875,13 +1087,17 @@ pub fn remove_outdated_buffs(
     }
 }
 
-pub fn refill_health(mut commands: Commands, mut player: Query<&mut Health, With<Player>>) {
-    let Ok(mut player_health) = player.get_single_mut() else {
+pub fn refill_health(
+    mut commands: Commands,
+    mut player: Query<(&mut Health, &MaxHealth, &HealthRegen), With<Player>>,
+) {
+    let Ok((mut player_health, player_max_health, player_health_regen)) = player.get_single_mut()
+    else {
         return;
     };
 
-    if player_health.0 < PLAYER_HEALTH {
-        player_health.0 += 1.0;
+    if player_health.0 < player_max_health.0 {
+        player_health.0 = (player_health.0 + player_health_regen.0).min(player_max_health.0);
     }
 
     commands.trigger(PlayerHealthChanged {
@@ -889,13 +1105,16 @@ pub fn refill_health(mut commands: Commands, mut player: Query<&mut Health, With
     });
 }
 
-pub fn refill_mana(mut commands: Commands, mut player: Query<&mut Mana, With<Player>>) {
-    let Ok(mut player_mana) = player.get_single_mut() else {
+pub fn refill_mana(
+    mut commands: Commands,
+    mut player: Query<(&mut Mana, &MaxMana, &ManaRegen), With<Player>>,
+) {
+    let Ok((mut player_mana, player_max_mana, player_mana_regen)) = player.get_single_mut() else {
         return;
     };
 
-    if player_mana.0 < PLAYER_MANA {
-        player_mana.0 += 1.0;
+    if player_mana.0 < player_max_mana.0 {
+        player_mana.0 = (player_mana.0 + player_mana_regen.0).min(player_max_mana.0);
     }
 
     commands.trigger(PlayerManaChanged {
@@ -903,84 +1122,52 @@ pub fn refill_mana(mut commands: Commands, mut player: Query<&mut Mana, With<Pla
     });
 }
 
-pub fn expand_circle_of_death(
+pub fn on_player_dealt_damage_show_number(
+    trigger: Trigger<PlayerDealtDamage>,
     mut commands: Commands,
-    mut meshes: ResMut<Assets<Mesh>>,
-    mut circle_of_death: Query<
-        (Entity, &mut Mesh2dHandle, &mut CircleOfDeath),
-        With<CircleOfDeath>,
-    >,
+    asset_server: Res<AssetServer>,
+    settings: Res<Settings>,
+    enemies: Query<&Transform, With<Enemy>>,
 ) {
-    for (circle_entity, mut mesh2d_handle, mut circle) in circle_of_death.iter_mut() {
-        let new_outer_radius = circle.outer_circle_radius * 0.2 + circle.outer_circle_radius;
-        let new_inner_radius = new_outer_radius - 10.0;
-
-        if new_inner_radius > BACKGROUND_TEXTURE_SCALE * BACKGROUND_TEXTURE_RESOLUTION.x_px {
-            commands.entity(circle_entity).despawn();
-            commands.trigger(DespawnPower(PowerTypeEnum::CircleOfDeath));
-            continue;
-        }
-
-        let new_mesh = meshes.add(Annulus::new(new_inner_radius, new_outer_radius));
-
-        circle.inner_circle_radius = new_inner_radius;
-        circle.outer_circle_radius = new_outer_radius;
-
-        *mesh2d_handle = Mesh2dHandle(new_mesh);
+    if !settings.damage_numbers {
+        return;
     }
-}
 
-pub fn despawn_powers(
-    trigger: Trigger<DespawnPower>,
-
-    commands: Commands,
-    player_query: Query<(&Children, &Player)>,
-    player_powers_query: Query<(Entity, &Power)>,
-
-    powers_query: Query<(Entity, &Power), With<Power>>,
-) {
     let event = trigger.event();
-    let power_type = event.0.clone();
-
-    if let PowerTypeEnum::CircleOfDeath = power_type {
-        despawn_circle_of_death_power(commands, player_query, player_powers_query, powers_query)
+    let Ok(enemy_transform) = enemies.get(event.entity) else {
+        return;
     };
+
+    spawn_damage_number(
+        &mut commands,
+        &asset_server,
+        event.damage,
+        enemy_transform.translation.truncate(),
+    );
 }
 
-fn despawn_circle_of_death_power(
+pub fn on_player_dealt_damage(
+    trigger: Trigger<PlayerDealtDamage>,
     mut commands: Commands,
-    player_query: Query<(&Children, &Player)>,
-    player_powers_query: Query<(Entity, &Power)>,
-
-    powers_query: Query<(Entity, &Power), With<Power>>,
+    mut player: Query<(&mut Health, &MaxHealth, &LifeSteal), With<Player>>,
 ) {
-    let Ok((player_children, _)) = player_query.get_single() else {
+    let Ok((mut player_health, player_max_health, player_life_steal)) = player.get_single_mut()
+    else {
         return;
     };
 
-    let mut current_player_powers_entity: Vec<Entity> = vec![];
-    for &child in player_children {
-        if let Ok(player_powers) = player_powers_query.get(child) {
-            current_player_powers_entity.push(player_powers.0);
-        }
+    if player_life_steal.0 <= 0. || player_health.0 >= player_max_health.0 {
+        return;
     }
 
-    let circle_of_death_key_code = get_key_code_based_on_power_type(PowerTypeEnum::CircleOfDeath);
+    let stolen = trigger.event().damage * player_life_steal.0;
+    player_health.0 = (player_health.0 + stolen).min(player_max_health.0);
 
-    for (power_entity, power) in powers_query.iter() {
-        // if current power is from player, do not despawn it
-        if current_player_powers_entity.contains(&power_entity) {
-            continue;
-        }
-
-        if power.trigger_key == circle_of_death_key_code {
-            commands.entity(power_entity).despawn();
-        }
-    }
+    commands.trigger(PlayerHealthChanged {
+        health: player_health.0,
+    });
 }
 
-const NUMBER_OF_POSITIONS: usize = 360; // 2pi
-
 pub fn animate_player_buffs(
     mut player_query: Query<&Children, With<Player>>,
     player_buff_group_query: Query<(&Children, &BuffGroup)>,
@@ -988,7 +1175,6 @@ pub fn animate_player_buffs(
     time: Res<Time>,
 ) {
     let elapsed_seconds = time.elapsed_seconds();
-    let degrees = elapsed_seconds % NUMBER_OF_POSITIONS as f32;
 
     let Ok(player_children) = player_query.get_single_mut() else {
         return;
@@ -1000,23 +1186,22 @@ pub fn animate_player_buffs(
         };
 
         for (idx, &player_buff_group_child) in player_buff_group_children.iter().enumerate() {
-            if player_buff_query.get_mut(player_buff_group_child).is_err() {
+            let Ok((mut player_buff_transform, player_buff)) =
+                player_buff_query.get_mut(player_buff_group_child)
+            else {
                 continue;
-            }
-
-            let (mut player_buff_transform, player_buff) =
-                player_buff_query.get_mut(player_buff_group_child).unwrap();
+            };
 
-            match player_buff.item {
-                ItemTypeEnum::Shield(_) => {
-                    let radians = DEGREES_TO_RADIANS
-                        * degrees
-                        * (idx + 1) as f32
-                        * (NUMBER_OF_POSITIONS / NUMBER_OF_BUFF_ITEMS) as f32;
+            match &player_buff.item {
+                ItemTypeEnum::Shield(shield) => {
+                    // The shields are evenly spread around the player
+                    let degrees = elapsed_seconds * shield.orbit_speed
+                        + 360. * idx as f32 / shield.count.max(1) as f32;
+                    let radians = DEGREES_TO_RADIANS * degrees;
 
                     let (mut y, mut x) = f32::sin_cos(radians);
-                    y *= RADIUS_FROM_PLAYER;
-                    x *= RADIUS_FROM_PLAYER;
+                    y *= shield.radius;
+                    x *= shield.radius;
 
                     player_buff_transform.translation = Vec3::new(x, y, 1.);
                 }
@@ -1413,7 +1598,7 @@ pub fn on_power_found(
     asset_server: Res<AssetServer>,
 
     current_game_level: Res<CurrentGameLevel>,
-    power_levels: Res<PowerLevels>,
+    registry: Res<PowerRegistry>,
 
     player_query: Query<(Entity, &Children, &Player)>,
     player_powers_query: Query<(Entity, &Power)>,
@@ -1422,35 +1607,42 @@ pub fn on_power_found(
         return;
     };
 
-    let mod_power_level_index = ((current_game_level.0 - 1) as usize % NUMBER_OF_POWERS) + 1;
-    let current_level_power = power_levels
-        .0
-        .iter()
-        .find(|power| power.level == mod_power_level_index);
-    if current_level_power.is_none() {
+    let Some(behaviour) = registry.get_by_level(current_game_level.0 as u32) else {
         println!("NO POWER MATCHING level FOUND!!!");
         return;
-    }
-    let power_by_level = current_level_power.unwrap();
+    };
 
-    let power_type = power_by_level.power.power_type.clone();
-    let keycode = get_key_code_based_on_power_type(power_type.clone());
+    let power_type = behaviour.power_type();
+    let key_action = KeyActionEnum::Power(power_type);
 
-    // Remove power from player if it has the same keycode - therefore
-    // it is the same type.
-    // The reason is because we want to replace it, not to add a `duplicate`
-    // one.
-    let mut power_increase = None;
+    // If the player already has the power, it goes one step down its
+    // upgrade tree instead
     for &child in player_children {
-        if let Ok(player_powers) = player_powers_query.get(child) {
-            if player_powers.1.trigger_key == keycode {
-                power_increase = Some(1.2f32);
-                commands
-                    .entity(player_entity)
-                    .remove_children(&[player_powers.0]);
-                break;
+        let Ok((power_entity, power)) = player_powers_query.get(child) else {
+            continue;
+        };
+        if power.power_type != power_type {
+            continue;
+        }
+
+        let choices = power.next_upgrade_choices();
+        match choices.len() {
+            // Fully upgraded
+            0 => {}
+            1 => commands.trigger(PowerUpgradeChosen {
+                power_entity,
+                choice: 0,
+            }),
+            _ => {
+                commands.insert_resource(PendingPowerUpgrade {
+                    power_entity,
+                    power_type,
+                });
+                spawn_power_upgrade_choice_ui(&mut commands, &asset_server, power_type, choices);
             }
         }
+
+        return;
     }
 
     equip_player_with_power(
@@ -1458,14 +1650,41 @@ pub fn on_power_found(
         texture_atlas_layout,
         &sprites,
         &asset_server,
-        power_by_level,
+        behaviour,
         player_entity,
-        power_increase,
     );
 
     commands.trigger(OnUpdatePowerUI {
         power_type,
-        keycode,
+        key_action,
+        level: 1,
+        branch: None,
+    });
+}
+
+pub fn on_power_upgrade_chosen(
+    trigger: Trigger<PowerUpgradeChosen>,
+    mut commands: Commands,
+    mut powers: Query<(&mut Power, &mut Damage)>,
+) {
+    let PowerUpgradeChosen {
+        power_entity,
+        choice,
+    } = *trigger.event();
+
+    let Ok((mut power, mut damage)) = powers.get_mut(power_entity) else {
+        return;
+    };
+
+    if !power.apply_upgrade(&mut damage, choice) {
+        return;
+    }
+
+    commands.trigger(OnUpdatePowerUI {
+        power_type: power.power_type,
+        key_action: power.key_action,
+        level: power.level,
+        branch: power.branch(),
     });
 }
 
@@ -1475,17 +1694,24 @@ pub fn update_power_ui(
     mut commands: Commands,
     asset_server: Res<AssetServer>,
     sprites: Res<SpritesResources>,
+    mut cooldown_materials: ResMut<Assets<PowerCooldownMaterial>>,
 
     power_ui_root_node: Query<(Entity, &PowerUIRootNode)>,
     mut power_ui_query: Query<(&mut PowerUI, &Children), With<PowerUI>>,
     power_sprite_ui_query: Query<&Children, With<PowerSpriteUI>>,
-    mut power_level_ui_query: Query<&mut Text, With<PowerLevelUI>>,
+    mut power_level_ui_query: Query<&mut Text, (With<PowerLevelUI>, Without<PowerBranchUI>)>,
+    mut power_branch_ui_query: Query<&mut Text, With<PowerBranchUI>>,
+    settings: Res<Settings>,
+    registry: Res<PowerRegistry>,
 ) {
     let event = trigger.event();
-    let power_type = event.power_type.clone();
-    let keycode = event.keycode;
+    let power_type = event.power_type;
+    let key_action = event.key_action;
 
-    let sprite_source = get_power_sprite_based_on_power_type(power_type.clone(), &sprites).source;
+    let Some(behaviour) = registry.get(power_type) else {
+        return;
+    };
+    let sprite_source = behaviour.sprite(&sprites).source;
 
     let Ok((power_ui_root_node_entity, _)) = power_ui_root_node.get_single() else {
         return;
@@ -1495,7 +1721,7 @@ pub fn update_power_ui(
     for mut power_ui in power_ui_query.iter_mut() {
         if power_ui.0.power_type == power_type {
             // Update level of existing power
-            power_ui.0.power_level += 1;
+            power_ui.0.power_level = event.level as usize;
 
             found = Some(power_ui);
             break;
@@ -1518,6 +1744,10 @@ pub fn update_power_ui(
                     power_level_ui_text.sections.first_mut().unwrap().value =
                         format!("{}", power_ui.power_level);
                 }
+                if let Ok(mut power_branch_ui_text) = power_branch_ui_query.get_mut(sprite_child) {
+                    power_branch_ui_text.sections.first_mut().unwrap().value =
+                        event.branch.unwrap_or_default().to_string();
+                }
             }
         }
 
@@ -1528,71 +1758,95 @@ pub fn update_power_ui(
     let child_id = spawn_power_ui(
         &mut commands,
         &asset_server,
+        &mut cooldown_materials,
         sprite_source,
         power_type,
-        keycode,
+        key_action,
+        settings.key_bindings.get(key_action),
     );
     commands
         .entity(power_ui_root_node_entity)
         .add_child(child_id);
 }
 
-pub fn on_window_resize(
-    mut resize_reader: EventReader<WindowResized>,
-    mut window_resolution: ResMut<WindowResolutionResource>,
-) {
-    for e in resize_reader.read() {
-        window_resolution.x_px = e.width;
-        window_resolution.y_px = e.height;
-    }
+pub fn on_screen_shake(trigger: Trigger<ScreenShake>, mut camera_effects: ResMut<CameraEffects>) {
+    camera_effects.add_trauma(trigger.event().trauma);
 }
 
-pub fn maybe_spawn_health_points_pack(
-    _trigger: Trigger<MaybeSpawnHealthPack>,
+pub fn on_prop_broken(
+    trigger: Trigger<PropBroken>,
     mut commands: Commands,
     mut texture_atlas_layout: ResMut<Assets<TextureAtlasLayout>>,
     sprites: Res<SpritesResources>,
     current_game_level: Res<CurrentGameLevel>,
     asset_server: Res<AssetServer>,
+    player_query: Query<&Luck, With<Player>>,
 ) {
-    let chance = get_random_chance();
-    let quantity = 1;
-    let level = current_game_level.0;
+    let event = trigger.event();
 
-    if chance < CHANCE_TO_SPAWN_HEALTH_POINTS_PACK {
-        spawn_item(
-            &mut commands,
-            &mut texture_atlas_layout,
-            &sprites,
-            &asset_server,
-            ItemTypeEnum::Health(crate::Health(10.)),
-            quantity,
-            level,
-        );
-    }
+    spawn_prop_break_effects(
+        &mut commands,
+        &mut texture_atlas_layout,
+        &asset_server,
+        &sprites,
+        event.kind,
+        event.position,
+    );
+
+    let player_luck = player_query.get_single().map_or(0., |luck| luck.0);
+
+    drop_loot(
+        &mut commands,
+        &mut texture_atlas_layout,
+        &sprites,
+        &asset_server,
+        &PROP_LOOT_TABLE,
+        event.position,
+        current_game_level.0,
+        get_luck(player_luck, current_game_level.0),
+    );
 }
 
-pub fn maybe_spawn_mana_points_pack(
-    _trigger: Trigger<MaybeSpawnManaPack>,
+/// Drops the loot of the class of the enemy, plus the elite one for elites.
+pub fn on_enemy_died_drop_loot(
+    trigger: Trigger<EnemyDied>,
     mut commands: Commands,
     mut texture_atlas_layout: ResMut<Assets<TextureAtlasLayout>>,
     sprites: Res<SpritesResources>,
     current_game_level: Res<CurrentGameLevel>,
     asset_server: Res<AssetServer>,
+    enemies: Query<(&Transform, &Enemy, Option<&Elite>)>,
+    player_query: Query<&Luck, With<Player>>,
 ) {
-    let chance = get_random_chance();
-    let quantity = 1;
-    let level = current_game_level.0;
+    let Ok((enemy_transform, enemy, elite)) = enemies.get(trigger.event().entity) else {
+        return;
+    };
+
+    let player_luck = player_query.get_single().map_or(0., |luck| luck.0);
+    let luck = get_luck(player_luck, current_game_level.0);
+    let position = enemy_transform.translation.truncate();
+
+    drop_loot(
+        &mut commands,
+        &mut texture_atlas_layout,
+        &sprites,
+        &asset_server,
+        &get_loot_table_based_on_enemy_class(enemy.class.clone()),
+        position,
+        current_game_level.0,
+        luck,
+    );
 
-    if chance < CHANCE_TO_SPAWN_MANA_POINTS_PACK {
-        spawn_item(
+    if elite.is_some() {
+        drop_loot(
             &mut commands,
             &mut texture_atlas_layout,
             &sprites,
             &asset_server,
-            ItemTypeEnum::Mana(crate::Mana(10.)),
-            quantity,
-            level,
+            &ELITE_LOOT_TABLE,
+            position,
+            current_game_level.0,
+            luck,
         );
     }
 }
@@ -1605,6 +1859,7 @@ pub fn change_background_texture(
     sprites: Res<SpritesResources>,
     mut texture_atlas_layout: ResMut<Assets<TextureAtlasLayout>>,
     background_query: Query<Entity, With<TileBackground>>,
+    run_seed: Res<RunSeed>,
 ) {
     background_query.iter().for_each(|e| {
         commands.entity(e).despawn_recursive();
@@ -1616,6 +1871,7 @@ pub fn change_background_texture(
         &asset_server,
         &sprites,
         current_game_level.0,
+        run_seed.0,
     );
 }
 
@@ -0,0 +1,120 @@
+use std::collections::HashMap;
+
+use crate::{prelude::*, Enemy};
+
+#[derive(Debug, Clone, Copy)]
+pub struct SpatialGridEntry {
+    pub entity: Entity,
+    pub position: Vec2,
+    pub velocity: Vec2,
+}
+
+/// Buckets the enemies by the cell they are in so we can look only at the
+/// ones that are close to each other instead of checking every pair.
+#[derive(Resource, Default)]
+pub struct EnemySpatialGrid {
+    cells: HashMap<IVec2, Vec<SpatialGridEntry>>,
+}
+
+impl EnemySpatialGrid {
+    fn get_cell(position: Vec2) -> IVec2 {
+        (position / FLOCKING_CELL_SIZE).floor().as_ivec2()
+    }
+
+    pub fn clear(&mut self) {
+        self.cells.clear();
+    }
+
+    pub fn insert(&mut self, entry: SpatialGridEntry) {
+        self.cells
+            .entry(Self::get_cell(entry.position))
+            .or_default()
+            .push(entry);
+    }
+
+    /// Returns the entries inside `radius` of `position`.
+    pub fn neighbours(
+        &self,
+        position: Vec2,
+        radius: f32,
+    ) -> impl Iterator<Item = &SpatialGridEntry> + '_ {
+        let min = Self::get_cell(position - Vec2::splat(radius));
+        let max = Self::get_cell(position + Vec2::splat(radius));
+
+        (min.x..=max.x)
+            .flat_map(move |x| (min.y..=max.y).map(move |y| IVec2::new(x, y)))
+            .filter_map(|cell| self.cells.get(&cell))
+            .flatten()
+            .filter(move |entry| entry.position.distance_squared(position) <= radius * radius)
+    }
+}
+
+pub fn update_enemy_spatial_grid(
+    mut grid: ResMut<EnemySpatialGrid>,
+    enemies: Query<(Entity, &Transform, &Enemy)>,
+) {
+    grid.clear();
+
+    for (entity, transform, enemy) in enemies.iter() {
+        grid.insert(SpatialGridEntry {
+            entity,
+            position: transform.translation.truncate(),
+            velocity: enemy.velocity,
+        });
+    }
+}
+
+/// Separation, alignment and cohesion steering based on the neighbours of
+/// the enemy. The result is added to the velocity the enemy already wants.
+pub(crate) fn get_flocking_steering(
+    grid: &EnemySpatialGrid,
+    entity: Entity,
+    position: Vec2,
+    velocity: Vec2,
+) -> Vec2 {
+    let mut separation = Vec2::ZERO;
+    let mut average_velocity = Vec2::ZERO;
+    let mut average_position = Vec2::ZERO;
+    let mut number_of_neighbours = 0;
+
+    for neighbour in grid.neighbours(position, FLOCKING_NEIGHBOUR_RADIUS) {
+        if neighbour.entity == entity {
+            continue;
+        }
+
+        let offset = position - neighbour.position;
+        let distance = offset.length();
+
+        if distance < FLOCKING_SEPARATION_RADIUS {
+            // Enemies on the exact same pixel still need to be pushed apart,
+            // so we use the entity index to pick a side.
+            let away = if distance > f32::EPSILON {
+                offset / distance
+            } else {
+                Vec2::from_angle(entity.index() as f32 * GOLDEN_ANGLE)
+            };
+            separation += away * (1.0 - distance / FLOCKING_SEPARATION_RADIUS);
+        }
+
+        average_velocity += neighbour.velocity;
+        average_position += neighbour.position;
+        number_of_neighbours += 1;
+    }
+
+    if number_of_neighbours == 0 {
+        return Vec2::ZERO;
+    }
+
+    let speed = velocity.length().max(ENEMY_MOVE_SPEED);
+
+    average_velocity /= number_of_neighbours as f32;
+    average_position /= number_of_neighbours as f32;
+
+    let alignment = (average_velocity - velocity).normalize_or_zero();
+    let cohesion = (average_position - position).normalize_or_zero();
+
+    (separation * FLOCKING_SEPARATION_WEIGHT
+        + alignment * FLOCKING_ALIGNMENT_WEIGHT
+        + cohesion * FLOCKING_COHESION_WEIGHT)
+        * speed
+}
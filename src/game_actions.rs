@@ -1,14 +1,21 @@
 use crate::{
     enemy::Enemy,
     events::ShootBullets,
+    flocking::{get_flocking_steering, EnemySpatialGrid},
+    get_power_target,
     player::Player,
     prelude::*,
-    spawn_orc_enemy, spawn_player_stats_ui, spawn_power,
-    util::{get_random_chance, get_unit_direction_vector, get_weapon_sprite_based_on_weapon_type},
-    AmmoBundle, Armor, AutoShootingEnabled, BaseCamera, CurrentBoss, Damage, Health, Mana,
-    MouseDirectionWhenAutoShooting, PlayAgainButton, PlayerManaChanged, PlayerStatsUI, Power,
-    RestartGame, RestartGameButton, Speed, SpritesResources, StartGameButton, UpdateAliveEnemiesUI,
-    Weapon, WindowResolutionResource,
+    spawn_player_stats_ui, spawn_power, spawn_settings_menu,
+    util::{
+        get_cursor_world_position, get_random_chance, get_unit_direction_vector,
+        get_weapon_sprite_based_on_weapon_type,
+    },
+    AmmoBundle, Armor, AutoShootingEnabled, BaseCamera, Boss, Damage, Dash, DashDistance, Elite,
+    Health, Mana, MouseDirectionWhenAutoShooting, NavGrid, NavPath, PendingKeyBinding, Perception,
+    PlayAgainButton, PlayerFlowField, PlayerManaChanged, PlayerStatsUI, Power, PowerInstance,
+    PowerRegistry, PowerSpawnContext, RestartGame, RestartGameButton, Settings, SettingsButton,
+    SettingsCloseButton, SettingsMenu, SettingsOptionButton, SettingsOptionEnum, Speed,
+    SpritesResources, StartGameButton, StatModifiers, Tilemap, Weapon,
 };
 
 pub fn change_enemy_direction(
@@ -53,40 +60,41 @@ pub fn move_enemies_towards_player(
     // immutable at the same time. See https://bevyengine.org/learn/errors/#b0001
     // for more.
     mut enemies: Query<
-        (&mut Transform, &Enemy),
+        (
+            Entity,
+            &mut Transform,
+            &mut Enemy,
+            &mut NavPath,
+            &Perception,
+            Option<&Boss>,
+            Option<&Elite>,
+        ),
         (With<Enemy>, Without<Player>, Without<BaseCamera>),
     >,
     time: Res<Time>,
-    player: Query<
-        (&Transform, &Sprite, &Player),
-        (With<Player>, Without<BaseCamera>, Without<Enemy>),
-    >,
+    spatial_grid: Res<EnemySpatialGrid>,
+    tilemap: Option<Res<Tilemap>>,
+    nav_grid: Res<NavGrid>,
+    flow_field: Res<PlayerFlowField>,
+    player: Query<(&Transform, &Player), (With<Player>, Without<BaseCamera>, Without<Enemy>)>,
     base_camera: Query<
         (&Transform, &BaseCamera),
         (With<BaseCamera>, Without<Player>, Without<Enemy>),
     >,
 ) {
-    let Ok((player_transform, player_sprite, _)) = player.get_single() else {
+    let Ok((player_transform, _)) = player.get_single() else {
         return;
     };
 
-    let mut position = Vec2::new(
-        player_transform.translation.x,
-        player_transform.translation.y,
-    );
-
     let Ok((base_camera_transform, _)) = base_camera.get_single() else {
         return;
     };
 
-    position = Vec2::new(
-        position.x + base_camera_transform.translation.x,
-        position.y + base_camera_transform.translation.y,
+    let player_position = Vec2::new(
+        player_transform.translation.x + base_camera_transform.translation.x,
+        player_transform.translation.y + base_camera_transform.translation.y,
     );
 
-    // check for player visibility
-    let is_player_visible = player_sprite.color.is_fully_opaque();
-
     let limit_x_left =
         (-BACKGROUND_TEXTURE_RESOLUTION.x_px * BACKGROUND_TEXTURE_SCALE + PLAYER_X_MARGIN) / 2.0;
     let limit_x_right =
@@ -96,51 +104,121 @@ pub fn move_enemies_towards_player(
     let limit_y_top =
         (BACKGROUND_TEXTURE_RESOLUTION.y_px * BACKGROUND_TEXTURE_SCALE - PLAYER_Y_MARGIN) / 2.0;
 
-    for (mut transform, enemy) in enemies.iter_mut() {
+    for (enemy_entity, mut transform, mut enemy, mut nav_path, perception, boss, elite) in
+        enemies.iter_mut()
+    {
         // We don't want the mage to move towards player. It's going to be a
         // range caster.
         if enemy.class == EnemyClassEnum::Mage {
             continue;
         }
 
+        // Bosses stand still while winding up an attack
+        if boss.is_some_and(|boss| boss.pending_attack.is_some()) {
+            continue;
+        }
+
+        let enemy_position = transform.translation.truncate();
+
         // Enemies have greater speed when charging the player.
         let mut speed = ENEMY_MOVE_SPEED * ENEMY_BOOST_SPEED_WHEN_CHARGING;
 
-        // If the player is gonna walk randomly or player is invisible,
+        // Each enemy gets its own angle around the player, so that instead of
+        // all of them piling up on the same spot they surround him and only
+        // close in when they are already near.
+        let surround_angle = enemy_entity.index() as f32 * GOLDEN_ANGLE;
+        let surround_position =
+            player_position + Vec2::from_angle(surround_angle) * ENEMY_SURROUND_RADIUS;
+        let mut position = if enemy_position.distance(player_position) > ENEMY_SURROUND_RADIUS * 1.5
+        {
+            surround_position
+        } else {
+            player_position
+        };
+
+        // If the enemy lost sight of the player, it circles around the last
+        // place he was seen.
+        //
+        // If the enemy is gonna walk randomly or can't see the player,
         // then uses the `direction_intention`
         // instead of the player's position as the origin for the unit direction vector.
         //
         // It also walks in a slower way.
-        if enemy.is_random || !is_player_visible {
+        //
+        // Only the enemies chasing the player have no `destination`, they
+        // follow the flow field towards him.
+        let mut destination = None;
+        if let Some(search_position) = perception.search_position() {
+            position = search_position
+                + Vec2::from_angle(surround_angle + time.elapsed_seconds()) * ENEMY_SEARCH_RADIUS;
+            destination = Some(search_position);
+            speed = ENEMY_MOVE_SPEED;
+        } else if enemy.is_random || !perception.can_see_player {
             position = enemy.direction_intention.translation.truncate();
+            destination = Some(position);
             speed = ENEMY_MOVE_SPEED / ENEMY_BOOST_SPEED_WHEN_CHARGING;
         }
 
-        let unit_direction = get_unit_direction_vector(position, transform.translation.truncate());
-
-        let old_pos_x = transform.translation.x;
-        let old_pos_y = transform.translation.y;
-
-        let mut char_new_pos_x = old_pos_x - unit_direction.x * speed * time.delta_seconds();
-        let mut char_new_pos_y = old_pos_y + unit_direction.y * speed * time.delta_seconds();
+        // Straight to the target when nothing is in the way, otherwise
+        // along a path around the obstacles.
+        let blocked = tilemap
+            .as_ref()
+            .is_some_and(|tilemap| !tilemap.has_line_of_sight(enemy_position, position));
+        if blocked {
+            let waypoint = match destination {
+                Some(destination) => nav_path.next_waypoint(&nav_grid, enemy_position, destination),
+                None => flow_field.next_waypoint(&nav_grid, enemy_position),
+            };
+
+            if let Some(waypoint) = waypoint {
+                position = waypoint;
+            }
+        }
 
-        if char_new_pos_x < limit_x_left {
-            char_new_pos_x = limit_x_left;
+        if let Some(elite) = elite {
+            speed *= elite.speed_multiplier();
         }
-        if char_new_pos_x > limit_x_right {
-            char_new_pos_x = limit_x_right;
+
+        let unit_direction = get_unit_direction_vector(position, enemy_position);
+        // Already at the target
+        if !unit_direction.is_finite() {
+            continue;
         }
-        if char_new_pos_y < limit_y_bottom {
-            char_new_pos_y = limit_y_bottom;
+
+        // See that these signs are the opposite of what we use when spawning bullets
+        // As now we need to make the enemies go *towards* the player, not *outwards*
+        let desired_velocity = Vec2::new(-unit_direction.x, unit_direction.y) * speed;
+        let steering = get_flocking_steering(
+            &spatial_grid,
+            enemy_entity,
+            enemy_position,
+            desired_velocity,
+        );
+        let mut velocity = (desired_velocity + steering).clamp_length_max(speed);
+
+        // Chasers go around the obstacles instead of sliding along them
+        if let Some(tilemap) = &tilemap {
+            velocity = tilemap.steer_around_solids(enemy_position, velocity);
         }
-        if char_new_pos_y > limit_y_top {
-            char_new_pos_y = limit_y_top;
+
+        let char_new_pos_x = (transform.translation.x + velocity.x * time.delta_seconds())
+            .clamp(limit_x_left, limit_x_right);
+        let char_new_pos_y = (transform.translation.y + velocity.y * time.delta_seconds())
+            .clamp(limit_y_bottom, limit_y_top);
+        let mut new_position = Vec2::new(char_new_pos_x, char_new_pos_y);
+
+        // Enemies can't walk through the solid tiles either
+        if let Some(tilemap) = &tilemap {
+            new_position = tilemap.resolve_movement(
+                enemy_position,
+                new_position,
+                Vec2::new(enemy.width_collider / 2., enemy.height_collider / 2.),
+            );
         }
 
-        // See that these `-=` and `+=` are the opposite of what we use when spawning bullets
-        // As now we need to make the enemies go *towards* the player, not *outwards*
-        transform.translation.x = char_new_pos_x;
-        transform.translation.y = char_new_pos_y;
+        transform.translation.x = new_position.x;
+        transform.translation.y = new_position.y;
+        enemy.velocity = velocity;
     }
 }
 
@@ -206,119 +284,6 @@ pub fn shoot_at_enemies(
     commands.spawn(ammo_bundle);
 }
 
-pub fn shoot_at_player(
-    mut commands: Commands,
-    asset_server: Res<AssetServer>,
-    sprites: Res<SpritesResources>,
-    mut texture_atlas_layout: ResMut<Assets<TextureAtlasLayout>>,
-
-    player_query: Query<&Transform, With<Player>>,
-    enemies: Query<(Entity, &Transform, &Children), With<Enemy>>,
-    weapon_query: Query<(&Weapon, &Damage)>,
-    base_camera: Query<(&Transform, &BaseCamera), Without<Player>>,
-) {
-    let Ok(player_transform) = player_query.get_single() else {
-        return;
-    };
-
-    let Ok((base_camera_transform, _)) = base_camera.get_single() else {
-        return;
-    };
-
-    let player_position = Vec2::new(
-        player_transform.translation.x + base_camera_transform.translation.x,
-        player_transform.translation.y + base_camera_transform.translation.y,
-    );
-
-    for (enemy_entity, enemy_transform, enemy_children) in enemies.iter() {
-        for &child in enemy_children.iter() {
-            if let Ok((weapon, weapon_damage)) = weapon_query.get(child) {
-                let enemy_position =
-                    Vec2::new(enemy_transform.translation.x, enemy_transform.translation.y);
-
-                let unit_direction = get_unit_direction_vector(enemy_position, player_position);
-                let angle = unit_direction.y.atan2(unit_direction.x) * -1.;
-                let rotation = Quat::from_rotation_z(angle);
-
-                let weapon_type = weapon.weapon_type.clone();
-                let direction = Vec3::new(unit_direction.x, unit_direction.y, 1.0);
-                let pos = Vec3::new(
-                    enemy_transform.translation.x + 8.0,
-                    enemy_transform.translation.y,
-                    enemy_transform.translation.z,
-                );
-                let scale = Vec3::ONE;
-                let layer = BASE_LAYER;
-
-                let ammo_bundle = AmmoBundle::new(
-                    &mut texture_atlas_layout,
-                    &sprites,
-                    &asset_server,
-                    scale,
-                    pos,
-                    weapon_type.clone(),
-                    direction,
-                    weapon_damage.0,
-                    rotation,
-                    layer.clone(),
-                    enemy_entity,
-                    crate::util::EquippedTypeEnum::Enemy,
-                );
-
-                commands.spawn(ammo_bundle);
-            }
-        }
-    }
-}
-
-pub fn make_boss_spawn_enemies(
-    mut commands: Commands,
-    asset_server: Res<AssetServer>,
-    sprites: Res<SpritesResources>,
-    mut texture_atlas_layout: ResMut<Assets<TextureAtlasLayout>>,
-    mut meshes: ResMut<Assets<Mesh>>,
-    mut materials: ResMut<Assets<ColorMaterial>>,
-
-    current_boss: Res<CurrentBoss>,
-    enemies: Query<(&Transform, &Enemy), With<Enemy>>,
-) {
-    if current_boss.0.is_none() {
-        return;
-    }
-
-    let creep_health = ENEMY_HEALTH;
-    let creep_damage = 25.0;
-    let creep_scale = Vec3::splat(2.5);
-    let health_bar_translation = Vec3::new(2.0, 15.0, 0.0);
-    let quantity = 1;
-
-    for (enemy_transform, enemy) in enemies.iter() {
-        if enemy.class != EnemyClassEnum::BossOrc {
-            continue;
-        }
-
-        let spawning_position = enemy_transform.translation;
-
-        spawn_orc_enemy(
-            &mut commands,
-            &asset_server,
-            &sprites,
-            &mut texture_atlas_layout,
-            &mut meshes,
-            &mut materials,
-            creep_health,
-            creep_damage,
-            creep_scale,
-            health_bar_translation,
-            quantity,
-            Some(spawning_position),
-        );
-    }
-
-    // Update alive enemies UI
-    commands.trigger(UpdateAliveEnemiesUI);
-}
-
 pub fn handle_click(
     mouse_button_input: Res<ButtonInput<MouseButton>>,
     camera: Query<(&Camera, &GlobalTransform, &BaseCamera)>,
@@ -326,12 +291,7 @@ pub fn handle_click(
     mut commands: Commands,
 ) {
     let (camera, camera_transform, _) = camera.single();
-    if let Some(pos) = windows
-        .single()
-        .cursor_position()
-        .and_then(|cursor| camera.viewport_to_world(camera_transform, cursor))
-        .map(|ray| ray.origin.truncate())
-    {
+    if let Some(pos) = get_cursor_world_position(windows.single(), camera, camera_transform) {
         if mouse_button_input.just_pressed(MouseButton::Left) {
             commands.trigger(ShootBullets { pos });
         }
@@ -372,12 +332,7 @@ pub fn get_mouse_cursor_position(
         mouse_direction_when_auto_shooting.y_px = ev.position.y;
 
         let (camera, camera_transform, _) = camera.single();
-        if let Some(pos) = windows
-            .single()
-            .cursor_position()
-            .and_then(|cursor| camera.viewport_to_world(camera_transform, cursor))
-            .map(|ray| ray.origin.truncate())
-        {
+        if let Some(pos) = get_cursor_world_position(windows.single(), camera, camera_transform) {
             mouse_direction_when_auto_shooting.x_px = pos.x;
             mouse_direction_when_auto_shooting.y_px = pos.y;
         }
@@ -390,59 +345,56 @@ pub fn single_ammo_shooting(autoshooting: Res<AutoShootingEnabled>) -> bool {
 
 pub fn enable_disable_autoshooting(
     keyboard_input: Res<ButtonInput<KeyCode>>,
+    settings: Res<Settings>,
     mut autoshooting: ResMut<AutoShootingEnabled>,
 ) {
-    if keyboard_input.any_just_pressed([KeyCode::KeyM].into_iter()) {
+    if keyboard_input.just_pressed(settings.key_bindings.auto_shoot) {
         autoshooting.0 = !autoshooting.0;
     }
 }
 
 pub fn move_player(
     keyboard_input: Res<ButtonInput<KeyCode>>,
-    mut player_query: Query<(&mut Transform, &Speed, &Player)>,
+    settings: Res<Settings>,
+    mut player_query: Query<(&mut Transform, &Speed, &Dash, &DashDistance, &Player)>,
     time: Res<Time>,
     mut base_camera: Query<(&mut Transform, &BaseCamera), Without<Player>>,
-    window_resolution: Res<WindowResolutionResource>,
+    tilemap: Option<Res<Tilemap>>,
 ) {
     let Ok((mut base_camera_transform, _)) = base_camera.get_single_mut() else {
         return;
     };
 
-    let Ok((mut player_transform, player_speed, _)) = player_query.get_single_mut() else {
+    let Ok((mut player_transform, player_speed, dash, dash_distance, _)) =
+        player_query.get_single_mut()
+    else {
         return;
     };
 
-    let mut direction_x = 0.;
-    let mut direction_y = 0.;
+    let direction = settings.key_bindings.movement_direction(&keyboard_input);
+    let mut direction_x = direction.x;
+    let mut direction_y = direction.y;
 
-    // top move
-    if keyboard_input.pressed(KeyCode::KeyW) {
-        direction_y += 1.0;
-    }
-    // left move
-    if keyboard_input.pressed(KeyCode::KeyA) {
-        direction_x -= 1.0;
-    }
-    // bottom move
-    if keyboard_input.pressed(KeyCode::KeyS) {
-        direction_y -= 1.0;
-    }
-    // right move
-    if keyboard_input.pressed(KeyCode::KeyD) {
-        direction_x += 1.0;
+    // The dash overrides the movement until it is over
+    let mut player_speed = player_speed.0;
+    if dash.is_dashing() {
+        direction_x = dash.direction.x;
+        direction_y = dash.direction.y;
+        player_speed = dash.speed(dash_distance);
     }
 
     // -------------------- PLAYER ------------------------
     let old_pos_x = player_transform.translation.x;
     let old_pos_y = player_transform.translation.y;
 
-    let mut char_new_pos_x = old_pos_x + direction_x * player_speed.0 * time.delta_seconds();
-    let mut char_new_pos_y = old_pos_y + direction_y * player_speed.0 * time.delta_seconds();
+    let mut char_new_pos_x = old_pos_x + direction_x * player_speed * time.delta_seconds();
+    let mut char_new_pos_y = old_pos_y + direction_y * player_speed * time.delta_seconds();
 
-    let limit_x_left = (-window_resolution.x_px + PLAYER_X_MARGIN) / 2.0;
-    let limit_x_right = (window_resolution.x_px - PLAYER_X_MARGIN) / 2.0;
-    let limit_y_bottom = (-window_resolution.y_px + PLAYER_Y_MARGIN) / 2.0;
-    let limit_y_top = (window_resolution.y_px - PLAYER_Y_MARGIN) / 2.0;
+    // The cameras always show the virtual resolution, whatever the window is
+    let limit_x_left = (-VIRTUAL_RESOLUTION.x_px + PLAYER_X_MARGIN) / 2.0;
+    let limit_x_right = (VIRTUAL_RESOLUTION.x_px - PLAYER_X_MARGIN) / 2.0;
+    let limit_y_bottom = (-VIRTUAL_RESOLUTION.y_px + PLAYER_Y_MARGIN) / 2.0;
+    let limit_y_top = (VIRTUAL_RESOLUTION.y_px - PLAYER_Y_MARGIN) / 2.0;
 
     if char_new_pos_x < limit_x_left {
         char_new_pos_x = limit_x_left;
@@ -457,18 +409,14 @@ pub fn move_player(
         char_new_pos_y = limit_y_top;
     }
 
-    // translate player
-    player_transform.translation.x = char_new_pos_x;
-    player_transform.translation.y = char_new_pos_y;
-
     // ------------------- CAMERA -------------------------
     let old_camera_pos_x = base_camera_transform.translation.x;
     let old_camera_pos_y = base_camera_transform.translation.y;
 
     let mut base_camera_new_pos_x =
-        old_camera_pos_x + direction_x * player_speed.0 * time.delta_seconds();
+        old_camera_pos_x + direction_x * player_speed * time.delta_seconds();
     let mut base_camera_new_pos_y =
-        old_camera_pos_y + direction_y * player_speed.0 * time.delta_seconds();
+        old_camera_pos_y + direction_y * player_speed * time.delta_seconds();
 
     let limit_x_left = (-BACKGROUND_TEXTURE_RESOLUTION.x_px) / 2.0;
     let limit_x_right = (BACKGROUND_TEXTURE_RESOLUTION.x_px) / 2.0;
@@ -488,6 +436,35 @@ pub fn move_player(
         base_camera_new_pos_y = limit_y_top;
     }
 
+    // ------------------- TILES --------------------------
+    // The player is drawn on top of the camera, so its position on the map
+    // is the sum of both. Solid tiles block each axis separately.
+    if let Some(tilemap) = tilemap {
+        let old_position = Vec2::new(old_pos_x + old_camera_pos_x, old_pos_y + old_camera_pos_y);
+        let new_position = Vec2::new(
+            char_new_pos_x + base_camera_new_pos_x,
+            char_new_pos_y + base_camera_new_pos_y,
+        );
+        let position = tilemap.resolve_movement(
+            old_position,
+            new_position,
+            Vec2::splat(PLAYER_SPRITE_SIZE as f32 / 2.),
+        );
+
+        if position.x != new_position.x {
+            char_new_pos_x = old_pos_x;
+            base_camera_new_pos_x = old_camera_pos_x;
+        }
+        if position.y != new_position.y {
+            char_new_pos_y = old_pos_y;
+            base_camera_new_pos_y = old_camera_pos_y;
+        }
+    }
+
+    // translate player
+    player_transform.translation.x = char_new_pos_x;
+    player_transform.translation.y = char_new_pos_y;
+
     // pan camera
     base_camera_transform.translation.x = base_camera_new_pos_x;
     base_camera_transform.translation.y = base_camera_new_pos_y;
@@ -495,24 +472,40 @@ pub fn move_player(
 
 pub fn handle_show_player_stats_ui(
     keyboard_input: Res<ButtonInput<KeyCode>>,
+    settings: Res<Settings>,
     mut commands: Commands,
     asset_server: Res<AssetServer>,
     sprites: Res<SpritesResources>,
     player_assets_ui_query: Query<Entity, With<PlayerStatsUI>>,
-    mut player_query: Query<(&Speed, &Armor, &Children, &Health, &Mana, &Player)>,
+    mut player_query: Query<(
+        &Speed,
+        &Armor,
+        &Children,
+        &Health,
+        &Mana,
+        &StatModifiers,
+        &Player,
+    )>,
     player_weapon_query: Query<(&Damage, &Weapon)>,
 ) {
     if player_query.get_single_mut().is_err() {
         return;
     }
 
-    if keyboard_input.pressed(KeyCode::KeyK) {
+    if keyboard_input.pressed(settings.key_bindings.player_stats) {
         let number_of_spawned_stats_ui = player_assets_ui_query.iter().len();
 
         // only spawns a new ui if it does not already exist
         if number_of_spawned_stats_ui == 0 {
-            let (player_speed, player_armor, player_children, player_health, player_mana, _) =
-                player_query.get_single_mut().unwrap();
+            let (
+                player_speed,
+                player_armor,
+                player_children,
+                player_health,
+                player_mana,
+                player_stat_modifiers,
+                _,
+            ) = player_query.get_single_mut().unwrap();
             for &child in player_children {
                 if player_weapon_query.get(child).is_err() {
                     continue;
@@ -533,6 +526,7 @@ pub fn handle_show_player_stats_ui(
                     player_weapon_damage.0,
                     player_armor.0,
                     player_speed.0,
+                    player_stat_modifiers,
                 );
 
                 break;
@@ -553,17 +547,24 @@ pub fn power_up(
     asset_server: Res<AssetServer>,
     sprites: Res<SpritesResources>,
     keyboard_input: Res<ButtonInput<KeyCode>>,
-    texture_atlas_layout: ResMut<Assets<TextureAtlasLayout>>,
-    meshes: ResMut<Assets<Mesh>>,
-    materials: ResMut<Assets<ColorMaterial>>,
+    settings: Res<Settings>,
+    registry: Res<PowerRegistry>,
+    mut texture_atlas_layout: ResMut<Assets<TextureAtlasLayout>>,
+    mut meshes: ResMut<Assets<Mesh>>,
+    mut materials: ResMut<Assets<ColorMaterial>>,
 
     mut player_query: Query<(Entity, &mut Mana, &Children, &Transform)>,
-    power_query: Query<(&Damage, &Power)>,
-    base_camera: Query<(&Transform, &BaseCamera), Without<Player>>,
-
-    enemies: Query<(Entity, &mut Health, &Damage, &Enemy), With<Enemy>>,
+    mut power_query: Query<(&Damage, &mut Power)>,
+    power_instances: Query<&PowerInstance>,
+    enemies: Query<&Transform, (With<Enemy>, Without<Player>)>,
+    windows: Query<&Window>,
+    base_camera: Query<
+        (&Transform, &Camera, &GlobalTransform),
+        (With<BaseCamera>, Without<Player>),
+    >,
 ) {
-    let Ok((base_camera_transform, _)) = base_camera.get_single() else {
+    let Ok((base_camera_transform, camera, camera_global_transform)) = base_camera.get_single()
+    else {
         return;
     };
 
@@ -572,68 +573,84 @@ pub fn power_up(
         return;
     };
 
-    let mut current_player_powers = vec![];
     for &child in player_children {
-        if let Ok(player_powers) = power_query.get(child) {
-            current_player_powers.push(player_powers);
+        let Ok((_, mut power)) = power_query.get_mut(child) else {
+            continue;
+        };
+
+        // Ground targeted powers are aimed while the key is held, and cast
+        // when it is released
+        let trigger_key = settings.key_bindings.get(power.key_action);
+        let cast_pressed = match power.targeting {
+            PowerTargeting::GroundTarget { .. } => keyboard_input.just_released(trigger_key),
+            _ => keyboard_input.just_pressed(trigger_key),
+        };
+
+        if cast_pressed {
+            power.buffered_press = Some(Timer::from_seconds(
+                POWER_INPUT_BUFFER_SECONDS,
+                TimerMode::Once,
+            ));
         }
     }
 
-    if current_player_powers.is_empty() {
+    // Only one power is cast at a time
+    let Some(power_entity) = player_children.iter().copied().find(|&child| {
+        power_query.get(child).is_ok_and(|(_, power)| {
+            power.buffered_press.is_some() && power.is_ready() && player_mana.0 >= power.mana_needed
+        })
+    }) else {
         return;
-    }
-
-    let get_player_power_based_on_the_keycode = |key_code: KeyCode| -> Option<(&Damage, &Power)> {
-        for player_power in current_player_powers {
-            let power = player_power.1;
-
-            if power.trigger_key == key_code {
-                return Some(player_power);
-            }
-        }
-
-        None
     };
 
-    let spawn_power_based_on_keypress = |key: KeyCode| -> Option<f32> {
-        let (power_damage, power) = get_player_power_based_on_the_keycode(key)?;
+    let Ok((power_damage, mut power)) = power_query.get_mut(power_entity) else {
+        return;
+    };
 
-        if player_mana.0 < power.mana_needed {
-            return None;
-        }
+    let player_direction = settings.key_bindings.movement_direction(&keyboard_input);
+
+    let player_translation = player_transform.translation + base_camera_transform.translation;
+    let cursor_position = windows
+        .get_single()
+        .ok()
+        .and_then(|window| get_cursor_world_position(window, camera, camera_global_transform));
+    let (target, aim_direction) = get_power_target(
+        power.targeting,
+        player_translation.truncate(),
+        player_direction,
+        cursor_position,
+        enemies
+            .iter()
+            .map(|transform| transform.translation.truncate()),
+    );
 
-        let player_translation = player_transform.translation + base_camera_transform.translation;
-
-        spawn_power(
-            &mut commands,
-            texture_atlas_layout,
-            &sprites,
-            asset_server,
-            meshes,
-            materials,
-            power.clone(),
-            power_damage.clone(),
-            player_translation,
-            enemies,
-        );
-        Some(power.mana_needed)
+    let active_instances = power_instances
+        .iter()
+        .filter(|instance| instance.power_type == power.power_type)
+        .count();
+
+    let mut ctx = PowerSpawnContext {
+        commands: &mut commands,
+        meshes: &mut meshes,
+        materials: &mut materials,
+        texture_atlas_layout: &mut texture_atlas_layout,
+        sprites: &sprites,
+        asset_server: &asset_server,
+        keyboard_input: &keyboard_input,
+        settings: &settings,
+        player_translation,
+        target,
+        aim_direction,
+        active_instances,
     };
-
-    let optional_mana = if keyboard_input.any_just_pressed([KeyCode::KeyH].into_iter()) {
-        spawn_power_based_on_keypress(KeyCode::KeyH)
-    } else if keyboard_input.any_just_pressed([KeyCode::KeyJ].into_iter()) {
-        spawn_power_based_on_keypress(KeyCode::KeyJ)
-    } else if keyboard_input.any_just_pressed([KeyCode::KeyL].into_iter()) {
-        spawn_power_based_on_keypress(KeyCode::KeyL)
-    } else {
+    if !spawn_power(&registry, &mut ctx, &power, power_damage) {
         return;
-    };
+    }
 
-    let Some(mana_needed) = optional_mana else {
-        return;
-    };
+    power.charges -= 1;
+    power.buffered_press = None;
 
-    player_mana.0 -= mana_needed;
+    player_mana.0 -= power.mana_needed;
     commands.trigger(PlayerManaChanged {
         mana: player_mana.0,
     });
@@ -690,3 +707,89 @@ fn _handle_button_click<T: Component>(
         }
     }
 }
+
+fn _update_button_color(interaction: &Interaction, background_color: &mut BackgroundColor) {
+    match *interaction {
+        Interaction::Hovered => {
+            *background_color = Color::srgba(26., 50., 27., 0.3).into();
+        }
+        Interaction::Pressed | Interaction::None => {
+            *background_color = Color::BLACK.into();
+        }
+    }
+}
+
+/// Opens the settings menu from the main menu or the pause screen.
+pub fn handle_settings_button_click(
+    mut commands: Commands,
+    asset_server: Res<AssetServer>,
+    settings: Res<Settings>,
+    registry: Res<PowerRegistry>,
+    settings_menu: Query<(), With<SettingsMenu>>,
+    mut interaction_query: Query<
+        (&Interaction, &mut BackgroundColor),
+        (Changed<Interaction>, With<SettingsButton>),
+    >,
+) {
+    for (interaction, mut background_color) in &mut interaction_query {
+        _update_button_color(interaction, &mut background_color);
+
+        if *interaction == Interaction::Pressed && settings_menu.is_empty() {
+            spawn_settings_menu(&mut commands, &asset_server, &settings, &registry);
+        }
+    }
+}
+
+/// Key binding options wait for the next key pressed, the others go to
+/// their next value.
+pub fn handle_settings_option_click(
+    mut commands: Commands,
+    mut settings: ResMut<Settings>,
+    mut interaction_query: Query<
+        (&Interaction, &mut BackgroundColor, &SettingsOptionButton),
+        Changed<Interaction>,
+    >,
+) {
+    for (interaction, mut background_color, option_button) in &mut interaction_query {
+        _update_button_color(interaction, &mut background_color);
+
+        if *interaction != Interaction::Pressed {
+            continue;
+        }
+
+        match option_button.0 {
+            SettingsOptionEnum::KeyBinding(action) => {
+                commands.insert_resource(PendingKeyBinding(action));
+            }
+            option => settings.cycle(option),
+        }
+    }
+}
+
+/// Escape also closes the menu, unless it is cancelling a key binding.
+pub fn handle_settings_close_click(
+    mut commands: Commands,
+    keyboard_input: Res<ButtonInput<KeyCode>>,
+    pending_key_binding: Option<Res<PendingKeyBinding>>,
+    settings_menu: Query<Entity, With<SettingsMenu>>,
+    mut interaction_query: Query<
+        (&Interaction, &mut BackgroundColor),
+        (Changed<Interaction>, With<SettingsCloseButton>),
+    >,
+) {
+    let mut close = pending_key_binding.is_none() && keyboard_input.just_pressed(KeyCode::Escape);
+
+    for (interaction, mut background_color) in &mut interaction_query {
+        _update_button_color(interaction, &mut background_color);
+        close |= *interaction == Interaction::Pressed;
+    }
+
+    if !close {
+        return;
+    }
+
+    for settings_menu_entity in settings_menu.iter() {
+        commands.entity(settings_menu_entity).despawn_recursive();
+    }
+    commands.remove_resource::<PendingKeyBinding>();
+}
@@ -118,3 +118,29 @@ pub fn spawn_item(
         commands.spawn(bundle);
     }
 }
+
+/// Spawns the item right where it was dropped.
+pub fn spawn_item_at(
+    commands: &mut Commands,
+    texture_atlas_layout: &mut ResMut<Assets<TextureAtlasLayout>>,
+    sprites: &Res<SpritesResources>,
+    asset_server: &Res<AssetServer>,
+
+    item_type: ItemTypeEnum,
+    level: u16,
+    position: Vec2,
+) {
+    let scale = Vec3::splat(2.);
+
+    let mut bundle = ItemBundle::new(
+        texture_atlas_layout,
+        sprites,
+        asset_server,
+        scale,
+        Vec3::ZERO,
+        get_item_based_on_game_level(item_type, level),
+    );
+    bundle.sprite.transform.translation = position.extend(CHAR_Z_INDEX);
+
+    commands.spawn(bundle);
+}
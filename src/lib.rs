@@ -1,42 +1,70 @@
 pub mod ammo;
 pub mod animation;
+pub mod arena;
 mod audio;
+pub mod boss;
 pub mod buffs;
+pub mod bullet_patterns;
 pub mod camera;
 pub mod cleanup;
 pub mod collision;
 pub mod config;
+pub mod dash;
+pub mod elite;
 pub mod enemy;
 pub mod events;
+pub mod flocking;
 pub mod game_actions;
 pub mod item;
+pub mod loot;
+pub mod minimap;
+pub mod obstacle;
+pub mod pathfinding;
+pub mod perception;
 pub mod player;
 pub mod powers;
+pub mod props;
 mod prelude;
 pub mod resources;
+pub mod settings;
 pub mod sprites;
 pub mod stats;
 pub mod system_sets;
+pub mod tilemap;
 pub mod ui;
 mod util;
 pub mod weapon;
 
 pub use ammo::*;
 pub use animation::*;
+pub use arena::*;
+pub use boss::*;
 pub use buffs::*;
+pub use bullet_patterns::*;
 pub use camera::*;
 pub use cleanup::*;
 pub use collision::*;
 pub use config::*;
+pub use dash::*;
+pub use elite::*;
 pub use enemy::*;
 pub use events::*;
+pub use flocking::*;
 pub use game_actions::*;
 pub use item::*;
+pub use loot::*;
+pub use minimap::*;
+pub use obstacle::*;
+pub use pathfinding::*;
+pub use perception::*;
 pub use player::*;
 pub use powers::*;
+pub use props::*;
 pub use resources::*;
+pub use settings::*;
 pub use sprites::*;
 pub use stats::*;
 pub use system_sets::*;
+pub use tilemap::*;
 pub use ui::*;
 pub use weapon::*;
@@ -0,0 +1,305 @@
+use rand::{seq::SliceRandom, Rng};
+
+use crate::{
+    prelude::*, spawn_chest_reward_choice_ui, spawn_item_at, ChestRewardChoiceUI,
+    CleanupWhenPlayerDies, Coins, Health, Item, ItemTypeEnum, Mana, Player, ScoreChanged,
+    SpritesResources, StatModifier, StatModifierOp, StatModifiers, Tilemap,
+};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ChestKind {
+    // Spills more loot
+    Loot,
+    // Lets the player choose a reward
+    Reward,
+}
+
+/// Opens when the player walks into it.
+#[derive(Component, Clone)]
+pub struct Chest {
+    pub kind: ChestKind,
+}
+
+/// Set while the player has to choose the reward of an opened chest.
+#[derive(Resource)]
+pub struct PendingChestReward {
+    pub choices: Vec<ChestReward>,
+}
+
+/// One weighted pick from the loot entries. Luck makes every drop but
+/// `Nothing` more likely.
+pub(crate) fn roll_loot(entries: &[LootEntry], luck: f32) -> LootDropEnum {
+    let weight = |entry: &LootEntry| match entry.drop {
+        LootDropEnum::Nothing => entry.weight as f32,
+        _ => entry.weight as f32 * (1. + luck.max(0.)),
+    };
+
+    let mut rng = rand::thread_rng();
+    let total_weight: f32 = entries.iter().map(weight).sum();
+    let mut roll = rng.gen_range(0.0..total_weight.max(1.));
+
+    for entry in entries {
+        if roll < weight(entry) {
+            return entry.drop;
+        }
+        roll -= weight(entry);
+    }
+
+    LootDropEnum::Nothing
+}
+
+/// Every guaranteed drop of the table plus its rolls, spread around the
+/// position.
+pub(crate) fn drop_loot(
+    commands: &mut Commands,
+    texture_atlas_layout: &mut ResMut<Assets<TextureAtlasLayout>>,
+    sprites: &Res<SpritesResources>,
+    asset_server: &Res<AssetServer>,
+    loot_table: &LootTable,
+    position: Vec2,
+    level: u16,
+    luck: f32,
+) {
+    let drops: Vec<LootDropEnum> = loot_table
+        .guaranteed
+        .iter()
+        .copied()
+        .chain((0..loot_table.rolls).map(|_| roll_loot(loot_table.entries, luck)))
+        .filter(|&drop| drop != LootDropEnum::Nothing)
+        .collect();
+
+    for (idx, &drop) in drops.iter().enumerate() {
+        // The first one lands right on the spot
+        let offset = if idx == 0 {
+            Vec2::ZERO
+        } else {
+            Vec2::from_angle(idx as f32 * std::f32::consts::TAU / (drops.len() - 1) as f32)
+                * LOOT_DROP_SPREAD
+        };
+
+        spawn_loot(
+            commands,
+            texture_atlas_layout,
+            sprites,
+            asset_server,
+            drop,
+            position + offset,
+            level,
+        );
+    }
+}
+
+/// Luck of the player plus the one given by the level.
+pub(crate) fn get_luck(player_luck: f32, level: u16) -> f32 {
+    player_luck + LOOT_LUCK_PER_LEVEL * level.saturating_sub(1) as f32
+}
+
+pub(crate) fn spawn_loot(
+    commands: &mut Commands,
+    texture_atlas_layout: &mut ResMut<Assets<TextureAtlasLayout>>,
+    sprites: &Res<SpritesResources>,
+    asset_server: &Res<AssetServer>,
+    drop: LootDropEnum,
+    position: Vec2,
+    level: u16,
+) {
+    let item_type = match drop {
+        LootDropEnum::Nothing => return,
+        LootDropEnum::HealthPack => ItemTypeEnum::Health(Health(LOOT_HEALTH_PACK_HEALTH)),
+        LootDropEnum::ManaPack => ItemTypeEnum::Mana(Mana(LOOT_MANA_PACK_MANA)),
+        LootDropEnum::Coins => ItemTypeEnum::Coins(Coins(COINS_PER_DROP)),
+        LootDropEnum::Chest => {
+            spawn_chest(
+                commands,
+                texture_atlas_layout,
+                sprites,
+                asset_server,
+                ChestKind::Loot,
+                position,
+            );
+            return;
+        }
+        LootDropEnum::RewardChest => {
+            spawn_chest(
+                commands,
+                texture_atlas_layout,
+                sprites,
+                asset_server,
+                ChestKind::Reward,
+                position,
+            );
+            return;
+        }
+    };
+
+    spawn_item_at(
+        commands,
+        texture_atlas_layout,
+        sprites,
+        asset_server,
+        item_type,
+        level,
+        position,
+    );
+}
+
+fn spawn_chest(
+    commands: &mut Commands,
+    texture_atlas_layout: &mut ResMut<Assets<TextureAtlasLayout>>,
+    sprites: &Res<SpritesResources>,
+    asset_server: &Res<AssetServer>,
+    kind: ChestKind,
+    position: Vec2,
+) {
+    // There is no chest sprite, it is a painted crate
+    let chest_sprite = sprites.0.crate_box.clone();
+    let (color, scale) = match kind {
+        ChestKind::Loot => (CHEST_COLOR, CHEST_SCALE),
+        ChestKind::Reward => (REWARD_CHEST_COLOR, REWARD_CHEST_SCALE),
+    };
+
+    commands.spawn((
+        SpriteBundle {
+            texture: asset_server.load(chest_sprite.source),
+            sprite: Sprite { color, ..default() },
+            transform: Transform {
+                translation: position.extend(CHAR_Z_INDEX),
+                scale: Vec3::splat(scale),
+                ..default()
+            },
+            ..default()
+        },
+        TextureAtlas {
+            layout: texture_atlas_layout.add(chest_sprite.layout),
+            index: 0,
+        },
+        Chest { kind },
+        Name::new("Chest"),
+        BASE_LAYER,
+        CleanupWhenPlayerDies,
+    ));
+}
+
+/// Drops the loot of an opened chest around it.
+pub(crate) fn open_chest(
+    commands: &mut Commands,
+    texture_atlas_layout: &mut ResMut<Assets<TextureAtlasLayout>>,
+    sprites: &Res<SpritesResources>,
+    asset_server: &Res<AssetServer>,
+    position: Vec2,
+    level: u16,
+    luck: f32,
+) {
+    let chest_loot_entries: Vec<LootEntry> = PROP_LOOT_TABLE
+        .entries
+        .iter()
+        .copied()
+        .filter(|entry| {
+            !matches!(
+                entry.drop,
+                LootDropEnum::Nothing | LootDropEnum::Chest | LootDropEnum::RewardChest
+            )
+        })
+        .collect();
+
+    for idx in 0..CHEST_LOOT_ROLLS {
+        let angle = idx as f32 * std::f32::consts::TAU / CHEST_LOOT_ROLLS as f32;
+
+        spawn_loot(
+            commands,
+            texture_atlas_layout,
+            sprites,
+            asset_server,
+            roll_loot(&chest_loot_entries, luck),
+            position + Vec2::from_angle(angle) * CHEST_LOOT_SPREAD,
+            level,
+        );
+    }
+}
+
+/// Shows a few random rewards for the player to choose from.
+pub(crate) fn open_reward_chest(commands: &mut Commands, asset_server: &Res<AssetServer>) {
+    let choices: Vec<ChestReward> = CHEST_REWARDS
+        .choose_multiple(&mut rand::thread_rng(), CHEST_REWARD_CHOICES)
+        .copied()
+        .collect();
+
+    spawn_chest_reward_choice_ui(commands, asset_server, &choices);
+    commands.insert_resource(PendingChestReward { choices });
+}
+
+pub fn choose_chest_reward(
+    mut commands: Commands,
+    keyboard_input: Res<ButtonInput<KeyCode>>,
+    pending_reward: Option<Res<PendingChestReward>>,
+    mut player_query: Query<&mut StatModifiers, With<Player>>,
+    choice_ui: Query<Entity, With<ChestRewardChoiceUI>>,
+) {
+    let Some(pending_reward) = pending_reward else {
+        return;
+    };
+
+    // The choice was cleaned up with everything else when the player died
+    if choice_ui.is_empty() {
+        commands.remove_resource::<PendingChestReward>();
+        return;
+    }
+
+    let number_keys = [KeyCode::Digit1, KeyCode::Digit2, KeyCode::Digit3];
+    let Some(choice) = number_keys
+        .iter()
+        .take(pending_reward.choices.len())
+        .position(|&key| keyboard_input.just_pressed(key))
+    else {
+        return;
+    };
+
+    let Ok(mut player_stat_modifiers) = player_query.get_single_mut() else {
+        return;
+    };
+
+    let chest_reward = pending_reward.choices[choice];
+    match chest_reward.reward {
+        ChestRewardEnum::Stat(stat, value) => {
+            player_stat_modifiers.add(StatModifier::new(
+                stat,
+                StatModifierOp::Add(value),
+                chest_reward.name,
+            ));
+        }
+        ChestRewardEnum::Coins(coins) => {
+            commands.trigger(ScoreChanged {
+                score: coins as f32 * COIN_SCORE,
+            });
+        }
+    }
+
+    commands.remove_resource::<PendingChestReward>();
+
+    for choice_ui_entity in choice_ui.iter() {
+        commands.entity(choice_ui_entity).despawn_recursive();
+    }
+}
+
+/// Loot that lands on a solid tile, e.g. next to a wall or after the level
+/// changed under it, is moved to the closest walkable tile.
+pub fn move_loot_out_of_solid_tiles(
+    tilemap: Option<Res<Tilemap>>,
+    mut loot: Query<&mut Transform, Or<(With<Item>, With<Chest>)>>,
+) {
+    let Some(tilemap) = tilemap else {
+        return;
+    };
+
+    for mut loot_transform in loot.iter_mut() {
+        let position = loot_transform.translation.truncate();
+        if !tilemap.is_solid(position) {
+            continue;
+        }
+
+        if let Some(walkable_position) = tilemap.closest_walkable_position(position) {
+            loot_transform.translation.x = walkable_position.x;
+            loot_transform.translation.y = walkable_position.y;
+        }
+    }
+}
@@ -48,6 +48,8 @@ fn main() {
         //     ..default()
         // }),
         Wireframe2dPlugin,
+        UiMaterialPlugin::<PowerCooldownMaterial>::default(),
+        PowersPlugin,
     ));
 
     #[cfg(not(feature = "web"))]
@@ -57,7 +59,12 @@ fn main() {
             .register_type::<Ammo>()
             .register_type::<Item>()
             .register_type::<Power>()
-            .register_type::<CircleOfDeath>()
+            .register_type::<PowerInstance>()
+            .register_type::<Dash>()
+            .register_type::<Perception>()
+            .register_type::<BaseStats>()
+            .register_type::<StatModifiers>()
+            .register_type::<Luck>()
             .add_plugins(WorldInspectorPlugin::new());
     }
 
@@ -80,22 +87,43 @@ fn main() {
             ),
         )
         // systems
-        .add_systems(Update, on_window_resize)
+        .add_systems(Update, (fit_cameras_to_window, toggle_fullscreen))
+        .add_systems(Update, (follow_base_camera, apply_camera_effects).chain())
+        .add_systems(
+            Update,
+            (
+                handle_settings_button_click,
+                handle_settings_option_click,
+                // Escape cancels the key binding before closing the menu
+                handle_settings_close_click,
+                read_pending_key_binding,
+                update_settings_menu_text,
+                update_power_key_ui,
+                apply_settings,
+                apply_audio_volume,
+                save_settings,
+            )
+                .chain(),
+        )
         .add_systems(
             Startup,
             (
+                setup_letterbox_camera,
                 setup_base_camera,
                 setup_player_camera,
                 setup_overlay_camera,
                 setup_menu_camera,
+                load_settings,
                 setup_resources,
                 setup_sprite,
+                setup_bullet_pool,
             )
                 .chain(),
         )
         .add_systems(
             OnEnter(GameState::Start),
             (
+                reset_bullet_pool,
                 cleanup_system::<MenuOverlay>,
                 cleanup_system::<GameOverOverlay>,
                 cleanup_system::<GameWonOverlay>,
@@ -113,8 +141,14 @@ fn main() {
                 move_player_ammo,
                 move_enemy_ammo,
                 animate_sprite,
-                move_laser_power,
+                tick_powers,
+                update_enemy_spatial_grid.before(move_enemies_towards_player),
+                update_enemy_perception.before(move_enemies_towards_player),
+                update_player_flow_field.before(move_enemies_towards_player),
+                move_new_enemies_to_spawn_zones.before(move_enemies_towards_player),
                 move_enemies_towards_player,
+                move_boss_lasers,
+                move_pooled_bullets,
                 get_mouse_cursor_position,
             )
                 .in_set(MoveSet),
@@ -123,10 +157,14 @@ fn main() {
             FixedUpdate,
             (
                 move_player,
+                start_dash.before(move_player),
                 handle_click.run_if(single_ammo_shooting),
                 handle_show_player_stats_ui,
                 power_up,
+                update_power_target_previews,
                 enable_disable_autoshooting,
+                choose_chest_reward,
+                toggle_minimap,
             )
                 .in_set(InputSet),
         )
@@ -134,12 +172,24 @@ fn main() {
             FixedUpdate,
             (
                 check_for_ammo_collisions_with_enemy,
+                check_for_ammo_collisions_with_props,
                 check_for_power_collisions_with_enemy,
                 check_for_player_collisions_to_enemy,
                 check_for_item_collisions,
+                check_for_chest_collisions,
                 check_for_weapon_collisions,
                 check_for_offensive_buff_collisions_with_enemy,
+                // Blocked projectiles must not hurt the player afterwards
+                check_for_enemy_projectile_collisions_with_shields
+                    .before(check_for_ammo_collisions_with_player)
+                    .before(check_for_pooled_bullet_collisions_with_player),
                 check_for_ammo_collisions_with_player,
+                check_for_boss_laser_collisions_with_player,
+                check_for_pooled_bullet_collisions_with_player,
+                check_for_elite_explosions_with_player,
+                check_for_hazard_tiles_under_player.run_if(on_timer(Duration::from_millis(
+                    HAZARD_TILE_DAMAGE_INTERVAL_MILLIS,
+                ))),
             )
                 .in_set(CollisionSet),
         )
@@ -148,7 +198,11 @@ fn main() {
         .add_systems(OnEnter(GameState::Won), game_won_screen)
         .add_systems(
             OnEnter(GameState::InBetweenLevels),
-            in_between_levels_pause_screen,
+            (in_between_levels_pause_screen, reset_bullet_pool),
+        )
+        .add_systems(
+            OnEnter(GameState::BossIntro),
+            (boss_intro_screen, start_boss_intro),
         )
         .add_systems(
             FixedUpdate,
@@ -156,9 +210,15 @@ fn main() {
                 handle_start_game_click.run_if(in_state(GameState::Menu)),
                 handle_restart_click.run_if(in_state(GameState::Dead)),
                 handle_play_again_click.run_if(in_state(GameState::Won)),
+                choose_power_upgrade.run_if(in_state(GameState::InBetweenLevels)),
+                // Waits for the player to choose the upgrade of their power
                 despawn_in_between_levels_pause_screen
                     .run_if(in_state(GameState::InBetweenLevels))
+                    // Waits for the settings menu to be closed too
+                    .run_if(not(any_with_component::<SettingsMenu>))
+                    .run_if(not(resource_exists::<PendingPowerUpgrade>))
                     .run_if(on_timer(Duration::from_secs(PAUSE_IN_BETWEEN_LEVELS))),
+                animate_boss_intro.run_if(in_state(GameState::BossIntro)),
             ),
         )
         .add_systems(
@@ -169,20 +229,50 @@ fn main() {
                 animate_player_buffs.run_if(on_timer(Duration::from_nanos(100))),
                 refill_health.run_if(on_timer(Duration::from_secs(1))),
                 refill_mana.run_if(on_timer(Duration::from_secs(1))),
-                expand_circle_of_death.run_if(on_timer(Duration::from_millis(50))),
+                expire_powers,
+                tick_power_cooldowns,
+                update_power_cooldown_ui,
+                tick_dash,
+                apply_stat_modifiers,
+                fade_dash_trails,
                 change_enemy_direction.run_if(on_timer(Duration::from_secs(5))),
-                shoot_at_player.run_if(on_timer(Duration::from_secs(2))),
-                make_boss_spawn_enemies.run_if(on_timer(Duration::from_secs(10))),
+                tick_bullet_pattern_emitters,
+                roll_elite_affixes,
+                teleport_elites,
+                (update_boss_phase, tick_boss_attacks).chain(),
+                despawn_orphan_boss_telegraphs,
                 auto_shoot.run_if(on_timer(Duration::from_millis(200))),
             )
                 .in_set(TimeBasedSet),
         )
+        .add_systems(
+            FixedUpdate,
+            (
+                regrow_broken_shields,
+                fade_shield_shards,
+                reset_props,
+                despawn_broken_props,
+                fade_prop_shards,
+                move_loot_out_of_solid_tiles,
+                update_minimap.run_if(on_timer(Duration::from_millis(MINIMAP_UPDATE_MILLIS))),
+                update_offscreen_indicators,
+                float_damage_numbers,
+            )
+                .in_set(TimeBasedSet),
+        )
         .observe(on_player_spawned)
         .observe(on_mouse_click)
         .observe(on_player_health_changed)
         .observe(on_player_mana_changed)
+        .observe(on_player_dealt_damage)
+        .observe(on_player_dealt_damage_show_number)
         .observe(on_enemy_health_changed)
         .observe(on_all_enemies_died)
+        .observe(on_elite_died)
+        .observe(on_boss_spawned)
+        .observe(on_boss_health_changed)
+        .observe(on_boss_phase_changed)
+        .observe(on_boss_enraged)
         .observe(on_game_over)
         .observe(on_restart_click)
         .observe(on_score_changed)
@@ -194,10 +284,11 @@ fn main() {
         .observe(on_weapon_found)
         .observe(on_player_profile_ui_set)
         .observe(on_power_found)
-        .observe(despawn_powers)
+        .observe(on_power_upgrade_chosen)
         .observe(update_power_ui)
-        .observe(maybe_spawn_health_points_pack)
-        .observe(maybe_spawn_mana_points_pack)
+        .observe(on_enemy_died_drop_loot)
+        .observe(on_prop_broken)
+        .observe(on_screen_shake)
         .observe(setup_new_time)
         .observe(change_background_texture)
         .observe(on_current_game_level_changed)
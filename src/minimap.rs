@@ -0,0 +1,388 @@
+use bevy::sprite::{MaterialMesh2dBundle, Mesh2dHandle};
+
+use crate::{
+    prelude::*, BaseCamera, Boss, Chest, CleanupWhenPlayerDies, Enemy, Item, MinimapEnabled,
+    OverlayCamera, Player, Settings, Tilemap, Weapon,
+};
+
+#[derive(Component)]
+pub struct MinimapUI;
+
+#[derive(Component)]
+pub struct MinimapDot;
+
+/// Arrow on the edge of the screen pointing at something outside of it.
+#[derive(Component)]
+pub struct OffscreenIndicator;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum MinimapMarker {
+    Player,
+    Enemy,
+    Boss,
+    Item,
+    Weapon,
+}
+
+impl MinimapMarker {
+    fn color(&self) -> Color {
+        match self {
+            MinimapMarker::Player => MINIMAP_PLAYER_COLOR,
+            MinimapMarker::Enemy => MINIMAP_ENEMY_COLOR,
+            MinimapMarker::Boss => MINIMAP_BOSS_COLOR,
+            MinimapMarker::Item => MINIMAP_ITEM_COLOR,
+            MinimapMarker::Weapon => MINIMAP_WEAPON_COLOR,
+        }
+    }
+
+    fn dot_size(&self) -> f32 {
+        match self {
+            MinimapMarker::Player => MINIMAP_PLAYER_DOT_SIZE_PX,
+            MinimapMarker::Boss => MINIMAP_BOSS_DOT_SIZE_PX,
+            MinimapMarker::Enemy | MinimapMarker::Item | MinimapMarker::Weapon => {
+                MINIMAP_DOT_SIZE_PX
+            }
+        }
+    }
+}
+
+pub(crate) fn spawn_minimap_ui(commands: &mut Commands) {
+    commands.spawn((
+        NodeBundle {
+            style: Style {
+                position_type: PositionType::Absolute,
+                top: Val::Px(45.),
+                right: Val::Px(10.),
+                width: Val::Px(MINIMAP_WIDTH_PX),
+                height: Val::Px(MINIMAP_HEIGHT_PX),
+                overflow: Overflow::clip(),
+                ..default()
+            },
+            border_radius: BorderRadius::all(Val::Px(5.)),
+            background_color: BackgroundColor(MINIMAP_BACKGROUND_COLOR),
+            ..default()
+        },
+        MinimapUI,
+        Name::new("Minimap"),
+        OVERLAY_LAYER,
+        CleanupWhenPlayerDies,
+    ));
+}
+
+/// Size of the whole map on the world. Without a tilemap it is the
+/// background texture.
+fn get_map_size(tilemap: Option<&Tilemap>) -> Vec2 {
+    match tilemap {
+        Some(tilemap) => {
+            Vec2::new(tilemap.columns as f32, tilemap.rows as f32) * Tilemap::tile_size()
+        }
+        None => {
+            Vec2::new(
+                BACKGROUND_TEXTURE_RESOLUTION.x_px,
+                BACKGROUND_TEXTURE_RESOLUTION.y_px,
+            ) * BACKGROUND_TEXTURE_SCALE
+        }
+    }
+}
+
+pub fn toggle_minimap(
+    keyboard_input: Res<ButtonInput<KeyCode>>,
+    settings: Res<Settings>,
+    mut minimap_enabled: ResMut<MinimapEnabled>,
+    mut minimap_ui: Query<&mut Visibility, With<MinimapUI>>,
+) {
+    if keyboard_input.just_pressed(settings.key_bindings.minimap) {
+        minimap_enabled.0 = !minimap_enabled.0;
+    }
+
+    // Also hides the minimap spawned for a new game
+    let minimap_visibility = if minimap_enabled.0 {
+        Visibility::Inherited
+    } else {
+        Visibility::Hidden
+    };
+
+    for mut visibility in minimap_ui.iter_mut() {
+        visibility.set_if_neq(minimap_visibility);
+    }
+}
+
+/// Draws a dot for everything worth knowing where it is. The dots are kept
+/// as children of the minimap and reused, the ones left over are hidden.
+pub fn update_minimap(
+    mut commands: Commands,
+    minimap_enabled: Res<MinimapEnabled>,
+    tilemap: Option<Res<Tilemap>>,
+    minimap_ui: Query<(Entity, Option<&Children>), With<MinimapUI>>,
+    mut dots: Query<(&mut Style, &mut BackgroundColor, &mut Visibility), With<MinimapDot>>,
+    player_query: Query<&Transform, With<Player>>,
+    base_camera: Query<&Transform, (With<BaseCamera>, Without<Player>)>,
+    enemies: Query<(&Transform, Option<&Boss>), With<Enemy>>,
+    items: Query<&Transform, Or<(With<Item>, With<Chest>)>>,
+    weapons: Query<&Transform, (With<Weapon>, Without<Parent>)>,
+) {
+    if !minimap_enabled.0 {
+        return;
+    }
+
+    let Ok((minimap_entity, minimap_children)) = minimap_ui.get_single() else {
+        return;
+    };
+
+    let Ok(player_transform) = player_query.get_single() else {
+        return;
+    };
+
+    let Ok(base_camera_transform) = base_camera.get_single() else {
+        return;
+    };
+
+    let player_position =
+        player_transform.translation.truncate() + base_camera_transform.translation.truncate();
+
+    let markers = items
+        .iter()
+        .map(|transform| (transform.translation.truncate(), MinimapMarker::Item))
+        .chain(
+            weapons
+                .iter()
+                .map(|transform| (transform.translation.truncate(), MinimapMarker::Weapon)),
+        )
+        .chain(enemies.iter().map(|(transform, boss)| {
+            let marker = match boss {
+                Some(_) => MinimapMarker::Boss,
+                None => MinimapMarker::Enemy,
+            };
+            (transform.translation.truncate(), marker)
+        }))
+        // Last, so it is drawn on top
+        .chain(std::iter::once((player_position, MinimapMarker::Player)));
+
+    let map_size = get_map_size(tilemap.as_deref());
+    // In the order they are drawn
+    let pooled_dots: Vec<Entity> = minimap_children
+        .into_iter()
+        .flatten()
+        .copied()
+        .filter(|&child| dots.contains(child))
+        .collect();
+    let mut pooled_dots = pooled_dots.into_iter();
+
+    for (position, marker) in markers {
+        // From the center of the map with y up to the top left corner
+        // with y down
+        let left = Val::Percent(((position.x / map_size.x + 0.5) * 100.).clamp(0., 100.));
+        let top = Val::Percent(((0.5 - position.y / map_size.y) * 100.).clamp(0., 100.));
+        let size = marker.dot_size();
+        let margin = UiRect::new(
+            Val::Px(-size / 2.),
+            Val::ZERO,
+            Val::Px(-size / 2.),
+            Val::ZERO,
+        );
+
+        let Some(dot_entity) = pooled_dots.next() else {
+            let dot_entity = commands
+                .spawn((
+                    NodeBundle {
+                        style: Style {
+                            position_type: PositionType::Absolute,
+                            left,
+                            top,
+                            width: Val::Px(size),
+                            height: Val::Px(size),
+                            margin,
+                            ..default()
+                        },
+                        border_radius: BorderRadius::MAX,
+                        background_color: BackgroundColor(marker.color()),
+                        ..default()
+                    },
+                    MinimapDot,
+                    OVERLAY_LAYER,
+                ))
+                .id();
+            commands.entity(minimap_entity).add_child(dot_entity);
+            continue;
+        };
+
+        let Ok((mut style, mut background_color, mut visibility)) = dots.get_mut(dot_entity) else {
+            continue;
+        };
+
+        style.left = left;
+        style.top = top;
+        style.width = Val::Px(size);
+        style.height = Val::Px(size);
+        style.margin = margin;
+        background_color.0 = marker.color();
+        visibility.set_if_neq(Visibility::Inherited);
+    }
+
+    for dot_entity in pooled_dots {
+        if let Ok((_, _, mut visibility)) = dots.get_mut(dot_entity) {
+            visibility.set_if_neq(Visibility::Hidden);
+        }
+    }
+}
+
+/// Points at the enemies, bosses and weapons and chests outside of the
+/// screen. The arrows are reused, the ones left over are hidden.
+pub fn update_offscreen_indicators(
+    mut commands: Commands,
+    mut meshes: ResMut<Assets<Mesh>>,
+    mut materials: ResMut<Assets<ColorMaterial>>,
+    mut indicator_assets: Local<
+        Option<(Mesh2dHandle, Vec<(MinimapMarker, Handle<ColorMaterial>)>)>,
+    >,
+    minimap_enabled: Res<MinimapEnabled>,
+    mut indicators: Query<
+        (&mut Transform, &mut Handle<ColorMaterial>, &mut Visibility),
+        With<OffscreenIndicator>,
+    >,
+    overlay_camera: Query<&OrthographicProjection, With<OverlayCamera>>,
+    base_camera: Query<&Transform, (With<BaseCamera>, Without<OffscreenIndicator>)>,
+    enemies: Query<(&Transform, Option<&Boss>), (With<Enemy>, Without<OffscreenIndicator>)>,
+    chests: Query<&Transform, (With<Chest>, Without<OffscreenIndicator>)>,
+    weapons: Query<&Transform, (With<Weapon>, Without<Parent>, Without<OffscreenIndicator>)>,
+) {
+    let mut pooled_indicators = indicators.iter_mut();
+
+    let positions = enemies
+        .iter()
+        .map(|(transform, boss)| {
+            let marker = match boss {
+                Some(_) => MinimapMarker::Boss,
+                None => MinimapMarker::Enemy,
+            };
+            (transform.translation.truncate(), marker)
+        })
+        .chain(
+            chests
+                .iter()
+                .map(|transform| (transform.translation.truncate(), MinimapMarker::Item)),
+        )
+        .chain(
+            weapons
+                .iter()
+                .map(|transform| (transform.translation.truncate(), MinimapMarker::Weapon)),
+        );
+
+    let targets = match (overlay_camera.get_single(), base_camera.get_single()) {
+        (Ok(overlay_projection), Ok(base_camera_transform)) if minimap_enabled.0 => {
+            get_offscreen_indicator_targets(
+                overlay_projection,
+                base_camera_transform.translation.truncate(),
+                positions,
+            )
+        }
+        _ => vec![],
+    };
+
+    if !targets.is_empty() {
+        // Created only once, every indicator shares them
+        let (arrow_mesh, arrow_materials) = indicator_assets.get_or_insert_with(|| {
+            let size = OFFSCREEN_INDICATOR_SIZE;
+            let mesh = Mesh2dHandle(meshes.add(Triangle2d::new(
+                Vec2::new(size, 0.),
+                Vec2::new(-size / 2., size / 2.),
+                Vec2::new(-size / 2., -size / 2.),
+            )));
+            let markers = [
+                MinimapMarker::Enemy,
+                MinimapMarker::Boss,
+                MinimapMarker::Item,
+                MinimapMarker::Weapon,
+            ];
+
+            (
+                mesh,
+                markers
+                    .iter()
+                    .map(|&marker| {
+                        let color = marker.color().with_alpha(OFFSCREEN_INDICATOR_ALPHA);
+                        (marker, materials.add(color))
+                    })
+                    .collect(),
+            )
+        });
+
+        for (transform, marker) in targets {
+            let Some((_, arrow_material)) = arrow_materials
+                .iter()
+                .find(|(material_marker, _)| *material_marker == marker)
+            else {
+                continue;
+            };
+
+            let Some((mut indicator_transform, mut indicator_material, mut visibility)) =
+                pooled_indicators.next()
+            else {
+                commands.spawn((
+                    MaterialMesh2dBundle {
+                        mesh: arrow_mesh.clone(),
+                        material: arrow_material.clone(),
+                        transform,
+                        ..default()
+                    },
+                    OffscreenIndicator,
+                    OVERLAY_LAYER,
+                    CleanupWhenPlayerDies,
+                ));
+                continue;
+            };
+
+            *indicator_transform = transform;
+            if *indicator_material != *arrow_material {
+                *indicator_material = arrow_material.clone();
+            }
+            visibility.set_if_neq(Visibility::Inherited);
+        }
+    }
+
+    for (_, _, mut visibility) in pooled_indicators {
+        visibility.set_if_neq(Visibility::Hidden);
+    }
+}
+
+/// Where each arrow goes, on the edge of the screen, and what it points at.
+fn get_offscreen_indicator_targets(
+    overlay_projection: &OrthographicProjection,
+    camera_position: Vec2,
+    positions: impl Iterator<Item = (Vec2, MinimapMarker)>,
+) -> Vec<(Transform, MinimapMarker)> {
+    // What the overlay camera shows is where things are relative to the base
+    // camera. Its area already follows the zoom and the shake of the camera
+    // effects, so it has to be read from the projection instead of the
+    // window size.
+    let screen = overlay_projection.area;
+    let center = screen.center();
+    let half_size = screen.half_size() - Vec2::splat(OFFSCREEN_INDICATOR_MARGIN);
+    if half_size.x <= 0. || half_size.y <= 0. {
+        return vec![];
+    }
+
+    positions
+        .filter_map(|(position, marker)| {
+            let direction = position - camera_position - center;
+
+            if direction.x.abs() <= half_size.x && direction.y.abs() <= half_size.y {
+                return None;
+            }
+
+            // Where the line from the center to the target leaves the screen
+            let edge_distance =
+                (half_size.x / direction.x.abs()).min(half_size.y / direction.y.abs());
+            let indicator_position = center + direction * edge_distance;
+
+            Some((
+                Transform {
+                    translation: indicator_position.extend(UI_Z_INDEX),
+                    rotation: Quat::from_rotation_z(direction.to_angle()),
+                    ..default()
+                },
+                marker,
+            ))
+        })
+        .collect()
+}
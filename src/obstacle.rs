@@ -0,0 +1,102 @@
+use crate::{
+    prelude::*, util::get_obstacle_sprite_based_on_obstacle_kind, SpritesResources, TileBackground,
+};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ObstacleKind {
+    Rock,
+    Crate,
+    DiggingMachine,
+}
+
+impl ObstacleKind {
+    /// How obstacles are written on the level layouts.
+    pub(crate) fn from_char(character: char) -> Option<Self> {
+        match character {
+            'R' => Some(ObstacleKind::Rock),
+            'C' => Some(ObstacleKind::Crate),
+            'M' => Some(ObstacleKind::DiggingMachine),
+            _ => None,
+        }
+    }
+
+    /// Columns and rows of tiles it covers, to the right and below the tile
+    /// it is placed on.
+    pub fn footprint(&self) -> (usize, usize) {
+        match self {
+            ObstacleKind::Rock | ObstacleKind::Crate => (1, 1),
+            ObstacleKind::DiggingMachine => (2, 2),
+        }
+    }
+}
+
+/// Something on the map that blocks movement, projectiles and sight. The
+/// tiles under it are solid.
+#[derive(Component, Clone)]
+pub struct Obstacle {
+    pub kind: ObstacleKind,
+}
+
+pub(crate) fn spawn_obstacle(
+    commands: &mut Commands,
+    texture_atlas_layout: &mut ResMut<Assets<TextureAtlasLayout>>,
+    asset_server: &Res<AssetServer>,
+    sprites: &Res<SpritesResources>,
+    kind: ObstacleKind,
+    position: Vec2,
+) {
+    let obstacle_sprite = get_obstacle_sprite_based_on_obstacle_kind(kind, sprites);
+    // The digging machine is drawn in two halves
+    let offset = match kind {
+        ObstacleKind::DiggingMachine => {
+            -(sprites.0.digging_machine_front.dimensions.width as f32) / 2.
+        }
+        _ => 0.,
+    };
+
+    let mut obstacle = commands.spawn((
+        SpriteBundle {
+            texture: asset_server.load(obstacle_sprite.source),
+            transform: Transform::from_translation(Vec3::new(
+                position.x + offset,
+                position.y,
+                TILE_Z_INDEX + 0.3,
+            )),
+            ..default()
+        },
+        TextureAtlas {
+            layout: texture_atlas_layout.add(obstacle_sprite.layout),
+            index: 0,
+        },
+        Obstacle { kind },
+        // Replaced with the rest of the level
+        TileBackground,
+        Name::new("Obstacle"),
+        BASE_LAYER,
+    ));
+
+    if kind != ObstacleKind::DiggingMachine {
+        return;
+    }
+
+    let front_sprite = sprites.0.digging_machine_front.clone();
+    let front_animation = front_sprite.animation.unwrap();
+    let x = (obstacle_sprite.dimensions.width + front_sprite.dimensions.width) as f32 / 2.;
+
+    obstacle.with_children(|parent| {
+        parent.spawn((
+            SpriteBundle {
+                texture: asset_server.load(front_sprite.source),
+                transform: Transform::from_translation(Vec3::new(x, 0., 0.)),
+                ..default()
+            },
+            TextureAtlas {
+                layout: texture_atlas_layout.add(front_sprite.layout),
+                index: front_animation.indices.first,
+            },
+            front_animation.indices,
+            front_animation.timer,
+            BASE_LAYER,
+        ));
+    });
+}
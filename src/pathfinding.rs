@@ -0,0 +1,263 @@
+use std::{cmp::Reverse, collections::BinaryHeap};
+
+use crate::{prelude::*, BaseCamera, Player, TileKind, Tilemap};
+
+type Cell = (usize, usize);
+
+/// Walking cost of every tile of the level. Solid tiles can't be walked and
+/// hazard tiles are avoided when there is another way around.
+#[derive(Resource, Default)]
+pub struct NavGrid {
+    columns: usize,
+    rows: usize,
+    costs: Vec<Option<u32>>,
+    // Center of the top left tile
+    origin: Vec2,
+}
+
+impl NavGrid {
+    pub fn from_tilemap(tilemap: &Tilemap) -> Self {
+        let costs = tilemap
+            .tiles()
+            .map(|(_, _, kind)| match kind {
+                TileKind::Solid => None,
+                TileKind::Hazard => Some(NAV_HAZARD_TILE_COST),
+                TileKind::Walkable | TileKind::SpawnZone => Some(NAV_TILE_COST),
+            })
+            .collect();
+
+        Self {
+            columns: tilemap.columns,
+            rows: tilemap.rows,
+            costs,
+            origin: tilemap.tile_center(0, 0),
+        }
+    }
+
+    pub fn cell_at(&self, position: Vec2) -> Option<Cell> {
+        let offset = (position - self.origin) / Tilemap::tile_size();
+        let column = offset.x.round();
+        let row = (-offset.y).round();
+
+        if column < 0. || row < 0. || column >= self.columns as f32 || row >= self.rows as f32 {
+            return None;
+        }
+
+        Some((column as usize, row as usize))
+    }
+
+    pub fn cell_center(&self, (column, row): Cell) -> Vec2 {
+        self.origin + Vec2::new(column as f32, -(row as f32)) * Tilemap::tile_size()
+    }
+
+    fn cost(&self, (column, row): Cell) -> Option<u32> {
+        self.costs[row * self.columns + column]
+    }
+
+    /// Walkable cells around this one with the cost to step into them.
+    /// Diagonals are only taken when both sides are free, so nothing cuts
+    /// the corner of a solid tile.
+    fn neighbours(&self, (column, row): Cell) -> impl Iterator<Item = (Cell, u32)> + '_ {
+        const OFFSETS: [(i32, i32); 8] = [
+            (1, 0),
+            (-1, 0),
+            (0, 1),
+            (0, -1),
+            (1, 1),
+            (1, -1),
+            (-1, 1),
+            (-1, -1),
+        ];
+
+        OFFSETS.iter().filter_map(move |&(x, y)| {
+            let next = self.offset_cell((column, row), x, y)?;
+            let cost = self.cost(next)?;
+
+            if x != 0 && y != 0 {
+                self.cost(self.offset_cell((column, row), x, 0)?)?;
+                self.cost(self.offset_cell((column, row), 0, y)?)?;
+                return Some((next, cost * NAV_DIAGONAL_COST / NAV_TILE_COST));
+            }
+
+            Some((next, cost))
+        })
+    }
+
+    fn offset_cell(&self, (column, row): Cell, x: i32, y: i32) -> Option<Cell> {
+        let column = column.checked_add_signed(x as isize)?;
+        let row = row.checked_add_signed(y as isize)?;
+
+        (column < self.columns && row < self.rows).then_some((column, row))
+    }
+
+    /// A* from one position to the other. The path is reversed, so the next
+    /// waypoint is the last one. `None` when there is no way to get there.
+    pub fn find_path(&self, from: Vec2, to: Vec2) -> Option<Vec<Vec2>> {
+        let start = self.cell_at(from)?;
+        let goal = self.cell_at(to)?;
+        self.cost(goal)?;
+
+        let heuristic = |(column, row): Cell| {
+            let dx = column.abs_diff(goal.0) as u32;
+            let dy = row.abs_diff(goal.1) as u32;
+            NAV_TILE_COST * dx.max(dy) + (NAV_DIAGONAL_COST - NAV_TILE_COST) * dx.min(dy)
+        };
+
+        let idx = |(column, row): Cell| row * self.columns + column;
+        let mut costs = vec![u32::MAX; self.costs.len()];
+        let mut came_from: Vec<Option<Cell>> = vec![None; self.costs.len()];
+        let mut open = BinaryHeap::from([Reverse((heuristic(start), start))]);
+        costs[idx(start)] = 0;
+
+        while let Some(Reverse((_, cell))) = open.pop() {
+            if cell == goal {
+                let mut path = vec![to];
+                let mut current = cell;
+
+                while let Some(previous) = came_from[idx(current)] {
+                    if previous != start {
+                        path.push(self.cell_center(previous));
+                    }
+                    current = previous;
+                }
+
+                return Some(path);
+            }
+
+            for (next, step_cost) in self.neighbours(cell) {
+                let cost = costs[idx(cell)] + step_cost;
+
+                if cost < costs[idx(next)] {
+                    costs[idx(next)] = cost;
+                    came_from[idx(next)] = Some(cell);
+                    open.push(Reverse((cost + heuristic(next), next)));
+                }
+            }
+        }
+
+        None
+    }
+}
+
+/// Distance from every cell to the player, shared by all the enemies
+/// chasing him. It is only computed again once the player steps on another
+/// cell.
+#[derive(Resource, Default)]
+pub struct PlayerFlowField {
+    target: Option<Cell>,
+    distances: Vec<u32>,
+}
+
+impl PlayerFlowField {
+    fn compute(&mut self, nav_grid: &NavGrid, target: Cell) {
+        let idx = |(column, row): Cell| row * nav_grid.columns + column;
+
+        self.target = Some(target);
+        self.distances = vec![u32::MAX; nav_grid.costs.len()];
+        self.distances[idx(target)] = 0;
+
+        // Dijkstra outwards from the player
+        let mut open = BinaryHeap::from([Reverse((0, target))]);
+        while let Some(Reverse((distance, cell))) = open.pop() {
+            if distance > self.distances[idx(cell)] {
+                continue;
+            }
+
+            for (next, step_cost) in nav_grid.neighbours(cell) {
+                let next_distance = distance + step_cost;
+
+                if next_distance < self.distances[idx(next)] {
+                    self.distances[idx(next)] = next_distance;
+                    open.push(Reverse((next_distance, next)));
+                }
+            }
+        }
+    }
+
+    /// Center of the neighbouring cell that is closest to the player.
+    pub fn next_waypoint(&self, nav_grid: &NavGrid, position: Vec2) -> Option<Vec2> {
+        let cell = nav_grid.cell_at(position)?;
+        let distance = *self.distances.get(cell.1 * nav_grid.columns + cell.0)?;
+
+        nav_grid
+            .neighbours(cell)
+            .map(|(next, _)| (self.distances[next.1 * nav_grid.columns + next.0], next))
+            .filter(|&(next_distance, _)| next_distance < distance)
+            .min()
+            .map(|(_, next)| nav_grid.cell_center(next))
+    }
+}
+
+/// Path an enemy follows to a place it can't walk straight to. It is only
+/// searched again when the destination moves to another cell.
+#[derive(Component, Debug, Clone, Default)]
+pub struct NavPath {
+    destination: Option<Cell>,
+    // Reversed, the next waypoint is the last one
+    waypoints: Vec<Vec2>,
+}
+
+impl NavPath {
+    pub fn next_waypoint(
+        &mut self,
+        nav_grid: &NavGrid,
+        position: Vec2,
+        destination: Vec2,
+    ) -> Option<Vec2> {
+        let destination_cell = nav_grid.cell_at(destination);
+
+        if destination_cell != self.destination {
+            self.destination = destination_cell;
+            self.waypoints = nav_grid
+                .find_path(position, destination)
+                .unwrap_or_default();
+        }
+
+        while let Some(&waypoint) = self.waypoints.last() {
+            if waypoint.distance(position) > Tilemap::tile_size() / 2. || self.waypoints.len() == 1
+            {
+                break;
+            }
+            self.waypoints.pop();
+        }
+
+        self.waypoints.last().copied()
+    }
+}
+
+/// Builds the nav grid when the level changes and the flow field when the
+/// player steps on another cell.
+pub fn update_player_flow_field(
+    tilemap: Option<Res<Tilemap>>,
+    mut nav_grid: ResMut<NavGrid>,
+    mut flow_field: ResMut<PlayerFlowField>,
+    player_query: Query<&Transform, With<Player>>,
+    base_camera: Query<&Transform, (With<BaseCamera>, Without<Player>)>,
+) {
+    let Some(tilemap) = tilemap else {
+        return;
+    };
+
+    if tilemap.is_changed() {
+        *nav_grid = NavGrid::from_tilemap(&tilemap);
+        flow_field.target = None;
+    }
+
+    let Ok(player_transform) = player_query.get_single() else {
+        return;
+    };
+
+    let Ok(base_camera_transform) = base_camera.get_single() else {
+        return;
+    };
+
+    let player_position =
+        player_transform.translation.truncate() + base_camera_transform.translation.truncate();
+    let Some(player_cell) = nav_grid.cell_at(player_position) else {
+        return;
+    };
+
+    if flow_field.target != Some(player_cell) {
+        flow_field.compute(&nav_grid, player_cell);
+    }
+}
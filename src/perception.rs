@@ -0,0 +1,114 @@
+use crate::{prelude::*, BaseCamera, Enemy, Player, Tilemap};
+
+/// What an enemy knows about where the player is. Enemies only chase, and
+/// shoot at, the player while they can see him. Once they lose sight of him
+/// they look around the last place he was seen for a while.
+#[cfg_attr(not(feature = "web"), derive(Reflect, Component, Debug, Clone))]
+#[cfg_attr(not(feature = "web"), reflect(Component))]
+#[cfg_attr(feature = "web", derive(Component, Debug, Clone))]
+pub struct Perception {
+    pub sight_radius: f32,
+    pub can_see_player: bool,
+    pub last_known_position: Option<Vec2>,
+    // Until the enemy gives up looking for the player
+    pub search_timer: Timer,
+    // Set when the enemy gets hurt, so it finds the player even if it could
+    // not see him
+    pub alerted: bool,
+}
+
+impl Default for Perception {
+    fn default() -> Self {
+        Self {
+            sight_radius: ENEMY_SIGHT_RADIUS,
+            can_see_player: false,
+            last_known_position: None,
+            search_timer: Timer::from_seconds(ENEMY_SEARCH_SECONDS, TimerMode::Once),
+            alerted: false,
+        }
+    }
+}
+
+impl Perception {
+    /// Where the enemy is looking for the player, if it lost sight of him.
+    pub fn search_position(&self) -> Option<Vec2> {
+        if self.can_see_player {
+            return None;
+        }
+
+        self.last_known_position
+    }
+
+    /// Attacks from stealth, by an invisible player the enemy could not see,
+    /// deal more damage. Either way the enemy now knows where the attack came
+    /// from.
+    pub fn damage_taken(&mut self, damage: f32, player_stealthed: bool) -> f32 {
+        let was_unaware = !self.can_see_player;
+        self.alerted = true;
+
+        if player_stealthed && was_unaware {
+            damage * STEALTH_ATTACK_DAMAGE_MULTIPLIER
+        } else {
+            damage
+        }
+    }
+}
+
+/// The player is invisible. Enemies need to be much closer to see him.
+#[derive(Component, Clone)]
+pub struct Stealthed;
+
+pub fn update_enemy_perception(
+    time: Res<Time>,
+    player_query: Query<(&Transform, Option<&Stealthed>), With<Player>>,
+    base_camera: Query<&Transform, (With<BaseCamera>, Without<Player>)>,
+    mut enemies: Query<
+        (&Transform, &mut Perception),
+        (With<Enemy>, Without<Player>, Without<BaseCamera>),
+    >,
+    tilemap: Option<Res<Tilemap>>,
+) {
+    let Ok((player_transform, stealthed)) = player_query.get_single() else {
+        return;
+    };
+
+    let Ok(base_camera_transform) = base_camera.get_single() else {
+        return;
+    };
+
+    let player_position =
+        player_transform.translation.truncate() + base_camera_transform.translation.truncate();
+
+    for (enemy_transform, mut perception) in enemies.iter_mut() {
+        let sight_radius = if stealthed.is_some() {
+            perception.sight_radius * STEALTH_SIGHT_MULTIPLIER
+        } else {
+            perception.sight_radius
+        };
+        let enemy_position = enemy_transform.translation.truncate();
+        let distance = enemy_position.distance(player_position);
+        // Walls and obstacles hide the player
+        let in_line_of_sight = tilemap
+            .as_ref()
+            .is_none_or(|tilemap| tilemap.has_line_of_sight(enemy_position, player_position));
+
+        perception.can_see_player =
+            perception.alerted || (distance <= sight_radius && in_line_of_sight);
+        perception.alerted = false;
+
+        if perception.can_see_player {
+            perception.last_known_position = Some(player_position);
+            perception.search_timer.reset();
+            continue;
+        }
+
+        if perception.last_known_position.is_none() {
+            continue;
+        }
+
+        perception.search_timer.tick(time.delta());
+        if perception.search_timer.finished() {
+            perception.last_known_position = None;
+        }
+    }
+}
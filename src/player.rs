@@ -1,6 +1,8 @@
 use crate::{
-    animation::*, prelude::*, spawn_health_bar, sprites::Sprites, AmmoBundle, Armor,
-    CleanupWhenPlayerDies, Health, Mana, PlayerSpawned, Speed, SpritesResources, WeaponBundle,
+    animation::*, prelude::*, spawn_health_bar, sprites::Sprites, AmmoBundle, Armor, BaseStats,
+    CleanupWhenPlayerDies, Dash, DashCharges, DashDistance, Health, HealthRegen, LifeSteal, Luck,
+    Mana, ManaRegen, MaxHealth, MaxMana, PlayerSpawned, Speed, SpritesResources, StatModifiers,
+    WeaponBundle,
 };
 
 #[derive(Component, Debug, Clone)]
@@ -15,6 +17,19 @@ pub(crate) struct PlayerBundle {
     pub(crate) mana: Mana,
     pub(crate) armor: Armor,
     pub(crate) speed: Speed,
+    pub(crate) dash_distance: DashDistance,
+    pub(crate) dash_charges: DashCharges,
+    pub(crate) max_health: MaxHealth,
+    pub(crate) max_mana: MaxMana,
+    pub(crate) health_regen: HealthRegen,
+    pub(crate) mana_regen: ManaRegen,
+    pub(crate) life_steal: LifeSteal,
+    pub(crate) luck: Luck,
+    // Speed, armor and the dash stats are computed from these
+    pub(crate) base_stats: BaseStats,
+    pub(crate) stat_modifiers: StatModifiers,
+
+    pub(crate) dash: Dash,
 
     pub(crate) sprite: SpriteBundle,
     pub(crate) atlas: TextureAtlas,
@@ -55,6 +70,28 @@ impl PlayerBundle {
             mana: Mana(PLAYER_MANA),
             speed: Speed(PLAYER_MOVE_SPEED),
             armor: Armor(PLAYER_ARMOR),
+            dash_distance: DashDistance(PLAYER_DASH_DISTANCE),
+            dash_charges: DashCharges(PLAYER_DASH_CHARGES),
+            max_health: MaxHealth(PLAYER_HEALTH),
+            max_mana: MaxMana(PLAYER_MANA),
+            health_regen: HealthRegen(PLAYER_HEALTH_REGEN),
+            mana_regen: ManaRegen(PLAYER_MANA_REGEN),
+            life_steal: LifeSteal(PLAYER_LIFE_STEAL),
+            luck: Luck(PLAYER_LUCK),
+            base_stats: BaseStats {
+                speed: PLAYER_MOVE_SPEED,
+                armor: PLAYER_ARMOR,
+                dash_distance: PLAYER_DASH_DISTANCE,
+                dash_charges: PLAYER_DASH_CHARGES as f32,
+                max_health: PLAYER_HEALTH,
+                max_mana: PLAYER_MANA,
+                health_regen: PLAYER_HEALTH_REGEN,
+                mana_regen: PLAYER_MANA_REGEN,
+                life_steal: PLAYER_LIFE_STEAL,
+                luck: PLAYER_LUCK,
+            },
+            stat_modifiers: StatModifiers::default(),
+            dash: Dash::default(),
             sprite: SpriteBundle {
                 texture: asset_server.load(player_sprite.source),
                 transform: Transform {
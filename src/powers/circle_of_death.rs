@@ -0,0 +1,292 @@
+use std::{f32::consts::PI, time::Duration};
+
+use bevy::{
+    render::{
+        mesh::{Indices, PrimitiveTopology},
+        render_asset::RenderAssetUsages,
+    },
+    sprite::{MaterialMesh2dBundle, Mesh2dHandle},
+};
+
+use super::{
+    Power, PowerBehaviour, PowerInstance, PowerSpawnContext, PowerTickContext, RegisterPowerExt,
+};
+use crate::{
+    damage_enemy_from_ammo_or_power, prelude::*, sprites::SpriteInfo, CleanupWhenPlayerDies,
+    CollisionSet, Damage, Elite, Enemy, Health, SpritesResources,
+};
+
+// Number of pairs of triangles used to draw the wave
+const CIRCLE_OF_DEATH_MESH_SEGMENTS: u32 = 32;
+
+/// A wave that grows from the player until it covers the whole map, hitting
+/// once every enemy it reaches. With a cone targeting it only grows towards
+/// the cursor.
+pub struct CircleOfDeathPower;
+
+pub struct CircleOfDeathPowerPlugin;
+
+impl Plugin for CircleOfDeathPowerPlugin {
+    fn build(&self, app: &mut App) {
+        app.register_power(&CircleOfDeathPower)
+            .add_systems(FixedUpdate, burn_enemies_in_rings.in_set(CollisionSet));
+    }
+}
+
+/// Left around the player by the circle of death once it has the burning
+/// ring upgrade. It burns the enemies standing on it every so often.
+#[derive(Component, Clone)]
+pub struct BurningRing {
+    pub damage: f32,
+    pub tick_timer: Timer,
+    pub lifetime: Timer,
+}
+
+impl PowerBehaviour for CircleOfDeathPower {
+    fn power_type(&self) -> &'static str {
+        "circle_of_death"
+    }
+
+    fn config(&self) -> &'static PowerType {
+        &CIRCLE_OF_DEATH_POWER
+    }
+
+    fn upgrade_tree(&self) -> &'static [PowerUpgrade] {
+        CIRCLE_OF_DEATH_UPGRADES
+    }
+
+    fn sprite(&self, sprites: &SpritesResources) -> SpriteInfo<'static> {
+        sprites.0.circle_of_death.clone()
+    }
+
+    fn spawn(&self, ctx: &mut PowerSpawnContext, power: &Power, damage: f32) -> bool {
+        let wider_cone_degrees: f32 = power
+            .upgrades()
+            .map(|upgrade| match upgrade {
+                PowerUpgradeEnum::WiderCone(degrees) => degrees,
+                _ => 0.,
+            })
+            .sum();
+        let half_angle = match power.targeting {
+            PowerTargeting::Cone { angle_degrees } => {
+                ((angle_degrees + wider_cone_degrees) * DEGREES_TO_RADIANS / 2.).min(PI)
+            }
+            _ => PI,
+        };
+
+        if power
+            .upgrades()
+            .any(|upgrade| upgrade == PowerUpgradeEnum::BurningRing)
+        {
+            ctx.commands.spawn((
+                MaterialMesh2dBundle {
+                    mesh: Mesh2dHandle(ctx.meshes.add(get_circle_of_death_mesh(
+                        BURNING_RING_INNER_RADIUS,
+                        BURNING_RING_OUTER_RADIUS,
+                        PI,
+                    ))),
+                    material: ctx.materials.add(Color::srgba(1.0, 0.4, 0.0, 0.4)),
+                    transform: Transform::from_translation(ctx.player_translation),
+                    ..default()
+                },
+                BurningRing {
+                    damage: damage * BURNING_RING_DAMAGE_FRACTION,
+                    tick_timer: Timer::from_seconds(
+                        BURNING_RING_TICK_SECONDS,
+                        TimerMode::Repeating,
+                    ),
+                    lifetime: Timer::from_seconds(BURNING_RING_SECONDS, TimerMode::Once),
+                },
+                Name::new("BurningRing"),
+                BASE_LAYER,
+                CleanupWhenPlayerDies,
+            ));
+        }
+
+        let circle = Mesh2dHandle(ctx.meshes.add(get_circle_of_death_mesh(
+            CIRCLE_OF_DEATH_INNER_RADIUS,
+            CIRCLE_OF_DEATH_OUTER_RADIUS,
+            half_angle,
+        )));
+        let color = Color::srgba(255., 0., 0., 0.8);
+        let thickness = CIRCLE_OF_DEATH_OUTER_RADIUS - CIRCLE_OF_DEATH_INNER_RADIUS;
+
+        for _ in 1..=power.quantity {
+            // It goes away once the inner circle is out of the map
+            let mut instance = PowerInstance::new(
+                self.power_type(),
+                damage,
+                StoppingCondition::RadiusLimit(
+                    BACKGROUND_TEXTURE_SCALE * BACKGROUND_TEXTURE_RESOLUTION.x_px + thickness,
+                ),
+            );
+            instance.radius = CIRCLE_OF_DEATH_OUTER_RADIUS;
+            instance.direction = ctx.aim_direction.extend(0.);
+            instance.cone_half_angle = half_angle;
+            instance.timer = Timer::new(
+                Duration::from_millis(CIRCLE_OF_DEATH_GROWTH_INTERVAL_MILLIS),
+                TimerMode::Repeating,
+            );
+            instance.hit_each_enemy_once = true;
+
+            ctx.commands.spawn((
+                MaterialMesh2dBundle {
+                    mesh: circle.clone(),
+                    material: ctx.materials.add(color),
+                    transform: Transform {
+                        translation: ctx.player_translation,
+                        rotation: Quat::from_rotation_z(
+                            ctx.aim_direction.y.atan2(ctx.aim_direction.x),
+                        ),
+                        ..default()
+                    },
+                    ..default()
+                },
+                instance,
+                Name::new("CircleOfDeath"),
+                BASE_LAYER,
+                CleanupWhenPlayerDies,
+            ));
+        }
+
+        true
+    }
+
+    fn tick(
+        &self,
+        ctx: &mut PowerTickContext,
+        instance: &mut PowerInstance,
+        _transform: &mut Transform,
+        mesh: Option<&mut Mesh2dHandle>,
+    ) {
+        instance.timer.tick(ctx.delta);
+        if !instance.timer.just_finished() {
+            return;
+        }
+
+        let thickness = CIRCLE_OF_DEATH_OUTER_RADIUS - CIRCLE_OF_DEATH_INNER_RADIUS;
+        let new_outer_radius = instance.radius * CIRCLE_OF_DEATH_GROWTH + instance.radius;
+        let new_inner_radius = new_outer_radius - thickness;
+
+        instance.radius = new_outer_radius;
+
+        if let Some(mesh) = mesh {
+            *mesh = Mesh2dHandle(ctx.meshes.add(get_circle_of_death_mesh(
+                new_inner_radius,
+                new_outer_radius,
+                instance.cone_half_angle,
+            )));
+        }
+    }
+
+    fn collides(
+        &self,
+        instance: &PowerInstance,
+        transform: &Transform,
+        enemy_collider: &Aabb2d,
+    ) -> bool {
+        let origin = transform.translation.truncate();
+        let enemy_center = (enemy_collider.min + enemy_collider.max) / 2.;
+        let to_enemy = enemy_center - origin;
+
+        // The wave has not reached the enemy yet
+        if to_enemy.length() > instance.radius {
+            return false;
+        }
+
+        if instance.cone_half_angle >= PI {
+            return true;
+        }
+
+        to_enemy.angle_between(instance.direction.truncate()).abs() <= instance.cone_half_angle
+    }
+}
+
+pub fn burn_enemies_in_rings(
+    mut commands: Commands,
+    time: Res<Time>,
+    mut rings: Query<(Entity, &Transform, &mut BurningRing)>,
+    mut enemies: Query<
+        (
+            Entity,
+            &Transform,
+            &mut Health,
+            &Damage,
+            &Enemy,
+            Option<&mut Elite>,
+        ),
+        Without<BurningRing>,
+    >,
+) {
+    for (ring_entity, ring_transform, mut ring) in rings.iter_mut() {
+        ring.lifetime.tick(time.delta());
+        if ring.lifetime.finished() {
+            commands.entity(ring_entity).despawn();
+            continue;
+        }
+
+        ring.tick_timer.tick(time.delta());
+        if !ring.tick_timer.just_finished() {
+            continue;
+        }
+
+        let ring_position = ring_transform.translation.truncate();
+
+        for (enemy_entity, enemy_transform, mut enemy_health, enemy_damage, enemy, mut elite) in
+            enemies.iter_mut()
+        {
+            let distance = enemy_transform
+                .translation
+                .truncate()
+                .distance(ring_position);
+            if !(BURNING_RING_INNER_RADIUS..=BURNING_RING_OUTER_RADIUS).contains(&distance) {
+                continue;
+            }
+
+            damage_enemy_from_ammo_or_power(
+                &mut commands,
+                None,
+                enemy_entity,
+                &mut enemy_health,
+                ring.damage,
+                enemy_damage,
+                enemy.max_health,
+                elite.as_deref_mut(),
+            );
+        }
+    }
+}
+
+/// Ring, or part of a ring, centered on the x axis.
+fn get_circle_of_death_mesh(inner_radius: f32, outer_radius: f32, half_angle: f32) -> Mesh {
+    let mut positions = vec![];
+    let mut indices = vec![];
+
+    for segment in 0..=CIRCLE_OF_DEATH_MESH_SEGMENTS {
+        let angle =
+            -half_angle + 2. * half_angle * segment as f32 / CIRCLE_OF_DEATH_MESH_SEGMENTS as f32;
+        let direction = Vec2::from_angle(angle);
+
+        positions.push((direction * inner_radius).extend(0.).to_array());
+        positions.push((direction * outer_radius).extend(0.).to_array());
+
+        if segment < CIRCLE_OF_DEATH_MESH_SEGMENTS {
+            let inner = segment * 2;
+            indices.extend_from_slice(&[inner, inner + 1, inner + 3, inner, inner + 3, inner + 2]);
+        }
+    }
+
+    let number_of_vertices = positions.len();
+
+    Mesh::new(
+        PrimitiveTopology::TriangleList,
+        RenderAssetUsages::default(),
+    )
+    .with_inserted_attribute(Mesh::ATTRIBUTE_POSITION, positions)
+    .with_inserted_attribute(
+        Mesh::ATTRIBUTE_NORMAL,
+        vec![[0., 0., 1.]; number_of_vertices],
+    )
+    .with_inserted_attribute(Mesh::ATTRIBUTE_UV_0, vec![[0., 0.]; number_of_vertices])
+    .with_inserted_indices(Indices::U32(indices))
+}
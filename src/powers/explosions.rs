@@ -0,0 +1,194 @@
+use std::f32::consts::TAU;
+
+use rand::Rng;
+
+use super::{Power, PowerBehaviour, PowerInstance, PowerSpawnContext, RegisterPowerExt};
+use crate::{
+    prelude::*, sprites::SpriteInfo, CleanupWhenPlayerDies, Enemy, ScreenShake, SpritesResources,
+};
+
+/// Explosions on random places of the targeted area. Each one goes away after
+/// hitting an enemy.
+pub struct ExplosionsPower;
+
+pub struct ExplosionsPowerPlugin;
+
+impl Plugin for ExplosionsPowerPlugin {
+    fn build(&self, app: &mut App) {
+        app.register_power(&ExplosionsPower)
+            .observe(on_explosion_chained);
+    }
+}
+
+/// An explosion with chains left hit an enemy, so a new one is spawned on the
+/// closest enemy that was not hit yet.
+#[derive(Event)]
+pub struct ExplosionChained {
+    pub position: Vec2,
+    pub damage: f32,
+    pub chains_left: u32,
+    pub enemies_hit: Vec<Entity>,
+}
+
+impl PowerBehaviour for ExplosionsPower {
+    fn power_type(&self) -> &'static str {
+        "explosions"
+    }
+
+    fn config(&self) -> &'static PowerType {
+        &EXPLOSIONS_POWER
+    }
+
+    fn upgrade_tree(&self) -> &'static [PowerUpgrade] {
+        EXPLOSIONS_UPGRADES
+    }
+
+    fn sprite(&self, sprites: &SpritesResources) -> SpriteInfo<'static> {
+        sprites.0.mine_bomb.clone()
+    }
+
+    fn spawn(&self, ctx: &mut PowerSpawnContext, power: &Power, damage: f32) -> bool {
+        let power_sprite = self.sprite(ctx.sprites);
+        let power_animation = power_sprite.animation.unwrap();
+        let texture_atlas_layout = ctx.texture_atlas_layout.add(power_sprite.layout);
+        let texture = ctx.asset_server.load(power_sprite.source);
+
+        let radius = match power.targeting {
+            PowerTargeting::GroundTarget { radius } => radius,
+            // The whole map
+            _ => BACKGROUND_TEXTURE_RESOLUTION.x_px * BACKGROUND_TEXTURE_SCALE / 2.,
+        };
+        let chains = power
+            .upgrades()
+            .map(|upgrade| match upgrade {
+                PowerUpgradeEnum::ChainExplosions(chains) => chains,
+                _ => 0,
+            })
+            .sum();
+        let mut rng = rand::thread_rng();
+
+        for _ in 1..=power.quantity {
+            for _ in 1..=power.max_value {
+                // sqrt so they are evenly spread on the area
+                let distance = radius * rng.gen::<f32>().sqrt();
+                let offset = Vec2::from_angle(rng.gen_range(0.0..TAU)) * distance;
+
+                let mut instance =
+                    PowerInstance::new(self.power_type(), damage, StoppingCondition::Hits(1));
+                instance.chains_left = chains;
+                // So the chained explosions know which enemy was hit
+                instance.hit_each_enemy_once = true;
+
+                ctx.commands.spawn((
+                    SpriteBundle {
+                        texture: texture.clone(),
+                        transform: Transform::from_translation(
+                            (ctx.target + offset).extend(CHAR_Z_INDEX),
+                        ),
+                        ..default()
+                    },
+                    TextureAtlas {
+                        layout: texture_atlas_layout.clone(),
+                        index: power_animation.indices.first,
+                    },
+                    power_animation.indices.clone(),
+                    power_animation.timer.clone(),
+                    instance,
+                    Name::new("Explosion"),
+                    BASE_LAYER,
+                    CleanupWhenPlayerDies,
+                ));
+            }
+        }
+
+        true
+    }
+
+    fn collides(
+        &self,
+        _instance: &PowerInstance,
+        transform: &Transform,
+        enemy_collider: &Aabb2d,
+    ) -> bool {
+        let power_collider = Aabb2d::new(
+            transform.translation.truncate(),
+            Vec2::new(
+                (POWER_SPRITE_SIZE / 2) as f32,
+                (POWER_SPRITE_SIZE / 2) as f32,
+            ),
+        );
+
+        power_collider.intersects(enemy_collider)
+    }
+
+    fn expire(&self, commands: &mut Commands, instance: &PowerInstance, transform: &Transform) {
+        if instance.chains_left == 0 {
+            return;
+        }
+
+        commands.trigger(ExplosionChained {
+            position: transform.translation.truncate(),
+            damage: instance.damage,
+            chains_left: instance.chains_left - 1,
+            enemies_hit: instance.enemies_hit.clone(),
+        });
+    }
+}
+
+pub fn on_explosion_chained(
+    trigger: Trigger<ExplosionChained>,
+    mut commands: Commands,
+    mut texture_atlas_layout: ResMut<Assets<TextureAtlasLayout>>,
+    sprites: Res<SpritesResources>,
+    asset_server: Res<AssetServer>,
+    enemies: Query<(Entity, &Transform), With<Enemy>>,
+) {
+    let event = trigger.event();
+
+    let closest_enemy = enemies
+        .iter()
+        .filter(|(enemy_entity, _)| !event.enemies_hit.contains(enemy_entity))
+        .map(|(_, enemy_transform)| enemy_transform.translation.truncate())
+        .filter(|enemy_position| enemy_position.distance(event.position) <= CHAIN_EXPLOSION_RANGE)
+        .min_by(|a, b| {
+            a.distance_squared(event.position)
+                .total_cmp(&b.distance_squared(event.position))
+        });
+    let Some(enemy_position) = closest_enemy else {
+        return;
+    };
+
+    let power_sprite = ExplosionsPower.sprite(&sprites);
+    let power_animation = power_sprite.animation.unwrap();
+
+    let mut instance = PowerInstance::new(
+        ExplosionsPower.power_type(),
+        event.damage,
+        StoppingCondition::Hits(1),
+    );
+    instance.chains_left = event.chains_left;
+    instance.hit_each_enemy_once = true;
+    instance.enemies_hit = event.enemies_hit.clone();
+
+    commands.spawn((
+        SpriteBundle {
+            texture: asset_server.load(power_sprite.source),
+            transform: Transform::from_translation(enemy_position.extend(CHAR_Z_INDEX)),
+            ..default()
+        },
+        TextureAtlas {
+            layout: texture_atlas_layout.add(power_sprite.layout),
+            index: power_animation.indices.first,
+        },
+        power_animation.indices,
+        power_animation.timer,
+        instance,
+        Name::new("Explosion"),
+        BASE_LAYER,
+        CleanupWhenPlayerDies,
+    ));
+
+    commands.trigger(ScreenShake {
+        trauma: CAMERA_EXPLOSION_TRAUMA,
+    });
+}
@@ -0,0 +1,164 @@
+use bevy::sprite::{MaterialMesh2dBundle, Mesh2dHandle};
+
+use super::{
+    Power, PowerBehaviour, PowerInstance, PowerSpawnContext, PowerTickContext, RegisterPowerExt,
+};
+use crate::{
+    prelude::*, sprites::SpriteInfo, util::oriented_rectangle_intersects_aabb,
+    CleanupWhenPlayerDies, SpritesResources,
+};
+
+/// A laser that crosses the map towards its target, bouncing on its edges.
+pub struct LaserPower;
+
+pub struct LaserPowerPlugin;
+
+impl Plugin for LaserPowerPlugin {
+    fn build(&self, app: &mut App) {
+        app.register_power(&LaserPower);
+    }
+}
+
+impl PowerBehaviour for LaserPower {
+    fn power_type(&self) -> &'static str {
+        "laser"
+    }
+
+    fn config(&self) -> &'static PowerType {
+        &LASER_POWER
+    }
+
+    fn upgrade_tree(&self) -> &'static [PowerUpgrade] {
+        LASER_UPGRADES
+    }
+
+    fn sprite(&self, sprites: &SpritesResources) -> SpriteInfo<'static> {
+        sprites.0.laser.clone()
+    }
+
+    fn spawn(&self, ctx: &mut PowerSpawnContext, power: &Power, damage: f32) -> bool {
+        let rectangle = Mesh2dHandle(
+            ctx.meshes
+                .add(Rectangle::new(LASER_POWER_WIDTH, LASER_POWER_HEIGHT)),
+        );
+        let color = Color::srgba(48., 255., 48., 0.8);
+
+        let extra_beams: u32 = power
+            .upgrades()
+            .map(|upgrade| match upgrade {
+                PowerUpgradeEnum::ExtraBeams(beams) => beams,
+                _ => 0,
+            })
+            .sum();
+        let pierce = power
+            .upgrades()
+            .any(|upgrade| upgrade == PowerUpgradeEnum::Pierce);
+
+        for _ in 1..=power.quantity {
+            // The extra beams alternate on both sides of the aimed one
+            for beam in 0..=extra_beams {
+                let side = if beam % 2 == 0 { 1. } else { -1. };
+                let beam_angle = side
+                    * beam.div_ceil(2) as f32
+                    * LASER_EXTRA_BEAM_SPREAD_DEGREES
+                    * DEGREES_TO_RADIANS;
+
+                let mut instance = PowerInstance::new(
+                    self.power_type(),
+                    damage,
+                    StoppingCondition::ScreenBounces(power.max_value),
+                );
+                instance.direction = Vec2::from_angle(beam_angle)
+                    .rotate(ctx.aim_direction)
+                    .extend(0.);
+                instance.pierce = pierce;
+
+                ctx.commands.spawn((
+                    MaterialMesh2dBundle {
+                        mesh: rectangle.clone(),
+                        material: ctx.materials.add(color),
+                        transform: Transform {
+                            translation: ctx.player_translation,
+                            scale: Vec3::ONE,
+                            rotation: get_laser_rotation(instance.direction),
+                        },
+                        ..default()
+                    },
+                    instance,
+                    Name::new("Laser"),
+                    BASE_LAYER,
+                    CleanupWhenPlayerDies,
+                ));
+            }
+        }
+
+        true
+    }
+
+    fn tick(
+        &self,
+        ctx: &mut PowerTickContext,
+        instance: &mut PowerInstance,
+        transform: &mut Transform,
+        _mesh: Option<&mut Mesh2dHandle>,
+    ) {
+        let delta_seconds = ctx.delta.as_secs_f32();
+
+        let mut new_translation_x =
+            transform.translation.x + instance.direction.x * LASER_MOVE_SPEED * delta_seconds;
+        let mut new_translation_y =
+            transform.translation.y + instance.direction.y * LASER_MOVE_SPEED * delta_seconds;
+
+        let off_screen_x = !(-BACKGROUND_TEXTURE_RESOLUTION.x_px
+            ..=BACKGROUND_TEXTURE_RESOLUTION.x_px)
+            .contains(&new_translation_x);
+        let off_screen_y = !(-BACKGROUND_TEXTURE_RESOLUTION.y_px
+            ..=BACKGROUND_TEXTURE_RESOLUTION.y_px)
+            .contains(&new_translation_y);
+
+        if off_screen_x {
+            // invert direction
+            instance.direction.x *= -1.;
+            new_translation_x =
+                transform.translation.x + instance.direction.x * POWER_MOVE_SPEED * delta_seconds;
+
+            transform.rotation = get_laser_rotation(instance.direction);
+
+            instance.bounces += 1;
+        }
+        if off_screen_y {
+            // invert direction
+            instance.direction.y *= -1.;
+            new_translation_y =
+                transform.translation.y + instance.direction.y * POWER_MOVE_SPEED * delta_seconds;
+
+            transform.rotation = get_laser_rotation(instance.direction);
+
+            instance.bounces += 1;
+        }
+
+        transform.translation.x = new_translation_x;
+        transform.translation.y = new_translation_y;
+    }
+
+    fn collides(
+        &self,
+        _instance: &PowerInstance,
+        transform: &Transform,
+        enemy_collider: &Aabb2d,
+    ) -> bool {
+        let (angle, _, _) = transform.rotation.to_euler(EulerRot::ZYX);
+
+        oriented_rectangle_intersects_aabb(
+            transform.translation.truncate(),
+            Vec2::new(LASER_POWER_WIDTH / 2., LASER_POWER_HEIGHT / 2.),
+            angle,
+            enemy_collider,
+        )
+    }
+}
+
+/// The laser is drawn along its direction.
+fn get_laser_rotation(direction: Vec3) -> Quat {
+    Quat::from_rotation_z(direction.y.atan2(direction.x))
+}
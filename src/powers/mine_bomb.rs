@@ -0,0 +1,274 @@
+use std::f32::consts::TAU;
+
+use bevy::{
+    math::bounding::BoundingCircle,
+    sprite::{MaterialMesh2dBundle, Mesh2dHandle},
+};
+
+use super::{
+    Power, PowerBehaviour, PowerInstance, PowerSpawnContext, PowerTickContext, RegisterPowerExt,
+};
+use crate::{
+    damage_enemy_from_ammo_or_power, prelude::*, sprites::SpriteInfo, Boss, CleanupWhenPlayerDies,
+    Damage, Elite, Enemy, Health, ScreenShake, SpritesResources, Tilemap, TimeBasedSet,
+};
+
+/// Proximity mines dropped behind the player. Once armed, the first enemy
+/// that gets close makes the mine explode, hurting and pushing away every
+/// enemy around it.
+pub struct MineBombPower;
+
+pub struct MineBombPowerPlugin;
+
+impl Plugin for MineBombPowerPlugin {
+    fn build(&self, app: &mut App) {
+        app.register_power(&MineBombPower)
+            .add_systems(FixedUpdate, despawn_mine_explosions.in_set(TimeBasedSet))
+            .observe(on_mine_exploded);
+    }
+}
+
+#[derive(Event)]
+pub struct MineExploded {
+    pub position: Vec2,
+    pub damage: f32,
+    pub radius: f32,
+}
+
+#[derive(Component, Clone)]
+pub struct MineExplosion {
+    pub timer: Timer,
+}
+
+impl PowerBehaviour for MineBombPower {
+    fn power_type(&self) -> &'static str {
+        "mine_bomb"
+    }
+
+    fn config(&self) -> &'static PowerType {
+        &MINE_BOMB_POWER
+    }
+
+    fn upgrade_tree(&self) -> &'static [PowerUpgrade] {
+        MINE_BOMB_UPGRADES
+    }
+
+    fn sprite(&self, sprites: &SpritesResources) -> SpriteInfo<'static> {
+        sprites.0.mine_bomb.clone()
+    }
+
+    fn spawn(&self, ctx: &mut PowerSpawnContext, power: &Power, damage: f32) -> bool {
+        let free_slots = MINE_BOMB_MAX_ACTIVE.saturating_sub(ctx.active_instances);
+        let number_of_mines = ((power.max_value * power.quantity) as usize).min(free_slots);
+        if number_of_mines == 0 {
+            return false;
+        }
+
+        let power_sprite = self.sprite(ctx.sprites);
+        let power_animation = power_sprite.animation.unwrap();
+        let texture_atlas_layout = ctx.texture_atlas_layout.add(power_sprite.layout);
+        let texture = ctx.asset_server.load(power_sprite.source);
+
+        let behind = -ctx
+            .settings
+            .key_bindings
+            .movement_direction(ctx.keyboard_input)
+            .normalize_or_zero();
+        let explosion_radius = power.upgrades().fold(
+            MINE_BOMB_EXPLOSION_RADIUS,
+            |radius, upgrade| match upgrade {
+                PowerUpgradeEnum::BiggerArea(multiplier) => radius * multiplier,
+                _ => radius,
+            },
+        );
+
+        for idx in 0..number_of_mines {
+            // A line behind the player, or a circle around them if they are
+            // standing still
+            let offset = if behind == Vec2::ZERO {
+                let angle = TAU * idx as f32 / number_of_mines as f32;
+                Vec2::from_angle(angle) * MINE_BOMB_DROP_DISTANCE
+            } else {
+                behind * MINE_BOMB_DROP_DISTANCE * (idx + 1) as f32
+            };
+
+            let mut instance =
+                PowerInstance::new(self.power_type(), damage, StoppingCondition::Hits(1));
+            instance.timer = Timer::from_seconds(MINE_BOMB_ARMING_SECONDS, TimerMode::Once);
+            instance.radius = explosion_radius;
+
+            ctx.commands.spawn((
+                SpriteBundle {
+                    texture: texture.clone(),
+                    transform: Transform {
+                        translation: ctx.player_translation + offset.extend(0.),
+                        scale: Vec3::splat(MINE_BOMB_ARMING_SCALE),
+                        ..default()
+                    },
+                    ..default()
+                },
+                TextureAtlas {
+                    layout: texture_atlas_layout.clone(),
+                    index: power_animation.indices.first,
+                },
+                power_animation.indices.clone(),
+                power_animation.timer.clone(),
+                instance,
+                Name::new("MineBomb"),
+                BASE_LAYER,
+                CleanupWhenPlayerDies,
+            ));
+        }
+
+        true
+    }
+
+    fn tick(
+        &self,
+        ctx: &mut PowerTickContext,
+        instance: &mut PowerInstance,
+        transform: &mut Transform,
+        _mesh: Option<&mut Mesh2dHandle>,
+    ) {
+        instance.timer.tick(ctx.delta);
+
+        // The mine grows to its full size once it is armed
+        if instance.timer.just_finished() {
+            transform.scale = Vec3::ONE;
+        }
+    }
+
+    fn collides(
+        &self,
+        instance: &PowerInstance,
+        transform: &Transform,
+        enemy_collider: &Aabb2d,
+    ) -> bool {
+        if !instance.timer.finished() {
+            return false;
+        }
+
+        let trigger_collider =
+            BoundingCircle::new(transform.translation.truncate(), MINE_BOMB_TRIGGER_RADIUS);
+
+        trigger_collider.intersects(enemy_collider)
+    }
+
+    // The damage is dealt by the explosion, to every enemy around the mine
+    fn on_hit(&self, _instance: &PowerInstance) -> Option<f32> {
+        None
+    }
+
+    fn expire(&self, commands: &mut Commands, instance: &PowerInstance, transform: &Transform) {
+        commands.trigger(MineExploded {
+            position: transform.translation.truncate(),
+            damage: instance.damage,
+            radius: instance.radius,
+        });
+    }
+}
+
+pub fn on_mine_exploded(
+    trigger: Trigger<MineExploded>,
+    mut commands: Commands,
+    mut meshes: ResMut<Assets<Mesh>>,
+    mut materials: ResMut<Assets<ColorMaterial>>,
+    tilemap: Option<Res<Tilemap>>,
+    mut enemies: Query<
+        (
+            Entity,
+            &mut Transform,
+            &mut Health,
+            &Damage,
+            &Enemy,
+            Option<&mut Elite>,
+            Option<&Boss>,
+        ),
+        With<Enemy>,
+    >,
+) {
+    let MineExploded {
+        position,
+        damage,
+        radius,
+    } = *trigger.event();
+
+    commands.trigger(ScreenShake {
+        trauma: CAMERA_EXPLOSION_TRAUMA,
+    });
+
+    for (
+        enemy_entity,
+        mut enemy_transform,
+        mut enemy_health,
+        enemy_damage,
+        enemy,
+        mut elite,
+        boss,
+    ) in enemies.iter_mut()
+    {
+        let enemy_position = enemy_transform.translation.truncate();
+        let distance = enemy_position.distance(position);
+        if distance > radius {
+            continue;
+        }
+
+        // Bosses are too heavy to be pushed
+        if boss.is_none() {
+            let push = 1. - distance / radius;
+            let direction = (enemy_position - position).normalize_or_zero();
+            let mut pushed_position =
+                enemy_position + direction * MINE_BOMB_KNOCKBACK_DISTANCE * push;
+
+            // They are stopped by the solid tiles, like when they walk
+            if let Some(tilemap) = &tilemap {
+                pushed_position = tilemap.resolve_movement(
+                    enemy_position,
+                    pushed_position,
+                    Vec2::new(enemy.width_collider / 2., enemy.height_collider / 2.),
+                );
+            }
+
+            enemy_transform.translation.x = pushed_position.x;
+            enemy_transform.translation.y = pushed_position.y;
+        }
+
+        damage_enemy_from_ammo_or_power(
+            &mut commands,
+            None,
+            enemy_entity,
+            &mut enemy_health,
+            damage,
+            enemy_damage,
+            enemy.max_health,
+            elite.as_deref_mut(),
+        );
+    }
+
+    commands.spawn((
+        MaterialMesh2dBundle {
+            mesh: Mesh2dHandle(meshes.add(Circle::new(radius))),
+            material: materials.add(Color::srgba(1.0, 0.6, 0.0, 0.5)),
+            transform: Transform::from_translation(position.extend(CHAR_Z_INDEX)),
+            ..default()
+        },
+        MineExplosion {
+            timer: Timer::from_seconds(MINE_BOMB_EXPLOSION_SECONDS, TimerMode::Once),
+        },
+        BASE_LAYER,
+        CleanupWhenPlayerDies,
+    ));
+}
+
+pub fn despawn_mine_explosions(
+    mut commands: Commands,
+    time: Res<Time>,
+    mut explosions: Query<(Entity, &mut MineExplosion)>,
+) {
+    for (explosion_entity, mut explosion) in explosions.iter_mut() {
+        explosion.timer.tick(time.delta());
+        if explosion.timer.finished() {
+            commands.entity(explosion_entity).despawn();
+        }
+    }
+}
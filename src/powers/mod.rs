@@ -0,0 +1,770 @@
+mod circle_of_death;
+mod explosions;
+mod laser;
+mod mine_bomb;
+
+pub use circle_of_death::*;
+pub use explosions::*;
+pub use laser::*;
+pub use mine_bomb::*;
+
+use crate::{
+    prelude::*, sprites::SpriteInfo, util::get_cursor_world_position, AnimationIndices,
+    AnimationTimer, BaseCamera, CleanupWhenPlayerDies, Damage, Direction, KeyActionEnum, Player,
+    PowerUpgradeChoiceUI, PowerUpgradeChosen, Settings, SpritesResources,
+};
+use bevy::sprite::{MaterialMesh2dBundle, Mesh2dHandle};
+
+/// Adds every power the player can get. Each power has its own plugin,
+/// which registers its `PowerBehaviour` and the systems and observers only
+/// that power needs.
+pub struct PowersPlugin;
+
+impl Plugin for PowersPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_plugins((
+            LaserPowerPlugin,
+            CircleOfDeathPowerPlugin,
+            ExplosionsPowerPlugin,
+            MineBombPowerPlugin,
+        ));
+    }
+}
+
+/// Powers added by the power plugins, in the order they are found on each
+/// level.
+#[derive(Resource, Default)]
+pub struct PowerRegistry(Vec<&'static dyn PowerBehaviour>);
+
+impl PowerRegistry {
+    pub fn get(&self, power_type: &str) -> Option<&'static dyn PowerBehaviour> {
+        self.0
+            .iter()
+            .find(|behaviour| behaviour.power_type() == power_type)
+            .copied()
+    }
+
+    /// Power found on the given level. They start over once every power
+    /// was found.
+    pub fn get_by_level(&self, level: u32) -> Option<&'static dyn PowerBehaviour> {
+        if self.0.is_empty() {
+            return None;
+        }
+
+        self.0
+            .get((level.max(1) - 1) as usize % self.0.len())
+            .copied()
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = &'static dyn PowerBehaviour> + '_ {
+        self.0.iter().copied()
+    }
+}
+
+pub trait RegisterPowerExt {
+    fn register_power(&mut self, behaviour: &'static dyn PowerBehaviour) -> &mut Self;
+}
+
+impl RegisterPowerExt for App {
+    fn register_power(&mut self, behaviour: &'static dyn PowerBehaviour) -> &mut Self {
+        self.init_resource::<PowerRegistry>();
+        self.world_mut()
+            .resource_mut::<PowerRegistry>()
+            .0
+            .push(behaviour);
+        self
+    }
+}
+
+/// Everything the game needs to know about a power. The spawned instances of
+/// the power are entities with a `PowerInstance`, and the hooks are called
+/// for them by `tick_powers`, `check_for_power_collisions_with_enemy` and
+/// `expire_powers`.
+pub trait PowerBehaviour: Sync {
+    /// Name used to refer to the power, e.g. on the key bindings.
+    fn power_type(&self) -> &'static str;
+
+    /// Damage, cooldown, targeting... of the power when it is found.
+    fn config(&self) -> &'static PowerType;
+
+    /// Choices offered when the power is found again.
+    fn upgrade_tree(&self) -> &'static [PowerUpgrade];
+
+    fn sprite(&self, sprites: &SpritesResources) -> SpriteInfo<'static>;
+
+    /// Spawns the instances of the power when the player casts it. Returns
+    /// false when nothing was spawned, so the mana is not spent.
+    fn spawn(&self, ctx: &mut PowerSpawnContext, power: &Power, damage: f32) -> bool;
+
+    /// Called every frame for each instance of the power.
+    fn tick(
+        &self,
+        _ctx: &mut PowerTickContext,
+        _instance: &mut PowerInstance,
+        _transform: &mut Transform,
+        _mesh: Option<&mut Mesh2dHandle>,
+    ) {
+    }
+
+    /// Whether the instance hits the enemy with the given collider.
+    fn collides(
+        &self,
+        instance: &PowerInstance,
+        transform: &Transform,
+        enemy_collider: &Aabb2d,
+    ) -> bool;
+
+    /// Called every time the instance hits an enemy. Returns the damage
+    /// dealt to it, `None` for the powers that deal their damage some other
+    /// way.
+    fn on_hit(&self, instance: &PowerInstance) -> Option<f32> {
+        Some(instance.damage)
+    }
+
+    /// Called right before an instance that met its stopping condition is
+    /// despawned.
+    fn expire(&self, _commands: &mut Commands, _instance: &PowerInstance, _transform: &Transform) {}
+}
+
+pub struct PowerSpawnContext<'a, 'w, 's> {
+    pub commands: &'a mut Commands<'w, 's>,
+    pub meshes: &'a mut Assets<Mesh>,
+    pub materials: &'a mut Assets<ColorMaterial>,
+    pub texture_atlas_layout: &'a mut Assets<TextureAtlasLayout>,
+    pub sprites: &'a SpritesResources,
+    pub asset_server: &'a AssetServer,
+    pub keyboard_input: &'a ButtonInput<KeyCode>,
+    pub settings: &'a Settings,
+    // Position of the player on the map
+    pub player_translation: Vec3,
+    // Position on the map chosen by the targeting of the power
+    pub target: Vec2,
+    // Unit vector from the player to the target
+    pub aim_direction: Vec2,
+    // Instances of this power that are still on the map
+    pub active_instances: usize,
+}
+
+pub struct PowerTickContext<'a> {
+    pub delta: std::time::Duration,
+    pub meshes: &'a mut Assets<Mesh>,
+}
+
+/// One spawned instance of a power, like a single explosion or laser.
+#[cfg_attr(not(feature = "web"), derive(Reflect, Component, Debug, Clone))]
+#[cfg_attr(not(feature = "web"), reflect(Component))]
+#[cfg_attr(feature = "web", derive(Component, Debug, Clone))]
+pub struct PowerInstance {
+    pub power_type: &'static str,
+    pub damage: f32,
+    pub stopping_condition: StoppingCondition,
+    pub direction: Vec3,
+    // Used by the powers that grow, like the circle of death
+    pub radius: f32,
+    // Used by the powers aimed as a cone. PI is a full circle.
+    pub cone_half_angle: f32,
+    // Used by the powers that do something every so often
+    pub timer: Timer,
+    pub hits: u32,
+    pub bounces: u32,
+    // When set, each enemy can only be hit once by this instance.
+    // Otherwise it hits the enemy every frame they are touching.
+    pub hit_each_enemy_once: bool,
+    pub enemies_hit: Vec<Entity>,
+    // Goes through the shields of the elites
+    pub pierce: bool,
+    // Times it jumps to another enemy after hitting one
+    pub chains_left: u32,
+}
+
+impl PowerInstance {
+    pub fn new(
+        power_type: &'static str,
+        damage: f32,
+        stopping_condition: StoppingCondition,
+    ) -> Self {
+        Self {
+            power_type,
+            damage,
+            stopping_condition,
+            direction: Vec3::ZERO,
+            radius: 0.,
+            cone_half_angle: std::f32::consts::PI,
+            timer: Timer::default(),
+            hits: 0,
+            bounces: 0,
+            hit_each_enemy_once: false,
+            enemies_hit: vec![],
+            pierce: false,
+            chains_left: 0,
+        }
+    }
+
+    pub fn has_expired(&self) -> bool {
+        match self.stopping_condition {
+            StoppingCondition::Hits(max_hits) => self.hits >= max_hits,
+            StoppingCondition::ScreenBounces(max_bounces) => self.bounces > max_bounces,
+            StoppingCondition::RadiusLimit(limit) => self.radius > limit,
+        }
+    }
+}
+
+#[cfg_attr(not(feature = "web"), derive(Reflect, Component, Debug, Clone))]
+#[cfg_attr(not(feature = "web"), reflect(Component))]
+#[cfg_attr(feature = "web", derive(Component, Debug, Clone))]
+pub struct Power {
+    // TODO: maybe remove this
+    pub origin: Vec2,
+    pub power_type: &'static str,
+    pub value: u32,
+    pub max_value: u32,
+    pub mana_needed: f32,
+    pub key_action: KeyActionEnum,
+    // How many of them should be spawned
+    pub quantity: u32,
+    // Gives back one charge every time it finishes
+    pub cooldown: Timer,
+    pub charges: u32,
+    pub max_charges: u32,
+    // Set when the key is pressed, and cleared once the power is cast or
+    // the timer finishes
+    pub buffered_press: Option<Timer>,
+    pub targeting: PowerTargeting,
+    #[cfg_attr(not(feature = "web"), reflect(ignore))]
+    pub upgrade_tree: &'static [PowerUpgrade],
+    // Starts at 1 and goes up every time the power is found again
+    pub level: u32,
+    // Index of the choice taken on each step of the upgrade tree
+    pub upgrade_path: Vec<usize>,
+}
+
+impl Power {
+    pub fn is_ready(&self) -> bool {
+        self.charges > 0
+    }
+
+    /// How much of the cooldown of the next charge has passed. It is 1.0
+    /// when all the charges are available.
+    pub fn cooldown_fraction(&self) -> f32 {
+        if self.charges >= self.max_charges {
+            return 1.0;
+        }
+
+        self.cooldown.fraction()
+    }
+
+    /// Upgrades chosen so far, starting from the root of the tree.
+    pub fn chosen_upgrades(&self) -> Vec<&'static PowerUpgrade> {
+        let mut choices = self.upgrade_tree;
+        let mut chosen = vec![];
+
+        for &choice in &self.upgrade_path {
+            let Some(upgrade) = choices.get(choice) else {
+                break;
+            };
+            chosen.push(upgrade);
+            choices = upgrade.next;
+        }
+
+        chosen
+    }
+
+    /// Choices offered the next time the power levels up. Empty once the
+    /// end of the tree is reached.
+    pub fn next_upgrade_choices(&self) -> &'static [PowerUpgrade] {
+        match self.chosen_upgrades().last() {
+            Some(upgrade) => upgrade.next,
+            None => self.upgrade_tree,
+        }
+    }
+
+    pub fn upgrades(&self) -> impl Iterator<Item = PowerUpgradeEnum> {
+        self.chosen_upgrades()
+            .into_iter()
+            .flat_map(|upgrade| upgrade.upgrades.iter().copied())
+    }
+
+    /// Name of the first upgrade the player had to pick between several.
+    pub fn branch(&self) -> Option<&'static str> {
+        let mut choices = self.upgrade_tree;
+
+        for &choice in &self.upgrade_path {
+            let upgrade = choices.get(choice)?;
+            if choices.len() > 1 {
+                return Some(upgrade.name);
+            }
+            choices = upgrade.next;
+        }
+
+        None
+    }
+
+    /// Takes the given choice of the upgrade tree. The upgrades that only
+    /// change the stats of the power are applied here, the rest are read by
+    /// the power behaviours when it is cast.
+    pub fn apply_upgrade(&mut self, damage: &mut Damage, choice: usize) -> bool {
+        let Some(upgrade) = self.next_upgrade_choices().get(choice) else {
+            return false;
+        };
+
+        for upgrade in upgrade.upgrades {
+            match *upgrade {
+                PowerUpgradeEnum::Damage(multiplier) => damage.0 *= multiplier,
+                PowerUpgradeEnum::ManaCost(multiplier) => self.mana_needed *= multiplier,
+                PowerUpgradeEnum::MaxValue(extra) => {
+                    self.max_value += extra;
+                    self.value += extra;
+                }
+                PowerUpgradeEnum::Targeting(targeting) => self.targeting = targeting,
+                _ => {}
+            }
+        }
+
+        self.upgrade_path.push(choice);
+        self.level += 1;
+
+        true
+    }
+}
+
+/// Set while the player has to choose how to upgrade a power.
+#[derive(Resource)]
+pub struct PendingPowerUpgrade {
+    pub power_entity: Entity,
+    pub power_type: &'static str,
+}
+
+#[derive(Bundle, Clone)]
+pub(crate) struct PowerBundle {
+    pub(crate) marker: Power,
+    pub(crate) direction: Direction,
+    pub(crate) damage: Damage,
+    pub(crate) sprite: SpriteBundle,
+    pub(crate) atlas: TextureAtlas,
+    pub(crate) animation_indices: AnimationIndices,
+    pub(crate) animation_timer: AnimationTimer,
+    pub(crate) layer: RenderLayers,
+    pub(crate) cleanup: CleanupWhenPlayerDies,
+    name: Name,
+}
+
+impl PowerBundle {
+    pub(crate) fn new(
+        texture_atlas_layout: &mut ResMut<Assets<TextureAtlasLayout>>,
+        sprites: &Res<SpritesResources>,
+        asset_server: &Res<AssetServer>,
+        scale: Vec3,
+        pos: Vec3,
+        direction: Vec3,
+        damage: f32,
+        rotation: Quat,
+        layer: RenderLayers,
+        behaviour: &dyn PowerBehaviour,
+        value: u32,
+        max_value: u32,
+        mana_needed: f32,
+        visibility: Visibility,
+        quantity: u32,
+    ) -> Self {
+        Self::_util(
+            texture_atlas_layout,
+            sprites,
+            asset_server,
+            scale,
+            pos,
+            direction,
+            damage,
+            rotation,
+            layer,
+            behaviour,
+            value,
+            max_value,
+            mana_needed,
+            visibility,
+            quantity,
+        )
+    }
+
+    fn _util(
+        texture_atlas_layout: &mut ResMut<Assets<TextureAtlasLayout>>,
+        sprites: &Res<SpritesResources>,
+        asset_server: &Res<AssetServer>,
+        scale: Vec3,
+        pos: Vec3,
+        direction: Vec3,
+        damage: f32,
+        rotation: Quat,
+        layer: RenderLayers,
+        behaviour: &dyn PowerBehaviour,
+        value: u32,
+        max_value: u32,
+        mana_needed: f32,
+        visibility: Visibility,
+        quantity: u32,
+    ) -> Self {
+        let power_sprite = behaviour.sprite(sprites);
+        let power_animation = power_sprite.animation.unwrap();
+        let texture_atlas_layout = texture_atlas_layout.add(power_sprite.layout);
+
+        let marker = Power {
+            power_type: behaviour.power_type(),
+            origin: pos.truncate(),
+            value,
+            max_value,
+            mana_needed,
+            key_action: KeyActionEnum::Power(behaviour.power_type()),
+            quantity,
+            cooldown: Timer::default(),
+            charges: 1,
+            max_charges: 1,
+            buffered_press: None,
+            targeting: PowerTargeting::Player,
+            upgrade_tree: behaviour.upgrade_tree(),
+            level: 1,
+            upgrade_path: vec![],
+        };
+
+        PowerBundle {
+            name: Name::new("Power"),
+            marker,
+            direction: Direction(direction),
+            damage: Damage(damage),
+            sprite: SpriteBundle {
+                texture: asset_server.load(power_sprite.source),
+                transform: Transform {
+                    rotation,
+                    translation: pos,
+                    scale,
+                },
+                visibility,
+                ..default()
+            },
+            atlas: TextureAtlas {
+                layout: texture_atlas_layout,
+                index: power_animation.indices.first,
+            },
+            animation_indices: power_animation.indices,
+            animation_timer: power_animation.timer,
+            layer,
+            cleanup: CleanupWhenPlayerDies,
+        }
+    }
+}
+
+pub fn equip_player_with_power(
+    commands: &mut Commands,
+    texture_atlas_layout: ResMut<Assets<TextureAtlasLayout>>,
+    sprites: &Res<SpritesResources>,
+    asset_server: &Res<AssetServer>,
+
+    behaviour: &dyn PowerBehaviour,
+    player_entity: Entity,
+) {
+    let visibility = Visibility::Hidden;
+
+    let PowerType {
+        damage,
+        mana_needed,
+        max_value,
+        quantity,
+        cooldown_seconds,
+        charges,
+        targeting,
+        default_key: _,
+    } = behaviour.config();
+
+    let mut power_bundle = _get_power_bundle(
+        texture_atlas_layout,
+        sprites,
+        asset_server,
+        behaviour,
+        *max_value,
+        *mana_needed,
+        *quantity,
+        *damage,
+        visibility,
+    );
+    power_bundle.marker.cooldown = Timer::from_seconds(*cooldown_seconds, TimerMode::Repeating);
+    power_bundle.marker.charges = *charges;
+    power_bundle.marker.max_charges = *charges;
+    power_bundle.marker.targeting = *targeting;
+
+    commands.entity(player_entity).with_children(|parent| {
+        parent.spawn(power_bundle);
+    });
+}
+
+pub fn spawn_power(
+    registry: &PowerRegistry,
+    ctx: &mut PowerSpawnContext,
+    power: &Power,
+    power_damage: &Damage,
+) -> bool {
+    let Some(behaviour) = registry.get(power.power_type) else {
+        return false;
+    };
+
+    behaviour.spawn(ctx, power, power_damage.0)
+}
+
+/// Area shown on the ground while a ground targeted power is being aimed.
+#[derive(Component)]
+pub struct PowerTargetPreview {
+    pub power_type: &'static str,
+}
+
+pub fn update_power_target_previews(
+    mut commands: Commands,
+    keyboard_input: Res<ButtonInput<KeyCode>>,
+    mut meshes: ResMut<Assets<Mesh>>,
+    mut materials: ResMut<Assets<ColorMaterial>>,
+    windows: Query<&Window>,
+    base_camera: Query<(&Camera, &GlobalTransform), With<BaseCamera>>,
+    player_query: Query<&Children, With<Player>>,
+    powers: Query<&Power>,
+    mut previews: Query<(Entity, &mut Transform, &PowerTargetPreview)>,
+    settings: Res<Settings>,
+) {
+    let Ok(player_children) = player_query.get_single() else {
+        return;
+    };
+
+    let Ok(window) = windows.get_single() else {
+        return;
+    };
+
+    let Ok((camera, camera_transform)) = base_camera.get_single() else {
+        return;
+    };
+
+    let cursor_position = get_cursor_world_position(window, camera, camera_transform);
+
+    for &child in player_children {
+        let Ok(power) = powers.get(child) else {
+            continue;
+        };
+
+        let PowerTargeting::GroundTarget { radius } = power.targeting else {
+            continue;
+        };
+
+        let preview = previews
+            .iter_mut()
+            .find(|(_, _, preview)| preview.power_type == power.power_type);
+        let aiming = keyboard_input.pressed(settings.key_bindings.get(power.key_action));
+
+        match (aiming, preview, cursor_position) {
+            (true, Some((_, mut preview_transform, _)), Some(cursor_position)) => {
+                preview_transform.translation = cursor_position.extend(CHAR_Z_INDEX);
+            }
+            (true, None, Some(cursor_position)) => {
+                commands.spawn((
+                    MaterialMesh2dBundle {
+                        mesh: Mesh2dHandle(meshes.add(Circle::new(radius))),
+                        material: materials.add(POWER_TARGET_PREVIEW_COLOR),
+                        transform: Transform::from_translation(
+                            cursor_position.extend(CHAR_Z_INDEX),
+                        ),
+                        ..default()
+                    },
+                    PowerTargetPreview {
+                        power_type: power.power_type,
+                    },
+                    BASE_LAYER,
+                    CleanupWhenPlayerDies,
+                ));
+            }
+            (false, Some((preview_entity, _, _)), _) => {
+                commands.entity(preview_entity).despawn();
+            }
+            _ => {}
+        }
+    }
+}
+
+/// Returns the target and the aim direction of the power.
+pub(crate) fn get_power_target(
+    targeting: PowerTargeting,
+    player_position: Vec2,
+    player_direction: Vec2,
+    cursor_position: Option<Vec2>,
+    enemy_positions: impl Iterator<Item = Vec2>,
+) -> (Vec2, Vec2) {
+    let cursor_target = cursor_position.unwrap_or(player_position);
+
+    let target = match targeting {
+        PowerTargeting::Player => player_position,
+        PowerTargeting::Cursor
+        | PowerTargeting::GroundTarget { .. }
+        | PowerTargeting::Cone { .. } => cursor_target,
+        PowerTargeting::NearestEnemy => enemy_positions
+            .min_by(|a, b| {
+                a.distance_squared(player_position)
+                    .total_cmp(&b.distance_squared(player_position))
+            })
+            .unwrap_or(cursor_target),
+    };
+
+    let aim_direction = match targeting {
+        PowerTargeting::Player => player_direction,
+        _ => target - player_position,
+    }
+    .try_normalize()
+    .unwrap_or(Vec2::X);
+
+    (target, aim_direction)
+}
+
+/// Picks one of the upgrades offered for a power with the number keys.
+pub fn choose_power_upgrade(
+    mut commands: Commands,
+    keyboard_input: Res<ButtonInput<KeyCode>>,
+    pending_upgrade: Option<Res<PendingPowerUpgrade>>,
+    powers: Query<&Power>,
+    choice_ui: Query<Entity, With<PowerUpgradeChoiceUI>>,
+) {
+    let Some(pending_upgrade) = pending_upgrade else {
+        return;
+    };
+
+    let Ok(power) = powers.get(pending_upgrade.power_entity) else {
+        return;
+    };
+
+    let number_keys = [
+        KeyCode::Digit1,
+        KeyCode::Digit2,
+        KeyCode::Digit3,
+        KeyCode::Digit4,
+    ];
+    let Some(choice) = number_keys
+        .iter()
+        .take(power.next_upgrade_choices().len())
+        .position(|&key| keyboard_input.just_pressed(key))
+    else {
+        return;
+    };
+
+    commands.trigger(PowerUpgradeChosen {
+        power_entity: pending_upgrade.power_entity,
+        choice,
+    });
+    commands.remove_resource::<PendingPowerUpgrade>();
+
+    for choice_ui_entity in choice_ui.iter() {
+        commands.entity(choice_ui_entity).despawn_recursive();
+    }
+}
+
+pub fn tick_power_cooldowns(
+    time: Res<Time>,
+    player_query: Query<&Children, With<Player>>,
+    mut powers: Query<&mut Power>,
+) {
+    let Ok(player_children) = player_query.get_single() else {
+        return;
+    };
+
+    for &child in player_children {
+        let Ok(mut power) = powers.get_mut(child) else {
+            continue;
+        };
+
+        if let Some(buffered_press) = power.buffered_press.as_mut() {
+            buffered_press.tick(time.delta());
+            if buffered_press.finished() {
+                power.buffered_press = None;
+            }
+        }
+
+        if power.charges >= power.max_charges {
+            continue;
+        }
+
+        power.cooldown.tick(time.delta());
+        if power.cooldown.just_finished() {
+            power.charges += 1;
+        }
+    }
+}
+
+pub fn tick_powers(
+    time: Res<Time>,
+    registry: Res<PowerRegistry>,
+    mut meshes: ResMut<Assets<Mesh>>,
+    mut powers: Query<(
+        &mut PowerInstance,
+        &mut Transform,
+        Option<&mut Mesh2dHandle>,
+    )>,
+) {
+    let mut ctx = PowerTickContext {
+        delta: time.delta(),
+        meshes: &mut meshes,
+    };
+
+    for (mut instance, mut transform, mut mesh) in powers.iter_mut() {
+        let Some(behaviour) = registry.get(instance.power_type) else {
+            continue;
+        };
+
+        behaviour.tick(&mut ctx, &mut instance, &mut transform, mesh.as_deref_mut());
+    }
+}
+
+pub fn expire_powers(
+    mut commands: Commands,
+    registry: Res<PowerRegistry>,
+    powers: Query<(Entity, &PowerInstance, &Transform)>,
+) {
+    for (power_entity, instance, transform) in powers.iter() {
+        if !instance.has_expired() {
+            continue;
+        }
+
+        if let Some(behaviour) = registry.get(instance.power_type) {
+            behaviour.expire(&mut commands, instance, transform);
+        }
+
+        commands.entity(power_entity).despawn_recursive();
+    }
+}
+
+fn _get_power_bundle(
+    mut texture_atlas_layout: ResMut<Assets<TextureAtlasLayout>>,
+    sprites: &Res<SpritesResources>,
+    asset_server: &Res<AssetServer>,
+
+    behaviour: &dyn PowerBehaviour,
+    max_value: u32,
+    mana_needed: f32,
+    quantity: u32,
+    damage: f32,
+    visibility: Visibility,
+) -> PowerBundle {
+    let scale = Vec3::ONE;
+    let direction = Vec3::ZERO;
+    let rotation = Quat::default();
+    let pos = Vec3::new(0.0, 0.0, 0.0);
+    let layer = BASE_LAYER;
+
+    let value = max_value;
+
+    PowerBundle::new(
+        &mut texture_atlas_layout,
+        sprites,
+        asset_server,
+        scale,
+        pos,
+        direction,
+        damage,
+        rotation,
+        layer,
+        behaviour,
+        value,
+        max_value,
+        mana_needed,
+        visibility,
+        quantity,
+    )
+}
@@ -0,0 +1,236 @@
+use rand::Rng;
+
+use crate::{
+    prelude::*,
+    util::{get_prop_sprite_based_on_prop_kind, get_shards_sprite_based_on_prop_kind},
+    CleanupWhenPlayerDies, CurrentWave, Health, SpritesResources, Tilemap,
+};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum PropKind {
+    EnergyPack,
+    Boulder,
+}
+
+impl PropKind {
+    /// How props are written on the level layouts.
+    pub(crate) fn from_char(character: char) -> Option<Self> {
+        match character {
+            'E' => Some(PropKind::EnergyPack),
+            'B' => Some(PropKind::Boulder),
+            _ => None,
+        }
+    }
+
+    pub fn health(&self) -> f32 {
+        match self {
+            PropKind::EnergyPack => ENERGY_PACK_PROP_HEALTH,
+            PropKind::Boulder => BOULDER_PROP_HEALTH,
+        }
+    }
+
+    fn scale(&self) -> f32 {
+        match self {
+            PropKind::EnergyPack => ENERGY_PACK_PROP_SCALE,
+            PropKind::Boulder => BOULDER_PROP_SCALE,
+        }
+    }
+}
+
+/// Breakable thing on the map. Unlike the obstacles it does not block
+/// anything, it is shot at for its loot.
+#[derive(Component, Clone)]
+pub struct Prop {
+    pub kind: PropKind,
+    pub half_size: Vec2,
+}
+
+/// Shards animation played once where a prop broke.
+#[derive(Component, Clone)]
+pub struct BreakingProp {
+    pub timer: Timer,
+}
+
+#[derive(Component, Clone)]
+pub struct PropShard {
+    pub velocity: Vec2,
+    pub timer: Timer,
+}
+
+pub(crate) fn spawn_prop(
+    commands: &mut Commands,
+    texture_atlas_layout: &mut ResMut<Assets<TextureAtlasLayout>>,
+    asset_server: &Res<AssetServer>,
+    sprites: &Res<SpritesResources>,
+    kind: PropKind,
+    position: Vec2,
+) {
+    let prop_sprite = get_prop_sprite_based_on_prop_kind(kind, sprites);
+    let half_size = Vec2::new(
+        prop_sprite.dimensions.width as f32,
+        prop_sprite.dimensions.height as f32,
+    ) * kind.scale()
+        / 2.;
+
+    commands.spawn((
+        SpriteBundle {
+            texture: asset_server.load(prop_sprite.source),
+            transform: Transform {
+                translation: position.extend(TILE_Z_INDEX + 0.4),
+                scale: Vec3::splat(kind.scale()),
+                ..default()
+            },
+            ..default()
+        },
+        TextureAtlas {
+            layout: texture_atlas_layout.add(prop_sprite.layout),
+            index: 0,
+        },
+        Prop { kind, half_size },
+        Health(kind.health()),
+        Name::new("Prop"),
+        BASE_LAYER,
+        CleanupWhenPlayerDies,
+    ));
+}
+
+/// The shards animation plus a few shards flying off.
+pub(crate) fn spawn_prop_break_effects(
+    commands: &mut Commands,
+    texture_atlas_layout: &mut ResMut<Assets<TextureAtlasLayout>>,
+    asset_server: &Res<AssetServer>,
+    sprites: &Res<SpritesResources>,
+    kind: PropKind,
+    position: Vec2,
+) {
+    let shards_sprite = get_shards_sprite_based_on_prop_kind(kind, sprites);
+    let shards_animation = shards_sprite.animation.unwrap();
+    let frames = shards_animation.indices.last - shards_animation.indices.first + 1;
+    let layout = texture_atlas_layout.add(shards_sprite.layout);
+    let texture = asset_server.load(shards_sprite.source);
+
+    commands.spawn((
+        SpriteBundle {
+            texture: texture.clone(),
+            transform: Transform {
+                translation: position.extend(CHAR_Z_INDEX),
+                scale: Vec3::splat(PROP_BREAK_SCALE),
+                ..default()
+            },
+            ..default()
+        },
+        TextureAtlas {
+            layout: layout.clone(),
+            index: shards_animation.indices.first,
+        },
+        BreakingProp {
+            timer: Timer::from_seconds(frames as f32 * PROP_BREAK_FRAME_SECONDS, TimerMode::Once),
+        },
+        shards_animation.indices.clone(),
+        shards_animation.timer,
+        Name::new("BreakingProp"),
+        BASE_LAYER,
+        CleanupWhenPlayerDies,
+    ));
+
+    let mut rng = rand::thread_rng();
+
+    for _ in 0..PROP_SHARDS {
+        let direction = Vec2::from_angle(rng.gen_range(0.0..std::f32::consts::TAU));
+
+        commands.spawn((
+            SpriteBundle {
+                texture: texture.clone(),
+                transform: Transform {
+                    translation: position.extend(CHAR_Z_INDEX),
+                    rotation: Quat::from_rotation_z(rng.gen_range(0.0..std::f32::consts::TAU)),
+                    scale: Vec3::splat(PROP_SHARD_SCALE),
+                },
+                ..default()
+            },
+            TextureAtlas {
+                layout: layout.clone(),
+                index: rng
+                    .gen_range(shards_animation.indices.first..=shards_animation.indices.last),
+            },
+            PropShard {
+                velocity: direction * PROP_SHARD_SPEED,
+                timer: Timer::from_seconds(PROP_SHARD_SECONDS, TimerMode::Once),
+            },
+            Name::new("PropShard"),
+            BASE_LAYER,
+            CleanupWhenPlayerDies,
+        ));
+    }
+}
+
+pub fn despawn_broken_props(
+    mut commands: Commands,
+    time: Res<Time>,
+    mut breaking_props: Query<(Entity, &mut BreakingProp)>,
+) {
+    for (breaking_prop_entity, mut breaking_prop) in breaking_props.iter_mut() {
+        breaking_prop.timer.tick(time.delta());
+        if breaking_prop.timer.finished() {
+            commands.entity(breaking_prop_entity).despawn();
+        }
+    }
+}
+
+pub fn fade_prop_shards(
+    mut commands: Commands,
+    time: Res<Time>,
+    mut shards: Query<(Entity, &mut Transform, &mut Sprite, &mut PropShard)>,
+) {
+    for (shard_entity, mut shard_transform, mut shard_sprite, mut shard) in shards.iter_mut() {
+        shard.timer.tick(time.delta());
+        if shard.timer.finished() {
+            commands.entity(shard_entity).despawn();
+            continue;
+        }
+
+        shard_transform.translation += (shard.velocity * time.delta_seconds()).extend(0.);
+        shard_sprite
+            .color
+            .set_alpha(shard.timer.fraction_remaining());
+    }
+}
+
+/// Places the props of the level when it starts and, if configured, puts
+/// every prop back when a new wave starts.
+pub fn reset_props(
+    mut commands: Commands,
+    mut texture_atlas_layout: ResMut<Assets<TextureAtlasLayout>>,
+    asset_server: Res<AssetServer>,
+    sprites: Res<SpritesResources>,
+    tilemap: Option<Res<Tilemap>>,
+    current_wave: Res<CurrentWave>,
+    props: Query<Entity, With<Prop>>,
+    mut props_wave: Local<Option<u16>>,
+) {
+    let Some(tilemap) = tilemap else {
+        return;
+    };
+
+    let new_wave = PROPS_RESET_EACH_WAVE && *props_wave != Some(current_wave.0);
+    if !tilemap.is_changed() && !new_wave {
+        return;
+    }
+
+    *props_wave = Some(current_wave.0);
+
+    for prop_entity in props.iter() {
+        commands.entity(prop_entity).despawn();
+    }
+
+    for (position, prop_kind) in tilemap.props() {
+        spawn_prop(
+            &mut commands,
+            &mut texture_atlas_layout,
+            &asset_server,
+            &sprites,
+            prop_kind,
+            position,
+        );
+    }
+}
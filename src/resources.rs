@@ -1,8 +1,8 @@
 use std::time::Duration;
 
 use crate::{
-    prelude::*, AnimationIndices, AnimationInfo, AnimationTimer, RectangularDimensions, SpriteInfo,
-    Sprites,
+    prelude::*, AnimationIndices, AnimationInfo, AnimationTimer, CameraEffects, EnemySpatialGrid,
+    NavGrid, PlayerFlowField, RectangularDimensions, Settings, SpriteInfo, Sprites,
 };
 
 #[derive(Resource)]
@@ -14,6 +14,28 @@ pub struct CurrentGameLevel(pub u16);
 #[derive(Resource)]
 pub struct CurrentBoss(pub Option<u16>);
 
+/// Everything procedural on a run comes from this seed, e.g. the arenas.
+#[derive(Resource)]
+pub struct RunSeed(pub u64);
+
+impl RunSeed {
+    /// The seed of `ARENA_RUN_SEED_ENV_VAR` when it is set, a random one
+    /// otherwise.
+    pub fn new_run() -> Self {
+        let seed = std::env::var(ARENA_RUN_SEED_ENV_VAR)
+            .ok()
+            .and_then(|seed| {
+                seed.trim()
+                    .parse()
+                    .inspect_err(|err| error!("Invalid run seed {seed:?}: {err}"))
+                    .ok()
+            })
+            .unwrap_or_else(rand::random);
+
+        Self(seed)
+    }
+}
+
 #[derive(Resource)]
 pub struct CurrentWave(pub u16);
 
@@ -29,6 +51,9 @@ pub struct CurrentTime {
 #[derive(Resource)]
 pub struct AutoShootingEnabled(pub bool);
 
+#[derive(Resource)]
+pub struct MinimapEnabled(pub bool);
+
 #[derive(Resource)]
 pub struct EnemyWaves(pub [EnemyByWave; NUMBER_OF_WAVES]);
 
@@ -38,9 +63,6 @@ pub struct WeaponWaves(pub [WeaponByWave<'static>; NUMBER_OF_WAVES]);
 #[derive(Resource)]
 pub struct ItemWaves(pub [ItemByWave<'static>; NUMBER_OF_WAVES]);
 
-#[derive(Resource)]
-pub struct PowerLevels(pub [PowerByLevel; NUMBER_OF_POWERS]);
-
 #[derive(Resource)]
 pub struct SpritesResources(pub Sprites<'static>);
 
@@ -53,12 +75,7 @@ pub enum GameState {
     Won,
     InBetweenLevels,
     Start,
-}
-
-#[derive(Resource)]
-pub struct WindowResolutionResource {
-    pub x_px: f32,
-    pub y_px: f32,
+    BossIntro,
 }
 
 #[derive(Resource)]
@@ -67,19 +84,18 @@ pub struct MouseDirectionWhenAutoShooting {
     pub y_px: f32,
 }
 
-pub fn setup_resources(mut commands: Commands, windows: Query<&Window>) {
-    let window = windows.single();
-
+pub fn setup_resources(mut commands: Commands, settings: Res<Settings>) {
     commands.insert_resource(CurrentWave(1));
     commands.insert_resource(CurrentBoss(None));
     commands.insert_resource(CurrentGameLevel(1));
+    commands.insert_resource(RunSeed::new_run());
     commands.insert_resource(EnemyWaves(ENEMIES_PER_WAVE));
     commands.insert_resource(WeaponWaves(WEAPONS_PER_WAVE));
     commands.insert_resource(ItemWaves(ITEMS_PER_WAVE));
-    commands.insert_resource(PowerLevels(POWERS_PER_LEVEL));
     commands.insert_resource(SpritesResources(get_sprites()));
     commands.insert_resource(CurrentScore(0.));
-    commands.insert_resource(AutoShootingEnabled(false));
+    commands.insert_resource(AutoShootingEnabled(settings.auto_shoot));
+    commands.insert_resource(MinimapEnabled(true));
     commands.insert_resource(MouseDirectionWhenAutoShooting {
         x_px: 0.0,
         y_px: 0.0,
@@ -92,10 +108,10 @@ pub fn setup_resources(mut commands: Commands, windows: Query<&Window>) {
         Duration::from_secs(3),
         TimerMode::Repeating,
     )));
-    commands.insert_resource(WindowResolutionResource {
-        x_px: window.resolution.width(),
-        y_px: window.resolution.height(),
-    });
+    commands.insert_resource(EnemySpatialGrid::default());
+    commands.insert_resource(NavGrid::default());
+    commands.insert_resource(PlayerFlowField::default());
+    commands.insert_resource(CameraEffects::default());
 }
 
 pub fn get_sprites() -> Sprites<'static> {
@@ -130,6 +146,129 @@ pub fn get_sprites() -> Sprites<'static> {
             animation: None,
             layout: TextureAtlasLayout::from_grid(UVec2::new(1920, 1080), 1, 1, None, None),
         },
+        rock: SpriteInfo {
+            dimensions: RectangularDimensions {
+                width: 64,
+                height: 64,
+            },
+            source: "textures/Tiles/RunnerTileSet.png",
+            animation: None,
+            layout: TextureAtlasLayout::from_grid(
+                UVec2::splat(64),
+                1,
+                1,
+                None,
+                Some(UVec2::new(128, 64)),
+            ),
+        },
+        crate_box: SpriteInfo {
+            dimensions: RectangularDimensions {
+                width: 64,
+                height: 64,
+            },
+            source: "textures/Tiles/RunnerTileSet.png",
+            animation: None,
+            layout: TextureAtlasLayout::from_grid(
+                UVec2::splat(64),
+                1,
+                1,
+                None,
+                Some(UVec2::new(320, 32)),
+            ),
+        },
+        digging_machine_back: SpriteInfo {
+            dimensions: RectangularDimensions {
+                width: 64,
+                height: 120,
+            },
+            source: "textures/Digging Machines/Digging_Machine_Back_Section.png",
+            animation: None,
+            layout: TextureAtlasLayout::from_grid(UVec2::new(64, 120), 1, 1, None, None),
+        },
+        digging_machine_front: SpriteInfo {
+            dimensions: RectangularDimensions {
+                width: 56,
+                height: 120,
+            },
+            source: "textures/Digging Machines/Digging_Machine_Front_Section_30fps.png",
+            animation: Some(AnimationInfo {
+                indices: AnimationIndices { first: 0, last: 3 },
+                timer: AnimationTimer(Timer::from_seconds(
+                    PLAYER_ANIMATION_TIMER,
+                    TimerMode::Repeating,
+                )),
+            }),
+            layout: TextureAtlasLayout::from_grid(UVec2::new(56, 120), 4, 1, None, None),
+        },
+        energy_pack: SpriteInfo {
+            dimensions: RectangularDimensions {
+                width: 16,
+                height: 16,
+            },
+            source: "textures/Other sprites/EnergyPack.png",
+            animation: None,
+            layout: TextureAtlasLayout::from_grid(UVec2::splat(16), 1, 1, None, None),
+        },
+        glass_shards: SpriteInfo {
+            dimensions: RectangularDimensions {
+                width: 16,
+                height: 16,
+            },
+            source: "textures/Other sprites/GlassShards.png",
+            animation: Some(AnimationInfo {
+                indices: AnimationIndices { first: 0, last: 2 },
+                timer: AnimationTimer(Timer::from_seconds(
+                    PROP_BREAK_FRAME_SECONDS,
+                    TimerMode::Repeating,
+                )),
+            }),
+            layout: TextureAtlasLayout::from_grid(UVec2::splat(16), 3, 1, None, None),
+        },
+        rock_shards: SpriteInfo {
+            dimensions: RectangularDimensions {
+                width: 16,
+                height: 16,
+            },
+            source: "textures/Other sprites/RockShards.png",
+            animation: Some(AnimationInfo {
+                indices: AnimationIndices { first: 0, last: 7 },
+                timer: AnimationTimer(Timer::from_seconds(
+                    PROP_BREAK_FRAME_SECONDS,
+                    TimerMode::Repeating,
+                )),
+            }),
+            layout: TextureAtlasLayout::from_grid(UVec2::splat(16), 8, 1, None, None),
+        },
+        coin: SpriteInfo {
+            dimensions: RectangularDimensions {
+                width: 16,
+                height: 16,
+            },
+            source: "textures/Other sprites/Diamond.png",
+            animation: Some(AnimationInfo {
+                indices: AnimationIndices { first: 0, last: 0 },
+                timer: AnimationTimer(Timer::from_seconds(
+                    PLAYER_ANIMATION_TIMER,
+                    TimerMode::Repeating,
+                )),
+            }),
+            layout: TextureAtlasLayout::from_grid(UVec2::splat(16), 1, 1, None, None),
+        },
+        tileset: SpriteInfo {
+            dimensions: RectangularDimensions {
+                width: TILE_SIZE_PX,
+                height: TILE_SIZE_PX,
+            },
+            source: "textures/Tiles/RunnerTileSet.png",
+            animation: None,
+            layout: TextureAtlasLayout::from_grid(
+                UVec2::splat(TILE_SIZE_PX),
+                TILESET_COLUMNS,
+                TILESET_ROWS,
+                None,
+                None,
+            ),
+        },
         player_char_idle: SpriteInfo {
             dimensions: RectangularDimensions {
                 width: PLAYER_PIXEL_SIZE,
@@ -0,0 +1,516 @@
+use std::collections::BTreeMap;
+
+use bevy::window::{PrimaryWindow, WindowMode};
+use serde::{Deserialize, Serialize};
+
+use crate::{prelude::*, util::get_key_name, PowerRegistry};
+
+/// Options chosen by the player on the settings menu. They are saved every
+/// time they change and loaded when the game starts.
+#[derive(Resource, Serialize, Deserialize, Debug, Clone, PartialEq)]
+#[serde(default)]
+pub struct Settings {
+    pub master_volume: f32,
+    pub music_volume: f32,
+    pub sfx_volume: f32,
+    pub fullscreen: bool,
+    // Size of the window compared to the virtual resolution, when it is not
+    // fullscreen
+    pub resolution_scale: f32,
+    pub damage_numbers: bool,
+    // Multiplies the screen shake, 0 turns it off
+    pub screen_shake: f32,
+    // Whether auto-shooting is on when a game starts
+    pub auto_shoot: bool,
+    pub key_bindings: KeyBindings,
+}
+
+impl Default for Settings {
+    fn default() -> Self {
+        Self {
+            master_volume: 1.0,
+            music_volume: 1.0,
+            sfx_volume: 1.0,
+            fullscreen: false,
+            resolution_scale: 1.0,
+            damage_numbers: true,
+            screen_shake: 1.0,
+            auto_shoot: false,
+            key_bindings: KeyBindings::default(),
+        }
+    }
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+#[serde(default)]
+pub struct KeyBindings {
+    pub move_up: KeyCode,
+    pub move_left: KeyCode,
+    pub move_down: KeyCode,
+    pub move_right: KeyCode,
+    pub dash: KeyCode,
+    pub auto_shoot: KeyCode,
+    pub player_stats: KeyCode,
+    pub minimap: KeyCode,
+    pub fullscreen: KeyCode,
+    // Key that casts each power, by its power type. The powers missing
+    // here get their default key when the settings are loaded.
+    pub powers: BTreeMap<String, KeyCode>,
+}
+
+impl Default for KeyBindings {
+    fn default() -> Self {
+        Self {
+            move_up: KeyCode::KeyW,
+            move_left: KeyCode::KeyA,
+            move_down: KeyCode::KeyS,
+            move_right: KeyCode::KeyD,
+            dash: KeyCode::Space,
+            auto_shoot: KeyCode::KeyM,
+            player_stats: KeyCode::KeyK,
+            minimap: KeyCode::Tab,
+            fullscreen: KeyCode::F11,
+            powers: BTreeMap::new(),
+        }
+    }
+}
+
+impl KeyBindings {
+    pub fn get(&self, action: KeyActionEnum) -> KeyCode {
+        match action {
+            KeyActionEnum::MoveUp => self.move_up,
+            KeyActionEnum::MoveLeft => self.move_left,
+            KeyActionEnum::MoveDown => self.move_down,
+            KeyActionEnum::MoveRight => self.move_right,
+            KeyActionEnum::Dash => self.dash,
+            KeyActionEnum::AutoShoot => self.auto_shoot,
+            KeyActionEnum::PlayerStats => self.player_stats,
+            KeyActionEnum::Minimap => self.minimap,
+            KeyActionEnum::Fullscreen => self.fullscreen,
+            KeyActionEnum::Power(power_type) => self
+                .powers
+                .get(power_type)
+                .copied()
+                .unwrap_or(SETTINGS_UNBOUND_KEY),
+        }
+    }
+
+    /// An action already bound to the key gets the previous key of this one,
+    /// so no two actions share a key.
+    pub fn set(&mut self, action: KeyActionEnum, key: KeyCode) {
+        let previous_key = self.get(action);
+        if let Some(other_binding) = self.bindings_mut().find(|binding| **binding == key) {
+            *other_binding = previous_key;
+        }
+
+        *self.binding_mut(action) = key;
+    }
+
+    /// Binds the registered powers that have no key yet to their default
+    /// one, unless it is already taken.
+    pub fn add_missing_powers(&mut self, registry: &PowerRegistry) {
+        for behaviour in registry.iter() {
+            if self.powers.contains_key(behaviour.power_type()) {
+                continue;
+            }
+
+            let default_key = behaviour.config().default_key;
+            let key = if self.bindings_mut().any(|binding| *binding == default_key) {
+                SETTINGS_UNBOUND_KEY
+            } else {
+                default_key
+            };
+            self.powers.insert(behaviour.power_type().to_string(), key);
+        }
+    }
+
+    fn binding_mut(&mut self, action: KeyActionEnum) -> &mut KeyCode {
+        match action {
+            KeyActionEnum::MoveUp => &mut self.move_up,
+            KeyActionEnum::MoveLeft => &mut self.move_left,
+            KeyActionEnum::MoveDown => &mut self.move_down,
+            KeyActionEnum::MoveRight => &mut self.move_right,
+            KeyActionEnum::Dash => &mut self.dash,
+            KeyActionEnum::AutoShoot => &mut self.auto_shoot,
+            KeyActionEnum::PlayerStats => &mut self.player_stats,
+            KeyActionEnum::Minimap => &mut self.minimap,
+            KeyActionEnum::Fullscreen => &mut self.fullscreen,
+            KeyActionEnum::Power(power_type) => self
+                .powers
+                .entry(power_type.to_string())
+                .or_insert(SETTINGS_UNBOUND_KEY),
+        }
+    }
+
+    fn bindings_mut(&mut self) -> impl Iterator<Item = &mut KeyCode> {
+        [
+            &mut self.move_up,
+            &mut self.move_left,
+            &mut self.move_down,
+            &mut self.move_right,
+            &mut self.dash,
+            &mut self.auto_shoot,
+            &mut self.player_stats,
+            &mut self.minimap,
+            &mut self.fullscreen,
+        ]
+        .into_iter()
+        .chain(self.powers.values_mut())
+    }
+
+    /// Direction the movement keys being pressed point to. It is not
+    /// normalized.
+    pub fn movement_direction(&self, keyboard_input: &ButtonInput<KeyCode>) -> Vec2 {
+        let mut direction = Vec2::ZERO;
+
+        if keyboard_input.pressed(self.move_up) {
+            direction.y += 1.0;
+        }
+        if keyboard_input.pressed(self.move_left) {
+            direction.x -= 1.0;
+        }
+        if keyboard_input.pressed(self.move_down) {
+            direction.y -= 1.0;
+        }
+        if keyboard_input.pressed(self.move_right) {
+            direction.x += 1.0;
+        }
+
+        direction
+    }
+}
+
+#[cfg_attr(
+    not(feature = "web"),
+    derive(Reflect, Debug, Clone, Copy, PartialEq, Eq)
+)]
+#[cfg_attr(feature = "web", derive(Debug, Clone, Copy, PartialEq, Eq))]
+pub enum KeyActionEnum {
+    MoveUp,
+    MoveLeft,
+    MoveDown,
+    MoveRight,
+    Dash,
+    AutoShoot,
+    PlayerStats,
+    Minimap,
+    Fullscreen,
+    // Casting the power with this power type
+    Power(&'static str),
+}
+
+impl KeyActionEnum {
+    // Every action but the powers, which come from the `PowerRegistry`
+    pub const ALL: [KeyActionEnum; 9] = [
+        KeyActionEnum::MoveUp,
+        KeyActionEnum::MoveLeft,
+        KeyActionEnum::MoveDown,
+        KeyActionEnum::MoveRight,
+        KeyActionEnum::Dash,
+        KeyActionEnum::AutoShoot,
+        KeyActionEnum::PlayerStats,
+        KeyActionEnum::Minimap,
+        KeyActionEnum::Fullscreen,
+    ];
+
+    pub fn name(&self) -> String {
+        let name = match self {
+            KeyActionEnum::MoveUp => "Move up",
+            KeyActionEnum::MoveLeft => "Move left",
+            KeyActionEnum::MoveDown => "Move down",
+            KeyActionEnum::MoveRight => "Move right",
+            KeyActionEnum::Dash => "Dash",
+            KeyActionEnum::AutoShoot => "Auto-shoot",
+            KeyActionEnum::PlayerStats => "Player stats",
+            KeyActionEnum::Minimap => "Minimap",
+            KeyActionEnum::Fullscreen => "Fullscreen",
+            // "circle_of_death" is shown as "Circle of death"
+            KeyActionEnum::Power(power_type) => {
+                let name = power_type.replace('_', " ");
+                let mut chars = name.chars();
+                return chars
+                    .next()
+                    .map(|first| first.to_uppercase().chain(chars).collect())
+                    .unwrap_or_default();
+            }
+        };
+
+        name.to_string()
+    }
+}
+
+/// Each row of the settings menu.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SettingsOptionEnum {
+    MasterVolume,
+    MusicVolume,
+    SfxVolume,
+    Fullscreen,
+    ResolutionScale,
+    DamageNumbers,
+    ScreenShake,
+    AutoShoot,
+    KeyBinding(KeyActionEnum),
+}
+
+impl SettingsOptionEnum {
+    pub const GENERAL: [SettingsOptionEnum; 8] = [
+        SettingsOptionEnum::MasterVolume,
+        SettingsOptionEnum::MusicVolume,
+        SettingsOptionEnum::SfxVolume,
+        SettingsOptionEnum::Fullscreen,
+        SettingsOptionEnum::ResolutionScale,
+        SettingsOptionEnum::DamageNumbers,
+        SettingsOptionEnum::ScreenShake,
+        SettingsOptionEnum::AutoShoot,
+    ];
+
+    pub fn label(&self, settings: &Settings, pending_key_binding: Option<KeyActionEnum>) -> String {
+        let on_off = |value: bool| if value { "On" } else { "Off" };
+        let percent = |value: f32| format!("{:.0}%", value * 100.);
+
+        match self {
+            SettingsOptionEnum::MasterVolume => {
+                format!("Master volume: {}", percent(settings.master_volume))
+            }
+            SettingsOptionEnum::MusicVolume => {
+                format!("Music volume: {}", percent(settings.music_volume))
+            }
+            SettingsOptionEnum::SfxVolume => {
+                format!("SFX volume: {}", percent(settings.sfx_volume))
+            }
+            SettingsOptionEnum::Fullscreen => {
+                format!("Fullscreen: {}", on_off(settings.fullscreen))
+            }
+            SettingsOptionEnum::ResolutionScale => {
+                format!("Resolution scale: {}", percent(settings.resolution_scale))
+            }
+            SettingsOptionEnum::DamageNumbers => {
+                format!("Damage numbers: {}", on_off(settings.damage_numbers))
+            }
+            SettingsOptionEnum::ScreenShake => {
+                format!("Screen shake: {}", percent(settings.screen_shake))
+            }
+            SettingsOptionEnum::AutoShoot => {
+                format!("Auto-shoot: {}", on_off(settings.auto_shoot))
+            }
+            SettingsOptionEnum::KeyBinding(action) if pending_key_binding == Some(*action) => {
+                format!("{}: press a key", action.name())
+            }
+            SettingsOptionEnum::KeyBinding(action) => {
+                format!(
+                    "{}: {}",
+                    action.name(),
+                    get_key_name(settings.key_bindings.get(*action))
+                )
+            }
+        }
+    }
+}
+
+impl Settings {
+    /// Goes to the next value of the option. Key bindings are changed with
+    /// the next key pressed instead.
+    pub fn cycle(&mut self, option: SettingsOptionEnum) {
+        let next_volume = |volume: f32| {
+            let next = ((volume + SETTINGS_VOLUME_STEP) / SETTINGS_VOLUME_STEP).round()
+                * SETTINGS_VOLUME_STEP;
+            if next > 1.0 + f32::EPSILON {
+                0.0
+            } else {
+                next
+            }
+        };
+
+        match option {
+            SettingsOptionEnum::MasterVolume => {
+                self.master_volume = next_volume(self.master_volume)
+            }
+            SettingsOptionEnum::MusicVolume => self.music_volume = next_volume(self.music_volume),
+            SettingsOptionEnum::SfxVolume => self.sfx_volume = next_volume(self.sfx_volume),
+            SettingsOptionEnum::Fullscreen => self.fullscreen = !self.fullscreen,
+            SettingsOptionEnum::ResolutionScale => {
+                self.resolution_scale = SETTINGS_RESOLUTION_SCALES
+                    .iter()
+                    .copied()
+                    .find(|&scale| scale > self.resolution_scale + f32::EPSILON)
+                    .unwrap_or(SETTINGS_RESOLUTION_SCALES[0]);
+            }
+            SettingsOptionEnum::DamageNumbers => self.damage_numbers = !self.damage_numbers,
+            SettingsOptionEnum::ScreenShake => {
+                self.screen_shake += SETTINGS_SCREEN_SHAKE_STEP;
+                if self.screen_shake > SETTINGS_MAX_SCREEN_SHAKE + f32::EPSILON {
+                    self.screen_shake = 0.0;
+                }
+            }
+            SettingsOptionEnum::AutoShoot => self.auto_shoot = !self.auto_shoot,
+            SettingsOptionEnum::KeyBinding(_) => {}
+        }
+    }
+}
+
+/// Sounds that are played with the SFX volume.
+#[derive(Component)]
+pub struct SfxAudio;
+
+/// Sounds that are played with the music volume.
+#[derive(Component)]
+pub struct MusicAudio;
+
+/// Set while the settings menu waits for the key of this action.
+#[derive(Resource)]
+pub struct PendingKeyBinding(pub KeyActionEnum);
+
+pub fn load_settings(mut commands: Commands, registry: Res<PowerRegistry>) {
+    let mut settings = read_settings().unwrap_or_default();
+    settings.key_bindings.add_missing_powers(&registry);
+
+    commands.insert_resource(settings);
+}
+
+pub fn save_settings(settings: Res<Settings>) {
+    // Nothing to save when they were just loaded
+    if !settings.is_changed() || settings.is_added() {
+        return;
+    }
+
+    match ron::to_string(&*settings) {
+        Ok(text) => write_settings(&text),
+        Err(err) => error!("Could not serialize the settings: {err}"),
+    }
+}
+
+#[cfg(not(target_arch = "wasm32"))]
+fn read_settings() -> Option<Settings> {
+    let text = std::fs::read_to_string(get_settings_path()).ok()?;
+    ron::from_str(&text)
+        .inspect_err(|err| error!("Could not read the settings: {err}"))
+        .ok()
+}
+
+#[cfg(not(target_arch = "wasm32"))]
+fn write_settings(text: &str) {
+    if let Err(err) = std::fs::write(get_settings_path(), text) {
+        error!("Could not save the settings: {err}");
+    }
+}
+
+/// Next to the executable, so it does not depend on where the game is run
+/// from. The working directory is used if it can't be found.
+#[cfg(not(target_arch = "wasm32"))]
+fn get_settings_path() -> std::path::PathBuf {
+    std::env::current_exe()
+        .ok()
+        .and_then(|exe| exe.parent().map(|dir| dir.join(SETTINGS_FILE_NAME)))
+        .unwrap_or_else(|| SETTINGS_FILE_NAME.into())
+}
+
+#[cfg(target_arch = "wasm32")]
+fn read_settings() -> Option<Settings> {
+    let storage = web_sys::window()?.local_storage().ok()??;
+    let text = storage.get_item(SETTINGS_STORAGE_KEY).ok()??;
+    ron::from_str(&text)
+        .inspect_err(|err| error!("Could not read the settings: {err}"))
+        .ok()
+}
+
+#[cfg(target_arch = "wasm32")]
+fn write_settings(text: &str) {
+    let Some(Ok(Some(storage))) = web_sys::window().map(|window| window.local_storage()) else {
+        error!("Could not save the settings: there is no local storage");
+        return;
+    };
+
+    if storage.set_item(SETTINGS_STORAGE_KEY, text).is_err() {
+        error!("Could not save the settings on the local storage");
+    }
+}
+
+/// Applies the settings that are not read directly by the systems using
+/// them. The window is only changed when its own settings change, so a
+/// window resized by hand keeps its size.
+pub fn apply_settings(
+    settings: Res<Settings>,
+    mut applied_window_settings: Local<Option<(bool, f32)>>,
+    mut windows: Query<&mut Window, With<PrimaryWindow>>,
+    audio_sinks: Query<(&AudioSink, Has<MusicAudio>), Or<(With<SfxAudio>, With<MusicAudio>)>>,
+) {
+    if !settings.is_changed() {
+        return;
+    }
+
+    let window_settings = (settings.fullscreen, settings.resolution_scale);
+    if *applied_window_settings != Some(window_settings) {
+        if let Ok(mut window) = windows.get_single_mut() {
+            *applied_window_settings = Some(window_settings);
+
+            window.mode = if settings.fullscreen {
+                WindowMode::BorderlessFullscreen
+            } else {
+                WindowMode::Windowed
+            };
+
+            if !settings.fullscreen {
+                window.resolution.set(
+                    VIRTUAL_RESOLUTION.x_px * settings.resolution_scale,
+                    VIRTUAL_RESOLUTION.y_px * settings.resolution_scale,
+                );
+            }
+        }
+    }
+
+    for (audio_sink, is_music) in audio_sinks.iter() {
+        audio_sink.set_volume(get_audio_volume(&settings, is_music));
+    }
+}
+
+/// Sounds start with the volume of the settings.
+pub fn apply_audio_volume(
+    settings: Res<Settings>,
+    audio_sinks: Query<
+        (&AudioSink, Has<MusicAudio>),
+        (Added<AudioSink>, Or<(With<SfxAudio>, With<MusicAudio>)>),
+    >,
+) {
+    for (audio_sink, is_music) in audio_sinks.iter() {
+        audio_sink.set_volume(get_audio_volume(&settings, is_music));
+    }
+}
+
+fn get_audio_volume(settings: &Settings, is_music: bool) -> f32 {
+    let volume = if is_music {
+        settings.music_volume
+    } else {
+        settings.sfx_volume
+    };
+
+    settings.master_volume * volume
+}
+
+/// The next key pressed is bound to the action being changed, swapping it
+/// with the action that had it. Escape keeps the binding as it was.
+pub fn read_pending_key_binding(
+    mut commands: Commands,
+    keyboard_input: Res<ButtonInput<KeyCode>>,
+    pending_key_binding: Option<Res<PendingKeyBinding>>,
+    mut settings: ResMut<Settings>,
+) {
+    let Some(pending_key_binding) = pending_key_binding else {
+        return;
+    };
+
+    let Some(&key) = keyboard_input.get_just_pressed().next() else {
+        return;
+    };
+
+    // Keeps waiting for a key that can be bound
+    if SETTINGS_RESERVED_KEYS.contains(&key) {
+        return;
+    }
+
+    if key != KeyCode::Escape {
+        settings.key_bindings.set(pending_key_binding.0, key);
+    }
+
+    commands.remove_resource::<PendingKeyBinding>();
+}
@@ -1,6 +1,10 @@
 use crate::{
-    animation::AnimationInfo, prelude::*, util::get_background_texture_based_on_game_level,
-    CurrentGameLevel, SpritesResources,
+    animation::AnimationInfo,
+    generate_arena,
+    prelude::*,
+    spawn_tilemap,
+    util::{get_background_texture_based_on_game_level, get_tilemap_layout_based_on_game_level},
+    CurrentGameLevel, RunSeed, SpritesResources, Tilemap,
 };
 
 #[derive(Clone, Debug, Default)]
@@ -37,6 +41,7 @@ pub struct Sprites<'a> {
     pub level_1_bg: SpriteInfo<'a>,
     pub level_2_bg: SpriteInfo<'a>,
     pub level_3_bg: SpriteInfo<'a>,
+    pub tileset: SpriteInfo<'a>,
     // enemies
     pub orc_idle: SpriteInfo<'a>,
     pub mage_idle: SpriteInfo<'a>,
@@ -59,6 +64,17 @@ pub struct Sprites<'a> {
     pub mine_bomb: SpriteInfo<'a>,
     pub laser: SpriteInfo<'a>,
     pub circle_of_death: SpriteInfo<'a>,
+    // obstacles
+    pub rock: SpriteInfo<'a>,
+    pub crate_box: SpriteInfo<'a>,
+    pub digging_machine_back: SpriteInfo<'a>,
+    pub digging_machine_front: SpriteInfo<'a>,
+    // props
+    pub energy_pack: SpriteInfo<'a>,
+    pub glass_shards: SpriteInfo<'a>,
+    pub rock_shards: SpriteInfo<'a>,
+    // loot
+    pub coin: SpriteInfo<'a>,
 }
 
 #[derive(Component)]
@@ -78,6 +94,7 @@ pub fn setup_sprite(
     sprites: Res<SpritesResources>,
     asset_server: Res<AssetServer>,
     current_game_level: Res<CurrentGameLevel>,
+    run_seed: Res<RunSeed>,
 ) {
     render_background_texture(
         &mut commands,
@@ -85,6 +102,7 @@ pub fn setup_sprite(
         &asset_server,
         &sprites,
         current_game_level.0,
+        run_seed.0,
     );
 }
 
@@ -123,27 +141,23 @@ pub(crate) fn render_background_texture(
     asset_server: &Res<AssetServer>,
     sprites: &Res<SpritesResources>,
     game_level: u16,
+    run_seed: u64,
 ) {
     let tile = get_background_texture_based_on_game_level(game_level, sprites);
 
-    // number of tiles in a row
-    let x_items = BACKGROUND_TEXTURE_RESOLUTION.x_px / tile.dimensions.width as f32;
-    let x_items: u32 = x_items.ceil() as u32;
+    // The background covers the whole map
+    setup_tile_sprite(commands, texture_atlas_layout, 0., 0., tile, asset_server);
 
-    // number of tiles in a column
-    let y_items = BACKGROUND_TEXTURE_RESOLUTION.y_px / tile.dimensions.height as f32;
-    let y_items: u32 = y_items.ceil() as u32;
+    let tilemap = match get_tilemap_layout_based_on_game_level(game_level) {
+        Some(layout) => Tilemap::from_layout(layout),
+        None => generate_arena(run_seed, game_level),
+    };
 
-    for _ in 0..y_items {
-        for _ in 0..x_items {
-            setup_tile_sprite(
-                commands,
-                texture_atlas_layout,
-                0.,
-                0.,
-                tile.clone(),
-                asset_server,
-            );
-        }
-    }
+    spawn_tilemap(
+        commands,
+        texture_atlas_layout,
+        asset_server,
+        sprites,
+        tilemap,
+    );
 }
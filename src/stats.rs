@@ -27,18 +27,337 @@ pub struct Mana(pub f32);
 )]
 #[cfg_attr(not(feature = "web"), reflect(Component))]
 #[cfg_attr(feature = "web", derive(Component, Default, Debug, Clone))]
+pub struct Coins(pub u32);
+
+#[cfg_attr(
+    not(feature = "web"),
+    derive(Reflect, Component, Default, Debug, Clone, PartialEq)
+)]
+#[cfg_attr(not(feature = "web"), reflect(Component))]
+#[cfg_attr(feature = "web", derive(Component, Default, Debug, Clone, PartialEq))]
 pub struct Armor(pub f32);
 
 #[cfg_attr(
     not(feature = "web"),
-    derive(Reflect, Component, Default, Debug, Clone)
+    derive(Reflect, Component, Default, Debug, Clone, PartialEq)
 )]
 #[cfg_attr(not(feature = "web"), reflect(Component))]
-#[cfg_attr(feature = "web", derive(Component, Default, Debug, Clone))]
+#[cfg_attr(feature = "web", derive(Component, Default, Debug, Clone, PartialEq))]
 pub struct Speed(pub f32);
 
+// How far the player goes with each dash
+#[cfg_attr(
+    not(feature = "web"),
+    derive(Reflect, Component, Default, Debug, Clone, PartialEq)
+)]
+#[cfg_attr(not(feature = "web"), reflect(Component))]
+#[cfg_attr(feature = "web", derive(Component, Default, Debug, Clone, PartialEq))]
+pub struct DashDistance(pub f32);
+
+// How many dashes the player can do in a row
+#[cfg_attr(
+    not(feature = "web"),
+    derive(Reflect, Component, Default, Debug, Clone, PartialEq)
+)]
+#[cfg_attr(not(feature = "web"), reflect(Component))]
+#[cfg_attr(feature = "web", derive(Component, Default, Debug, Clone, PartialEq))]
+pub struct DashCharges(pub u32);
+
+// Health can not go above this
+#[cfg_attr(
+    not(feature = "web"),
+    derive(Reflect, Component, Default, Debug, Clone, PartialEq)
+)]
+#[cfg_attr(not(feature = "web"), reflect(Component))]
+#[cfg_attr(feature = "web", derive(Component, Default, Debug, Clone, PartialEq))]
+pub struct MaxHealth(pub f32);
+
+// Mana can not go above this
+#[cfg_attr(
+    not(feature = "web"),
+    derive(Reflect, Component, Default, Debug, Clone, PartialEq)
+)]
+#[cfg_attr(not(feature = "web"), reflect(Component))]
+#[cfg_attr(feature = "web", derive(Component, Default, Debug, Clone, PartialEq))]
+pub struct MaxMana(pub f32);
+
+// Health given back every second
+#[cfg_attr(
+    not(feature = "web"),
+    derive(Reflect, Component, Default, Debug, Clone, PartialEq)
+)]
+#[cfg_attr(not(feature = "web"), reflect(Component))]
+#[cfg_attr(feature = "web", derive(Component, Default, Debug, Clone, PartialEq))]
+pub struct HealthRegen(pub f32);
+
+// Mana given back every second
+#[cfg_attr(
+    not(feature = "web"),
+    derive(Reflect, Component, Default, Debug, Clone, PartialEq)
+)]
+#[cfg_attr(not(feature = "web"), reflect(Component))]
+#[cfg_attr(feature = "web", derive(Component, Default, Debug, Clone, PartialEq))]
+pub struct ManaRegen(pub f32);
+
+// Fraction of the damage dealt to the enemies that heals the player
+#[cfg_attr(
+    not(feature = "web"),
+    derive(Reflect, Component, Default, Debug, Clone, PartialEq)
+)]
+#[cfg_attr(not(feature = "web"), reflect(Component))]
+#[cfg_attr(feature = "web", derive(Component, Default, Debug, Clone, PartialEq))]
+pub struct LifeSteal(pub f32);
+
+// Makes drops more likely
+#[cfg_attr(
+    not(feature = "web"),
+    derive(Reflect, Component, Default, Debug, Clone, PartialEq)
+)]
+#[cfg_attr(not(feature = "web"), reflect(Component))]
+#[cfg_attr(feature = "web", derive(Component, Default, Debug, Clone, PartialEq))]
+pub struct Luck(pub f32);
+
 #[derive(Component, Clone)]
 pub struct Damage(pub f32);
 
 #[derive(Component, Clone)]
 pub struct Direction(pub Vec3);
+
+/*
+* Stats that can be changed by items, buffs, ... are computed every tick from
+* the base value plus the modifiers of the entity, instead of being mutated in
+* place.
+*
+* */
+
+#[cfg_attr(
+    not(feature = "web"),
+    derive(Reflect, Debug, Clone, Copy, PartialEq, Eq)
+)]
+#[cfg_attr(feature = "web", derive(Debug, Clone, Copy, PartialEq, Eq))]
+pub enum StatKind {
+    Speed,
+    Armor,
+    DashDistance,
+    DashCharges,
+    MaxHealth,
+    MaxMana,
+    HealthRegen,
+    ManaRegen,
+    LifeSteal,
+    Luck,
+}
+
+#[cfg_attr(not(feature = "web"), derive(Reflect, Debug, Clone, Copy, PartialEq))]
+#[cfg_attr(feature = "web", derive(Debug, Clone, Copy, PartialEq))]
+pub enum StatModifierOp {
+    Add(f32),
+    // Applied after all the additions
+    Multiply(f32),
+}
+
+#[cfg_attr(not(feature = "web"), derive(Reflect, Debug, Clone, Copy, PartialEq))]
+#[cfg_attr(feature = "web", derive(Debug, Clone, Copy, PartialEq))]
+pub enum StatModifierSource {
+    // e.g. "Speed item"
+    Named(&'static str),
+    // The shield buff group giving the armor, it goes away with it
+    Shield(Entity),
+}
+
+impl From<&'static str> for StatModifierSource {
+    fn from(name: &'static str) -> Self {
+        StatModifierSource::Named(name)
+    }
+}
+
+impl std::fmt::Display for StatModifierSource {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            StatModifierSource::Named(name) => write!(f, "{name}"),
+            StatModifierSource::Shield(_) => write!(f, "Shield"),
+        }
+    }
+}
+
+#[cfg_attr(not(feature = "web"), derive(Reflect, Debug, Clone))]
+#[cfg_attr(feature = "web", derive(Debug, Clone))]
+pub struct StatModifier {
+    pub stat: StatKind,
+    pub op: StatModifierOp,
+    pub source: StatModifierSource,
+    // None means it is never removed
+    pub expiry: Option<Timer>,
+}
+
+impl StatModifier {
+    pub fn new(stat: StatKind, op: StatModifierOp, source: impl Into<StatModifierSource>) -> Self {
+        Self {
+            stat,
+            op,
+            source: source.into(),
+            expiry: None,
+        }
+    }
+
+    pub fn with_duration(mut self, seconds: f32) -> Self {
+        self.expiry = Some(Timer::from_seconds(seconds, TimerMode::Once));
+        self
+    }
+}
+
+#[cfg_attr(
+    not(feature = "web"),
+    derive(Reflect, Component, Default, Debug, Clone)
+)]
+#[cfg_attr(not(feature = "web"), reflect(Component))]
+#[cfg_attr(feature = "web", derive(Component, Default, Debug, Clone))]
+pub struct BaseStats {
+    pub speed: f32,
+    pub armor: f32,
+    pub dash_distance: f32,
+    pub dash_charges: f32,
+    pub max_health: f32,
+    pub max_mana: f32,
+    pub health_regen: f32,
+    pub mana_regen: f32,
+    pub life_steal: f32,
+    pub luck: f32,
+}
+
+impl BaseStats {
+    pub fn get(&self, stat: StatKind) -> f32 {
+        match stat {
+            StatKind::Speed => self.speed,
+            StatKind::Armor => self.armor,
+            StatKind::DashDistance => self.dash_distance,
+            StatKind::DashCharges => self.dash_charges,
+            StatKind::MaxHealth => self.max_health,
+            StatKind::MaxMana => self.max_mana,
+            StatKind::HealthRegen => self.health_regen,
+            StatKind::ManaRegen => self.mana_regen,
+            StatKind::LifeSteal => self.life_steal,
+            StatKind::Luck => self.luck,
+        }
+    }
+}
+
+#[cfg_attr(
+    not(feature = "web"),
+    derive(Reflect, Component, Default, Debug, Clone)
+)]
+#[cfg_attr(not(feature = "web"), reflect(Component))]
+#[cfg_attr(feature = "web", derive(Component, Default, Debug, Clone))]
+pub struct StatModifiers(pub Vec<StatModifier>);
+
+impl StatModifiers {
+    pub fn add(&mut self, modifier: StatModifier) {
+        self.0.push(modifier);
+    }
+
+    pub fn remove_source(&mut self, source: StatModifierSource) {
+        self.0.retain(|modifier| modifier.source != source);
+    }
+
+    /// Base value plus every addition, times every multiplier.
+    pub fn compute(&self, stat: StatKind, base: f32) -> f32 {
+        let mut added = 0.;
+        let mut multiplier = 1.;
+
+        for modifier in self.0.iter().filter(|modifier| modifier.stat == stat) {
+            match modifier.op {
+                StatModifierOp::Add(value) => added += value,
+                StatModifierOp::Multiply(value) => multiplier *= value,
+            }
+        }
+
+        (base + added) * multiplier
+    }
+}
+
+pub fn apply_stat_modifiers(
+    time: Res<Time>,
+    mut query: Query<(
+        &BaseStats,
+        &mut StatModifiers,
+        &mut Speed,
+        &mut Armor,
+        Option<&mut DashDistance>,
+        Option<&mut DashCharges>,
+        Option<&mut MaxHealth>,
+        Option<&mut MaxMana>,
+        Option<&mut HealthRegen>,
+        Option<&mut ManaRegen>,
+        Option<&mut LifeSteal>,
+        Option<&mut Luck>,
+    )>,
+) {
+    for (
+        base_stats,
+        mut modifiers,
+        mut speed,
+        mut armor,
+        dash_distance,
+        dash_charges,
+        max_health,
+        max_mana,
+        health_regen,
+        mana_regen,
+        life_steal,
+        luck,
+    ) in query.iter_mut()
+    {
+        modifiers
+            .0
+            .retain_mut(|modifier| match modifier.expiry.as_mut() {
+                Some(expiry) => !expiry.tick(time.delta()).finished(),
+                None => true,
+            });
+
+        // Only the stats that changed are written, so `Changed` filters
+        // keep working on them
+        speed.set_if_neq(Speed(modifiers.compute(StatKind::Speed, base_stats.speed)));
+        armor.set_if_neq(Armor(modifiers.compute(StatKind::Armor, base_stats.armor)));
+
+        if let Some(mut dash_distance) = dash_distance {
+            dash_distance.set_if_neq(DashDistance(
+                modifiers.compute(StatKind::DashDistance, base_stats.dash_distance),
+            ));
+        }
+        if let Some(mut dash_charges) = dash_charges {
+            dash_charges.set_if_neq(DashCharges(
+                modifiers
+                    .compute(StatKind::DashCharges, base_stats.dash_charges)
+                    .floor() as u32,
+            ));
+        }
+        if let Some(mut max_health) = max_health {
+            max_health.set_if_neq(MaxHealth(
+                modifiers.compute(StatKind::MaxHealth, base_stats.max_health),
+            ));
+        }
+        if let Some(mut max_mana) = max_mana {
+            max_mana.set_if_neq(MaxMana(
+                modifiers.compute(StatKind::MaxMana, base_stats.max_mana),
+            ));
+        }
+        if let Some(mut health_regen) = health_regen {
+            health_regen.set_if_neq(HealthRegen(
+                modifiers.compute(StatKind::HealthRegen, base_stats.health_regen),
+            ));
+        }
+        if let Some(mut mana_regen) = mana_regen {
+            mana_regen.set_if_neq(ManaRegen(
+                modifiers.compute(StatKind::ManaRegen, base_stats.mana_regen),
+            ));
+        }
+        if let Some(mut life_steal) = life_steal {
+            life_steal.set_if_neq(LifeSteal(
+                modifiers.compute(StatKind::LifeSteal, base_stats.life_steal),
+            ));
+        }
+        if let Some(mut luck) = luck {
+            luck.set_if_neq(Luck(modifiers.compute(StatKind::Luck, base_stats.luck)));
+        }
+    }
+}
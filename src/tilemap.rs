@@ -0,0 +1,329 @@
+use crate::{prelude::*, spawn_obstacle, ObstacleKind, PropKind, SpritesResources, TileBackground};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TileKind {
+    Walkable,
+    // Nothing goes through it
+    Solid,
+    // Hurts the player standing on it
+    Hazard,
+    // Walkable, the enemies of the level appear on it
+    SpawnZone,
+}
+
+impl TileKind {
+    fn from_char(character: char) -> Self {
+        match character {
+            '#' => TileKind::Solid,
+            '~' => TileKind::Hazard,
+            'S' => TileKind::SpawnZone,
+            _ => TileKind::Walkable,
+        }
+    }
+}
+
+/// Layout of the current level, centered on the map. Everything outside of
+/// it is walkable.
+#[derive(Resource, Debug, Clone)]
+pub struct Tilemap {
+    pub columns: usize,
+    pub rows: usize,
+    tiles: Vec<TileKind>,
+    // Top left tile of each obstacle
+    obstacles: Vec<(usize, usize, ObstacleKind)>,
+    props: Vec<(usize, usize, PropKind)>,
+}
+
+impl Tilemap {
+    pub fn new(columns: usize, rows: usize, tiles: Vec<TileKind>) -> Self {
+        Self {
+            columns,
+            rows,
+            tiles,
+            obstacles: vec![],
+            props: vec![],
+        }
+    }
+
+    pub fn from_layout(layout: &str) -> Self {
+        let lines: Vec<&str> = layout
+            .lines()
+            .map(str::trim_end)
+            .filter(|line| !line.is_empty())
+            .collect();
+        let rows = lines.len();
+        let columns = lines
+            .iter()
+            .map(|line| line.chars().count())
+            .max()
+            .unwrap_or(0);
+
+        let mut tiles = vec![TileKind::Walkable; rows * columns];
+        let mut obstacles = vec![];
+        let mut props = vec![];
+        for (row, line) in lines.iter().enumerate() {
+            for (column, character) in line.chars().enumerate() {
+                tiles[row * columns + column] = TileKind::from_char(character);
+
+                if let Some(obstacle_kind) = ObstacleKind::from_char(character) {
+                    obstacles.push((column, row, obstacle_kind));
+                }
+                if let Some(prop_kind) = PropKind::from_char(character) {
+                    props.push((column, row, prop_kind));
+                }
+            }
+        }
+
+        let mut tilemap = Self::new(columns, rows, tiles);
+        for (column, row, obstacle_kind) in obstacles {
+            tilemap.add_obstacle(column, row, obstacle_kind);
+        }
+        tilemap.props = props;
+
+        tilemap
+    }
+
+    /// The tiles under the obstacle become solid.
+    pub fn add_obstacle(&mut self, column: usize, row: usize, obstacle_kind: ObstacleKind) {
+        let (width, height) = obstacle_kind.footprint();
+
+        for obstacle_row in row..(row + height).min(self.rows) {
+            for obstacle_column in column..(column + width).min(self.columns) {
+                self.tiles[obstacle_row * self.columns + obstacle_column] = TileKind::Solid;
+            }
+        }
+
+        self.obstacles.push((column, row, obstacle_kind));
+    }
+
+    pub fn is_under_obstacle(&self, column: usize, row: usize) -> bool {
+        self.obstacles
+            .iter()
+            .any(|&(obstacle_column, obstacle_row, obstacle_kind)| {
+                let (width, height) = obstacle_kind.footprint();
+                (obstacle_column..obstacle_column + width).contains(&column)
+                    && (obstacle_row..obstacle_row + height).contains(&row)
+            })
+    }
+
+    /// Every obstacle with the center of the tiles it covers.
+    pub fn obstacles(&self) -> impl Iterator<Item = (Vec2, ObstacleKind)> + '_ {
+        self.obstacles.iter().map(|&(column, row, obstacle_kind)| {
+            let (width, height) = obstacle_kind.footprint();
+            let offset = Vec2::new(width as f32 - 1., -(height as f32 - 1.)) / 2.;
+
+            (
+                self.tile_center(column, row) + offset * Self::tile_size(),
+                obstacle_kind,
+            )
+        })
+    }
+
+    pub fn add_prop(&mut self, column: usize, row: usize, prop_kind: PropKind) {
+        self.props.push((column, row, prop_kind));
+    }
+
+    /// Every prop with the center of its tile.
+    pub fn props(&self) -> impl Iterator<Item = (Vec2, PropKind)> + '_ {
+        self.props
+            .iter()
+            .map(|&(column, row, prop_kind)| (self.tile_center(column, row), prop_kind))
+    }
+
+    /// Size of each tile on the world.
+    pub fn tile_size() -> f32 {
+        TILE_SIZE_PX as f32 * BACKGROUND_TEXTURE_SCALE
+    }
+
+    fn top_left(&self) -> Vec2 {
+        Vec2::new(-(self.columns as f32), self.rows as f32) * Self::tile_size() / 2.
+    }
+
+    pub fn tile_center(&self, column: usize, row: usize) -> Vec2 {
+        self.top_left() + Vec2::new(column as f32 + 0.5, -(row as f32 + 0.5)) * Self::tile_size()
+    }
+
+    /// Every tile with its column and row.
+    pub fn tiles(&self) -> impl Iterator<Item = (usize, usize, TileKind)> + '_ {
+        self.tiles
+            .iter()
+            .enumerate()
+            .map(|(idx, &kind)| (idx % self.columns, idx / self.columns, kind))
+    }
+
+    /// Center of every spawn zone tile.
+    pub fn spawn_positions(&self) -> Vec<Vec2> {
+        self.tiles()
+            .filter(|&(_, _, kind)| kind == TileKind::SpawnZone)
+            .map(|(column, row, _)| self.tile_center(column, row))
+            .collect()
+    }
+
+    pub fn tile_at(&self, position: Vec2) -> TileKind {
+        let offset = (position - self.top_left()) / Self::tile_size();
+        let column = offset.x.floor();
+        let row = (-offset.y).floor();
+
+        if column < 0. || row < 0. || column >= self.columns as f32 || row >= self.rows as f32 {
+            return TileKind::Walkable;
+        }
+
+        self.tiles[row as usize * self.columns + column as usize]
+    }
+
+    pub fn is_solid(&self, position: Vec2) -> bool {
+        self.tile_at(position) == TileKind::Solid
+    }
+
+    /// Center of the closest tile that is not solid, `None` if every tile is.
+    pub fn closest_walkable_position(&self, position: Vec2) -> Option<Vec2> {
+        self.tiles()
+            .filter(|&(_, _, kind)| kind != TileKind::Solid)
+            .map(|(column, row, _)| self.tile_center(column, row))
+            .min_by(|a, b| {
+                a.distance_squared(position)
+                    .total_cmp(&b.distance_squared(position))
+            })
+    }
+
+    /// Whether nothing solid is in the way between both positions.
+    pub fn has_line_of_sight(&self, from: Vec2, to: Vec2) -> bool {
+        // A few samples per tile so corners are not skipped
+        let step = Self::tile_size() / 4.;
+        let samples = (from.distance(to) / step).ceil() as u32;
+
+        (1..samples).all(|sample| {
+            let position = from.lerp(to, sample as f32 / samples as f32);
+            !self.is_solid(position)
+        })
+    }
+
+    /// Turns the velocity away from a solid tile right ahead, trying both
+    /// sides a bit more each time. Keeps the velocity if there is no way
+    /// around.
+    pub fn steer_around_solids(&self, position: Vec2, velocity: Vec2) -> Vec2 {
+        let look_ahead = Self::tile_size();
+
+        if velocity == Vec2::ZERO || !self.is_solid(position + velocity.normalize() * look_ahead) {
+            return velocity;
+        }
+
+        for degrees in [45., -45., 90., -90., 135., -135.] {
+            let turned = Vec2::from_angle(degrees * DEGREES_TO_RADIANS).rotate(velocity);
+
+            if !self.is_solid(position + turned.normalize() * look_ahead) {
+                return turned;
+            }
+        }
+
+        velocity
+    }
+
+    /// Whether any tile of this kind is under the box.
+    pub fn overlaps(&self, center: Vec2, half_size: Vec2, kind: TileKind) -> bool {
+        let tile_size = Self::tile_size();
+        let min = center - half_size;
+        let max = center + half_size;
+
+        let mut y = min.y;
+        loop {
+            let mut x = min.x;
+            loop {
+                if self.tile_at(Vec2::new(x, y)) == kind {
+                    return true;
+                }
+                if x >= max.x {
+                    break;
+                }
+                x = (x + tile_size).min(max.x);
+            }
+            if y >= max.y {
+                break;
+            }
+            y = (y + tile_size).min(max.y);
+        }
+
+        false
+    }
+
+    /// Where a box moving from `from` to `to` ends up. Each axis is blocked
+    /// separately so it slides along the solid tiles. Something already
+    /// stuck on a solid tile is free to move out of it.
+    pub fn resolve_movement(&self, from: Vec2, to: Vec2, half_size: Vec2) -> Vec2 {
+        if self.overlaps(from, half_size, TileKind::Solid) {
+            return to;
+        }
+
+        let mut position = from;
+
+        if !self.overlaps(Vec2::new(to.x, position.y), half_size, TileKind::Solid) {
+            position.x = to.x;
+        }
+        if !self.overlaps(Vec2::new(position.x, to.y), half_size, TileKind::Solid) {
+            position.y = to.y;
+        }
+
+        position
+    }
+}
+
+/// Draws the solid and hazard tiles of the tilemap and makes it the current
+/// one.
+pub(crate) fn spawn_tilemap(
+    commands: &mut Commands,
+    texture_atlas_layout: &mut ResMut<Assets<TextureAtlasLayout>>,
+    asset_server: &Res<AssetServer>,
+    sprites: &Res<SpritesResources>,
+    tilemap: Tilemap,
+) {
+    let tileset = sprites.0.tileset.clone();
+    let texture = asset_server.load(tileset.source);
+    let tileset_layout = texture_atlas_layout.add(tileset.layout);
+
+    for (column, row, kind) in tilemap.tiles() {
+        // The obstacle is drawn instead
+        if tilemap.is_under_obstacle(column, row) {
+            continue;
+        }
+
+        let (index, z_offset) = match kind {
+            TileKind::Walkable | TileKind::SpawnZone => continue,
+            TileKind::Hazard => (HAZARD_TILE_INDEX, 0.1),
+            TileKind::Solid => (SOLID_TILE_INDEX, 0.2),
+        };
+
+        commands.spawn((
+            SpriteBundle {
+                texture: texture.clone(),
+                transform: Transform {
+                    translation: tilemap
+                        .tile_center(column, row)
+                        .extend(TILE_Z_INDEX + z_offset),
+                    scale: Vec3::splat(BACKGROUND_TEXTURE_SCALE),
+                    ..default()
+                },
+                ..default()
+            },
+            TextureAtlas {
+                layout: tileset_layout.clone(),
+                index,
+            },
+            TileBackground,
+            Name::new("Tile"),
+            BASE_LAYER,
+        ));
+    }
+
+    for (position, obstacle_kind) in tilemap.obstacles() {
+        spawn_obstacle(
+            commands,
+            texture_atlas_layout,
+            asset_server,
+            sprites,
+            obstacle_kind,
+            position,
+        );
+    }
+
+    commands.insert_resource(tilemap);
+}
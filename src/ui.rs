@@ -1,11 +1,16 @@
 use bevy::{
     color::palettes::css::YELLOW,
+    render::render_resource::{AsBindGroup, ShaderRef},
     sprite::{MaterialMesh2dBundle, Mesh2dHandle},
+    ui::FocusPolicy,
+    ui::UiMaterial,
 };
 
 use crate::{
-    prelude::*, CleanupWhenPlayerDies, CurrentGameLevel, CurrentScore, GameState,
-    ItemTypeEnum, PlayerProfileUISet, SpawnEntitiesForNewWave,
+    prelude::*, spawn_minimap_ui, util::get_key_name, BossIntro, CleanupWhenPlayerDies,
+    CurrentGameLevel, CurrentScore, GameState, ItemTypeEnum, KeyActionEnum, PendingKeyBinding,
+    Player, PlayerProfileUISet, Power, PowerRegistry, Settings, SettingsOptionEnum,
+    SpawnEntitiesForNewWave, StatModifierOp, StatModifiers,
 };
 
 // ############## UI ####################
@@ -50,16 +55,56 @@ pub struct PowerUIRootNode;
 
 #[derive(Component)]
 pub struct PowerUI {
-    pub power_type: PowerTypeEnum,
+    pub power_type: &'static str,
     pub power_level: usize,
 }
 
 #[derive(Component)]
 pub struct PowerSpriteUI;
 
+#[derive(Component)]
+pub struct PowerCooldownUI {
+    pub power_type: &'static str,
+}
+
+#[derive(Component)]
+pub struct PowerChargesUI {
+    pub power_type: &'static str,
+}
+
+/// Radial sweep drawn over the power icons while they are on cooldown.
+#[derive(AsBindGroup, Asset, TypePath, Debug, Clone)]
+pub struct PowerCooldownMaterial {
+    #[uniform(0)]
+    pub color: Vec4,
+    // Only `x` is used. It is a Vec4 so it is aligned on WebGL2.
+    #[uniform(1)]
+    pub progress: Vec4,
+}
+
+impl UiMaterial for PowerCooldownMaterial {
+    fn fragment_shader() -> ShaderRef {
+        "shaders/power_cooldown.wgsl".into()
+    }
+}
+
 #[derive(Component)]
 pub struct PowerLevelUI;
 
+// Name of the branch of the upgrade tree chosen for the power
+#[derive(Component)]
+pub struct PowerBranchUI;
+
+// Key bound to cast the power
+#[derive(Component)]
+pub struct PowerKeyUI(pub KeyActionEnum);
+
+#[derive(Component)]
+pub struct PowerUpgradeChoiceUI;
+
+#[derive(Component)]
+pub struct ChestRewardChoiceUI;
+
 #[derive(Component)]
 pub struct PlayerProfileUI;
 
@@ -69,6 +114,27 @@ pub struct PlayerProfileUIBarsRootNode;
 #[derive(Component)]
 pub struct PlayerStatsUI;
 
+#[derive(Component)]
+pub struct BossHealthBarUI {
+    pub boss_entity: Entity,
+}
+
+#[derive(Component)]
+pub struct BossHealthBarFillUI {
+    pub boss_entity: Entity,
+}
+
+#[derive(Component)]
+pub struct BossNameUI {
+    pub boss_entity: Entity,
+}
+
+#[derive(Component)]
+pub struct BossPhaseUI {
+    pub boss_entity: Entity,
+    pub number_of_phases: usize,
+}
+
 // ############## BUTTONS ####################
 #[derive(Component)]
 pub struct PlayAgainButton;
@@ -79,6 +145,18 @@ pub struct StartGameButton;
 #[derive(Component)]
 pub struct RestartGameButton;
 
+#[derive(Component)]
+pub struct SettingsButton;
+
+#[derive(Component)]
+pub struct SettingsCloseButton;
+
+#[derive(Component)]
+pub struct SettingsOptionButton(pub SettingsOptionEnum);
+
+#[derive(Component)]
+pub struct SettingsOptionText(pub SettingsOptionEnum);
+
 // ############## SCREENS ####################
 #[derive(Component)]
 pub struct MenuOverlay;
@@ -92,6 +170,18 @@ pub struct GameWonOverlay;
 #[derive(Component)]
 pub struct InBetweenLevelsPauseOverlay;
 
+#[derive(Component)]
+pub struct BossIntroOverlay;
+
+#[derive(Component)]
+pub struct SettingsMenu;
+
+// Floats up from where an enemy was hit
+#[derive(Component)]
+pub struct DamageNumber {
+    pub timer: Timer,
+}
+
 const MAX_VALUE_BAR: f32 = 100.0;
 const BAR_SCALE: f32 = 0.2;
 const BAR_UI_SCALE: f32 = 1.5;
@@ -275,6 +365,133 @@ fn spawn_ui_bar<T: Component>(
     }
 }
 
+/// Screen-wide health bar shown at the top of the screen while a boss is alive.
+/// Each phase after the first one gets a marker at its health threshold.
+pub(crate) fn spawn_boss_health_bar_ui(
+    commands: &mut Commands,
+    asset_server: &Res<AssetServer>,
+    boss_entity: Entity,
+    moveset: &BossMoveset,
+) {
+    let font = asset_server.load("fonts/FiraSans-Bold.ttf");
+    let text_style = TextStyle {
+        font: font.clone(),
+        font_size: 25.0,
+        color: Color::WHITE,
+    };
+    let number_of_phases = moveset.phases.len();
+
+    let name_text = (
+        TextBundle::from_section(moveset.name, text_style.clone()),
+        BossNameUI { boss_entity },
+        OVERLAY_LAYER,
+    );
+
+    let phase_text = (
+        TextBundle::from_section(
+            format!("Phase 1/{}", number_of_phases),
+            TextStyle {
+                font_size: 20.0,
+                color: Color::Srgba(YELLOW),
+                ..text_style
+            },
+        ),
+        BossPhaseUI {
+            boss_entity,
+            number_of_phases,
+        },
+        OVERLAY_LAYER,
+    );
+
+    let bar_fill = (
+        NodeBundle {
+            style: Style {
+                width: Val::Percent(100.),
+                height: Val::Percent(100.),
+                ..default()
+            },
+            background_color: BackgroundColor(Color::srgb(0.75, 0.1, 0.1)),
+            ..default()
+        },
+        BossHealthBarFillUI { boss_entity },
+        OVERLAY_LAYER,
+    );
+
+    commands
+        .spawn((
+            NodeBundle {
+                style: Style {
+                    width: Val::Percent(BOSS_HEALTH_BAR_WIDTH_PERCENT),
+                    position_type: PositionType::Absolute,
+                    top: Val::Px(80.),
+                    left: Val::Percent((100. - BOSS_HEALTH_BAR_WIDTH_PERCENT) / 2.),
+                    flex_direction: FlexDirection::Column,
+                    row_gap: Val::Px(4.),
+                    ..default()
+                },
+                ..default()
+            },
+            BossHealthBarUI { boss_entity },
+            OVERLAY_LAYER,
+            CleanupWhenPlayerDies,
+        ))
+        .with_children(|parent| {
+            parent
+                .spawn((
+                    NodeBundle {
+                        style: Style {
+                            width: Val::Percent(100.),
+                            justify_content: JustifyContent::SpaceBetween,
+                            ..default()
+                        },
+                        ..default()
+                    },
+                    OVERLAY_LAYER,
+                ))
+                .with_children(|parent| {
+                    parent.spawn(name_text);
+                    parent.spawn(phase_text);
+                });
+
+            parent
+                .spawn((
+                    NodeBundle {
+                        style: Style {
+                            width: Val::Percent(100.),
+                            height: Val::Px(BOSS_HEALTH_BAR_HEIGHT_PX),
+                            border: UiRect::all(Val::Px(2.)),
+                            ..default()
+                        },
+                        background_color: BackgroundColor(Color::srgb(0.1, 0.1, 0.1)),
+                        border_color: BorderColor(Color::WHITE),
+                        ..default()
+                    },
+                    OVERLAY_LAYER,
+                ))
+                .with_children(|parent| {
+                    parent.spawn(bar_fill);
+
+                    // The first phase starts at full health, so it has no marker
+                    for phase in moveset.phases.iter().skip(1) {
+                        parent.spawn((
+                            NodeBundle {
+                                style: Style {
+                                    width: Val::Px(2.),
+                                    height: Val::Percent(100.),
+                                    position_type: PositionType::Absolute,
+                                    left: Val::Percent(phase.health_threshold * 100.),
+                                    ..default()
+                                },
+                                background_color: BackgroundColor(Color::WHITE),
+                                ..default()
+                            },
+                            OVERLAY_LAYER,
+                        ));
+                    }
+                });
+        });
+}
+
 fn current_wave(commands: &mut Commands, asset_server: &Res<AssetServer>) {
     let font = asset_server.load("fonts/FiraSans-Bold.ttf");
     let text_style = TextStyle {
@@ -637,9 +854,11 @@ pub(crate) fn spawn_power_ui_root_node(commands: &mut Commands) {
 pub(crate) fn spawn_power_ui(
     commands: &mut Commands,
     asset_server: &Res<AssetServer>,
+    cooldown_materials: &mut Assets<PowerCooldownMaterial>,
     sprite_source: &str,
-    power_type: PowerTypeEnum,
-    keycode: KeyCode,
+    power_type: &'static str,
+    key_action: KeyActionEnum,
+    key: KeyCode,
 ) -> Entity {
     let parent = commands
         .spawn((
@@ -678,6 +897,26 @@ pub(crate) fn spawn_power_ui(
         ))
         .id();
 
+    let cooldown_ui_id = commands
+        .spawn((
+            MaterialNodeBundle {
+                style: Style {
+                    position_type: PositionType::Absolute,
+                    width: Val::Percent(100.),
+                    height: Val::Percent(100.),
+                    ..default()
+                },
+                material: cooldown_materials.add(PowerCooldownMaterial {
+                    color: Vec4::new(0.0, 0.0, 0.0, 0.7),
+                    progress: Vec4::ONE,
+                }),
+                ..default()
+            },
+            OVERLAY_LAYER,
+            PowerCooldownUI { power_type },
+        ))
+        .id();
+
     let font = asset_server.load("fonts/FiraSans-Bold.ttf");
     let text_style = TextStyle {
         font: font.clone(),
@@ -685,6 +924,23 @@ pub(crate) fn spawn_power_ui(
         ..default()
     };
 
+    let charges_ui_id = commands
+        .spawn((
+            TextBundle {
+                text: Text::from_section("", text_style.clone()),
+                style: Style {
+                    position_type: PositionType::Absolute,
+                    top: Val::Px(1.5),
+                    left: Val::Px(3.),
+                    ..default()
+                },
+                ..default()
+            },
+            OVERLAY_LAYER,
+            PowerChargesUI { power_type },
+        ))
+        .id();
+
     let power_level_ui_id = commands
         .spawn((
             TextBundle {
@@ -703,17 +959,34 @@ pub(crate) fn spawn_power_ui(
         ))
         .id();
 
-    let keycode_string = match keycode {
-        KeyCode::KeyH => "H",
-        KeyCode::KeyJ => "J",
-        KeyCode::KeyL => "L",
-        _ => unimplemented!(),
-    };
+    let power_branch_ui_id = commands
+        .spawn((
+            TextBundle {
+                text: Text::from_section(
+                    "",
+                    TextStyle {
+                        font: font.clone(),
+                        font_size: 12.0,
+                        ..default()
+                    },
+                ),
+                style: Style {
+                    position_type: PositionType::Absolute,
+                    bottom: Val::Px(62.),
+                    left: Val::Px(0.),
+                    ..default()
+                },
+                ..default()
+            },
+            OVERLAY_LAYER,
+            PowerBranchUI,
+        ))
+        .id();
 
     let keycode_ui_id = commands
         .spawn((
             TextBundle {
-                text: Text::from_section(keycode_string.to_string(), text_style),
+                text: Text::from_section(get_key_name(key), text_style),
                 style: Style {
                     position_type: PositionType::Relative,
                     // TODO: get rid of magic numbers
@@ -724,17 +997,181 @@ pub(crate) fn spawn_power_ui(
                 ..default()
             },
             OVERLAY_LAYER,
+            PowerKeyUI(key_action),
         ))
         .id();
 
     let child_id = commands
         .entity(sprite_ui_id)
+        .add_child(cooldown_ui_id)
+        .add_child(charges_ui_id)
         .add_child(power_level_ui_id)
+        .add_child(power_branch_ui_id)
         .add_child(keycode_ui_id)
         .id();
     commands.entity(parent).add_child(child_id).id()
 }
 
+/// Shown on top of the in-between levels pause screen until the player picks
+/// one of the upgrades with the number keys.
+pub(crate) fn spawn_power_upgrade_choice_ui(
+    commands: &mut Commands,
+    asset_server: &Res<AssetServer>,
+    power_type: &'static str,
+    choices: &'static [PowerUpgrade],
+) {
+    let title = commands
+        .spawn(_build_custom_text_bundle(
+            asset_server,
+            &format!("Upgrade {}", power_type.replace('_', " ")),
+            40.,
+            Color::WHITE,
+        ))
+        .id();
+
+    let mut children = vec![title];
+    for (idx, choice) in choices.iter().enumerate() {
+        let choice_text = commands
+            .spawn(_build_custom_text_bundle(
+                asset_server,
+                &format!("[{}] {}: {}", idx + 1, choice.name, choice.description),
+                25.,
+                Color::srgb(0.9, 0.9, 0.9),
+            ))
+            .id();
+        children.push(choice_text);
+    }
+
+    commands
+        .spawn((
+            NodeBundle {
+                style: Style {
+                    position_type: PositionType::Absolute,
+                    width: Val::Percent(100.),
+                    bottom: Val::Percent(15.),
+                    flex_direction: FlexDirection::Column,
+                    align_items: AlignItems::Center,
+                    row_gap: Val::Px(10.),
+                    ..default()
+                },
+                z_index: ZIndex::Global(1),
+                ..default()
+            },
+            MENU_UI_LAYER,
+            PowerUpgradeChoiceUI,
+            CleanupWhenPlayerDies,
+        ))
+        .push_children(&children);
+}
+
+pub(crate) fn spawn_chest_reward_choice_ui(
+    commands: &mut Commands,
+    asset_server: &Res<AssetServer>,
+    choices: &[ChestReward],
+) {
+    let title = commands
+        .spawn(_build_custom_text_bundle(
+            asset_server,
+            "Choose a reward",
+            40.,
+            Color::WHITE,
+        ))
+        .id();
+
+    let mut children = vec![title];
+    for (idx, choice) in choices.iter().enumerate() {
+        let choice_text = commands
+            .spawn(_build_custom_text_bundle(
+                asset_server,
+                &format!("[{}] {}: {}", idx + 1, choice.name, choice.description),
+                25.,
+                Color::srgb(0.9, 0.9, 0.9),
+            ))
+            .id();
+        children.push(choice_text);
+    }
+
+    commands
+        .spawn((
+            NodeBundle {
+                style: Style {
+                    position_type: PositionType::Absolute,
+                    width: Val::Percent(100.),
+                    bottom: Val::Percent(15.),
+                    flex_direction: FlexDirection::Column,
+                    align_items: AlignItems::Center,
+                    row_gap: Val::Px(10.),
+                    ..default()
+                },
+                z_index: ZIndex::Global(1),
+                ..default()
+            },
+            OVERLAY_LAYER,
+            ChestRewardChoiceUI,
+            CleanupWhenPlayerDies,
+        ))
+        .push_children(&children);
+}
+
+pub fn update_power_cooldown_ui(
+    mut cooldown_materials: ResMut<Assets<PowerCooldownMaterial>>,
+    player_query: Query<&Children, With<Player>>,
+    powers: Query<&Power>,
+    cooldown_ui_query: Query<(&Handle<PowerCooldownMaterial>, &PowerCooldownUI)>,
+    mut charges_ui_query: Query<(&mut Text, &PowerChargesUI)>,
+) {
+    let Ok(player_children) = player_query.get_single() else {
+        return;
+    };
+
+    for &child in player_children {
+        let Ok(power) = powers.get(child) else {
+            continue;
+        };
+
+        for (material_handle, cooldown_ui) in cooldown_ui_query.iter() {
+            if cooldown_ui.power_type != power.power_type {
+                continue;
+            }
+
+            // The icon is only fully darkened when there are no charges left
+            let progress = power.cooldown_fraction();
+            let alpha = if power.is_ready() { 0.3 } else { 0.7 };
+
+            // Changing the material makes it upload again, so it is only
+            // done when it looks different
+            let Some(material) = cooldown_materials.get(material_handle) else {
+                continue;
+            };
+            if material.progress.x == progress && material.color.w == alpha {
+                continue;
+            }
+
+            if let Some(material) = cooldown_materials.get_mut(material_handle) {
+                material.progress.x = progress;
+                material.color.w = alpha;
+            }
+        }
+
+        for (mut charges_text, charges_ui) in charges_ui_query.iter_mut() {
+            if charges_ui.power_type != power.power_type {
+                continue;
+            }
+
+            // Powers with a single charge do not need the counter
+            let charges = if power.max_charges > 1 {
+                format!("{}", power.charges)
+            } else {
+                String::new()
+            };
+
+            if charges_text.sections[0].value != charges {
+                charges_text.sections[0].value = charges;
+            }
+        }
+    }
+}
+
 pub fn spawn_player_stats_ui(
     commands: &mut Commands,
     asset_server: &Res<AssetServer>,
@@ -747,6 +1184,8 @@ pub fn spawn_player_stats_ui(
 
     current_armor_value: f32,
     current_speed_value: f32,
+
+    stat_modifiers: &StatModifiers,
 ) {
     let parent = commands
         .spawn((
@@ -755,7 +1194,7 @@ pub fn spawn_player_stats_ui(
                     flex_direction: FlexDirection::Column,
                     display: Display::Flex,
                     width: Val::Px(400.),
-                    height: Val::Px(400.),
+                    min_height: Val::Px(400.),
                     position_type: PositionType::Absolute,
                     top: Val::Px(120.),
                     left: Val::Px(10.),
@@ -890,9 +1329,46 @@ pub fn spawn_player_stats_ui(
         .add_child(speed_text_node)
         .id();
 
+    // Where every bonus comes from
+    let modifiers_node = commands
+        .spawn(NodeBundle {
+            style: Style {
+                flex_direction: FlexDirection::Column,
+                padding: UiRect::vertical(Val::Px(10.)),
+                row_gap: Val::Px(2.0),
+                ..default()
+            },
+            ..default()
+        })
+        .with_children(|parent| {
+            for modifier in &stat_modifiers.0 {
+                let value = match modifier.op {
+                    StatModifierOp::Add(value) => format!("{value:+.1}"),
+                    StatModifierOp::Multiply(value) => format!("x{value:.2}"),
+                };
+                let remaining = match &modifier.expiry {
+                    Some(expiry) => format!(", {:.0}s", expiry.remaining_secs()),
+                    None => String::new(),
+                };
+
+                parent.spawn(TextBundle::from_section(
+                    format!(
+                        "{value} {:?} ({}{remaining})",
+                        modifier.stat, modifier.source
+                    ),
+                    TextStyle {
+                        font: asset_server.load("fonts/FiraSans-Bold.ttf"),
+                        font_size: 18.0,
+                        ..default()
+                    },
+                ));
+            }
+        })
+        .id();
+
     commands
         .entity(parent)
-        .push_children(&[player, weapon, armor, speed]);
+        .push_children(&[player, weapon, armor, speed, modifiers_node]);
 }
 
 pub fn setup_ui(mut commands: Commands, asset_server: Res<AssetServer>) {
@@ -903,6 +1379,7 @@ pub fn setup_ui(mut commands: Commands, asset_server: Res<AssetServer>) {
     spawn_container_buffs_ui(&mut commands);
     spawn_power_ui_root_node(&mut commands);
     alive_enemies_left(&mut commands, &asset_server);
+    spawn_minimap_ui(&mut commands);
 }
 
 pub fn menu_screen(mut commands: Commands, asset_server: Res<AssetServer>) {
@@ -931,10 +1408,12 @@ pub fn menu_screen(mut commands: Commands, asset_server: Res<AssetServer>) {
         })
         .id();
 
+    let three = _spawn_settings_button(&mut commands, &asset_server);
+
     _default_screen(
         &mut commands,
         MenuOverlay,
-        vec![one, two],
+        vec![one, two, three],
         Color::srgb(0.1, 0.1, 0.1).into(),
     );
 }
@@ -1034,38 +1513,284 @@ pub fn game_won_screen(
 }
 
 pub fn in_between_levels_pause_screen(
-    commands: Commands,
+    mut commands: Commands,
     asset_server: Res<AssetServer>,
     current_game_level: Res<CurrentGameLevel>,
 ) {
     let title = &format!("Level #{}!", current_game_level.0);
-    _pause_screen(commands, asset_server, title, InBetweenLevelsPauseOverlay);
+    let settings_button = _spawn_settings_button(&mut commands, &asset_server);
+    _pause_screen(
+        &mut commands,
+        &asset_server,
+        title,
+        InBetweenLevelsPauseOverlay,
+        vec![settings_button],
+    );
 }
 
-fn _pause_screen<T: Component>(
+pub fn boss_intro_screen(
     mut commands: Commands,
     asset_server: Res<AssetServer>,
+    boss_intro: Res<BossIntro>,
+) {
+    _pause_screen(
+        &mut commands,
+        &asset_server,
+        boss_intro.boss_name,
+        BossIntroOverlay,
+        vec![],
+    );
+}
+
+fn _pause_screen<T: Component>(
+    commands: &mut Commands,
+    asset_server: &Res<AssetServer>,
     title: &str,
     entity: T,
+    other_children: Vec<Entity>,
 ) {
     let font_size = 100.;
     let one = commands
         .spawn(_build_custom_text_bundle(
-            &asset_server,
+            asset_server,
             title,
             font_size,
             Color::WHITE,
         ))
         .id();
 
+    let mut children = vec![one];
+    children.extend(other_children);
+
     let _ = _default_screen(
-        &mut commands,
+        commands,
         entity,
-        vec![one],
+        children,
         Color::srgba(0.1, 0.1, 0.1, 0.1).into(),
     );
 }
 
+fn _spawn_settings_button(commands: &mut Commands, asset_server: &Res<AssetServer>) -> Entity {
+    commands
+        .spawn(_build_custom_button(SettingsButton))
+        .with_children(|parent| {
+            parent.spawn(_build_custom_text_bundle(
+                asset_server,
+                "Settings",
+                40.,
+                Color::srgb(0.9, 0.9, 0.9),
+            ));
+        })
+        .id()
+}
+
+/// Shown on top of the main menu or the pause screen. Every option is a
+/// button that goes to its next value when clicked.
+pub(crate) fn spawn_settings_menu(
+    commands: &mut Commands,
+    asset_server: &Res<AssetServer>,
+    settings: &Settings,
+    registry: &PowerRegistry,
+) {
+    let title = commands
+        .spawn(_build_custom_text_bundle(
+            asset_server,
+            "SETTINGS",
+            60.,
+            Color::WHITE,
+        ))
+        .id();
+
+    let key_binding_options: Vec<SettingsOptionEnum> = KeyActionEnum::ALL
+        .into_iter()
+        .chain(
+            registry
+                .iter()
+                .map(|behaviour| KeyActionEnum::Power(behaviour.power_type())),
+        )
+        .map(SettingsOptionEnum::KeyBinding)
+        .collect();
+    let columns: Vec<Entity> = [&SettingsOptionEnum::GENERAL[..], &key_binding_options[..]]
+        .iter()
+        .map(|options| {
+            let option_buttons: Vec<Entity> = options
+                .iter()
+                .map(|&option| {
+                    commands
+                        .spawn((
+                            ButtonBundle {
+                                style: Style {
+                                    width: Val::Px(320.0),
+                                    height: Val::Px(40.0),
+                                    border: UiRect::all(Val::Px(1.0)),
+                                    justify_content: JustifyContent::Center,
+                                    align_items: AlignItems::Center,
+                                    ..default()
+                                },
+                                border_color: BorderColor(Color::BLACK),
+                                border_radius: BorderRadius::MAX,
+                                background_color: Color::BLACK.into(),
+                                ..default()
+                            },
+                            SettingsOptionButton(option),
+                            MENU_UI_LAYER,
+                        ))
+                        .with_children(|parent| {
+                            parent.spawn((
+                                _build_custom_text_bundle(
+                                    asset_server,
+                                    &option.label(settings, None),
+                                    22.,
+                                    Color::srgb(0.9, 0.9, 0.9),
+                                ),
+                                SettingsOptionText(option),
+                            ));
+                        })
+                        .id()
+                })
+                .collect();
+
+            commands
+                .spawn((
+                    NodeBundle {
+                        style: Style {
+                            flex_direction: FlexDirection::Column,
+                            row_gap: Val::Px(8.),
+                            ..default()
+                        },
+                        ..default()
+                    },
+                    MENU_UI_LAYER,
+                ))
+                .push_children(&option_buttons)
+                .id()
+        })
+        .collect();
+
+    let options = commands
+        .spawn((
+            NodeBundle {
+                style: Style {
+                    flex_direction: FlexDirection::Row,
+                    column_gap: Val::Px(30.),
+                    margin: UiRect::vertical(Val::Px(20.)),
+                    ..default()
+                },
+                ..default()
+            },
+            MENU_UI_LAYER,
+        ))
+        .push_children(&columns)
+        .id();
+
+    let close_button = commands
+        .spawn(_build_custom_button(SettingsCloseButton))
+        .with_children(|parent| {
+            parent.spawn(_build_custom_text_bundle(
+                asset_server,
+                "Back",
+                40.,
+                Color::srgb(0.9, 0.9, 0.9),
+            ));
+        })
+        .id();
+
+    let settings_menu = _default_screen(
+        commands,
+        SettingsMenu,
+        vec![title, options, close_button],
+        Color::srgba(0.1, 0.1, 0.1, 0.95).into(),
+    );
+
+    // On top of the screen it was opened from, which can't be clicked
+    commands
+        .entity(settings_menu)
+        .insert((ZIndex::Global(10), FocusPolicy::Block));
+}
+
+/// The keys of the powers follow the key bindings.
+pub fn update_power_key_ui(
+    settings: Res<Settings>,
+    mut power_key_texts: Query<(&mut Text, &PowerKeyUI)>,
+) {
+    if !settings.is_changed() {
+        return;
+    }
+
+    for (mut text, power_key) in power_key_texts.iter_mut() {
+        let key_name = get_key_name(settings.key_bindings.get(power_key.0));
+
+        if text.sections[0].value != key_name {
+            text.sections[0].value = key_name;
+        }
+    }
+}
+
+pub fn update_settings_menu_text(
+    settings: Res<Settings>,
+    pending_key_binding: Option<Res<PendingKeyBinding>>,
+    mut option_texts: Query<(&mut Text, &SettingsOptionText)>,
+) {
+    let pending_action = pending_key_binding.map(|pending| pending.0);
+
+    for (mut text, option_text) in option_texts.iter_mut() {
+        let label = option_text.0.label(&settings, pending_action);
+
+        if text.sections[0].value != label {
+            text.sections[0].value = label;
+        }
+    }
+}
+
+pub(crate) fn spawn_damage_number(
+    commands: &mut Commands,
+    asset_server: &Res<AssetServer>,
+    damage: f32,
+    position: Vec2,
+) {
+    let offset = Vec2::new(rand::random::<f32>() - 0.5, rand::random::<f32>() - 0.5)
+        * 2.
+        * DAMAGE_NUMBER_SPREAD;
+
+    commands.spawn((
+        Text2dBundle {
+            text: Text::from_section(
+                format!("{:.0}", damage),
+                _build_text_style(asset_server, DAMAGE_NUMBER_FONT_SIZE, Color::WHITE),
+            ),
+            transform: Transform::from_translation((position + offset).extend(UI_Z_INDEX)),
+            ..default()
+        },
+        DamageNumber {
+            timer: Timer::from_seconds(DAMAGE_NUMBER_SECONDS, TimerMode::Once),
+        },
+        BASE_LAYER,
+        CleanupWhenPlayerDies,
+    ));
+}
+
+pub fn float_damage_numbers(
+    mut commands: Commands,
+    time: Res<Time>,
+    mut damage_numbers: Query<(Entity, &mut Transform, &mut Text, &mut DamageNumber)>,
+) {
+    for (entity, mut transform, mut text, mut damage_number) in damage_numbers.iter_mut() {
+        damage_number.timer.tick(time.delta());
+
+        if damage_number.timer.finished() {
+            commands.entity(entity).despawn();
+            continue;
+        }
+
+        transform.translation.y += DAMAGE_NUMBER_RISE_SPEED * time.delta_seconds();
+
+        let alpha = 1. - damage_number.timer.fraction();
+        for section in text.sections.iter_mut() {
+            section.style.color.set_alpha(alpha);
+        }
+    }
+}
+
 pub fn despawn_in_between_levels_pause_screen(
     commands: Commands,
     query: Query<Entity, With<InBetweenLevelsPauseOverlay>>,
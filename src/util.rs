@@ -1,6 +1,6 @@
 use crate::{
-    prelude::*, Armor, Health, Invisibility, ItemTypeEnum, Mana, PowerTypeEnum, Shield, Speed,
-    SpriteInfo, SpritesResources,
+    prelude::*, Armor, Coins, Health, Invisibility, ItemTypeEnum, Mana, ObstacleKind, PropKind,
+    Shield, Speed, SpriteInfo, SpritesResources,
 };
 use rand::{Rng, SeedableRng};
 use rand_chacha::ChaCha8Rng;
@@ -43,6 +43,15 @@ pub(crate) fn get_unit_vector(vec: Vec2) -> Vec2 {
 
     Vec2::new(normalized_direction_x, normalized_direction_y)
 }
+/// `KeyCode::KeyH` is shown as `H`.
+pub(crate) fn get_key_name(key: KeyCode) -> String {
+    if key == SETTINGS_UNBOUND_KEY {
+        return "-".to_string();
+    }
+
+    format!("{:?}", key).trim_start_matches("Key").to_string()
+}
+
 pub(crate) fn get_random_vec3(increment: u64, seed: Option<u64>) -> Vec3 {
     let random_seed = seed.unwrap_or(ENEMY_RANDOM_SEED);
     let mut rng = ChaCha8Rng::seed_from_u64(random_seed + increment);
@@ -85,26 +94,50 @@ pub(crate) fn get_item_sprite_based_on_item_type(
         ItemTypeEnum::Health(_) => sprites.0.hp_pack.clone(),
         ItemTypeEnum::Mana(_) => sprites.0.mana_potion.clone(),
         ItemTypeEnum::Invisibility(_) => sprites.0.invisibility.clone(),
+        ItemTypeEnum::Coins(_) => sprites.0.coin.clone(),
     }
 }
 
-pub(crate) fn get_power_sprite_based_on_power_type(
-    power_type: PowerTypeEnum,
-    sprites: &Res<SpritesResources>,
-) -> SpriteInfo<'static> {
-    match power_type {
-        PowerTypeEnum::Explosions => sprites.0.mine_bomb.clone(),
-        PowerTypeEnum::CircleOfDeath => sprites.0.circle_of_death.clone(),
-        PowerTypeEnum::Laser => sprites.0.laser.clone(),
-    }
+pub(crate) fn get_cursor_world_position(
+    window: &Window,
+    camera: &Camera,
+    camera_transform: &GlobalTransform,
+) -> Option<Vec2> {
+    // The cursor is on the window, but the camera only draws on its viewport
+    let viewport_min = camera
+        .logical_viewport_rect()
+        .map_or(Vec2::ZERO, |viewport| viewport.min);
+
+    window
+        .cursor_position()
+        .and_then(|cursor| camera.viewport_to_world(camera_transform, cursor - viewport_min))
+        .map(|ray| ray.origin.truncate())
 }
 
-pub(crate) fn get_key_code_based_on_power_type(power_type: PowerTypeEnum) -> KeyCode {
-    match power_type {
-        PowerTypeEnum::Explosions => KeyCode::KeyL,
-        PowerTypeEnum::CircleOfDeath => KeyCode::KeyJ,
-        PowerTypeEnum::Laser => KeyCode::KeyH,
+/// Separating axis test between a rectangle rotated by `angle` and an AABB.
+pub(crate) fn oriented_rectangle_intersects_aabb(
+    center: Vec2,
+    half_size: Vec2,
+    angle: f32,
+    aabb: &Aabb2d,
+) -> bool {
+    let rotation = Rot2::radians(angle);
+    let rectangle_axes = [rotation * Vec2::X, rotation * Vec2::Y];
+    let aabb_center = (aabb.min + aabb.max) / 2.;
+    let aabb_half_size = (aabb.max - aabb.min) / 2.;
+    let distance = aabb_center - center;
+
+    for axis in [Vec2::X, Vec2::Y, rectangle_axes[0], rectangle_axes[1]] {
+        let rectangle_radius = half_size.x * rectangle_axes[0].dot(axis).abs()
+            + half_size.y * rectangle_axes[1].dot(axis).abs();
+        let aabb_radius = aabb_half_size.x * axis.x.abs() + aabb_half_size.y * axis.y.abs();
+
+        if distance.dot(axis).abs() > rectangle_radius + aabb_radius {
+            return false;
+        }
     }
+
+    true
 }
 
 pub(crate) fn get_enemy_sprite_based_on_enemy_class(
@@ -133,6 +166,34 @@ pub(crate) fn get_boss_type_based_on_game_level(game_level: u16) -> EnemyType {
     }
 }
 
+pub(crate) fn get_boss_moveset_based_on_enemy_class(
+    enemy_class: EnemyClassEnum,
+) -> Option<BossMoveset> {
+    match enemy_class {
+        EnemyClassEnum::BossOrc => Some(BOSS_ORC_MOVESET),
+        EnemyClassEnum::BossMage => Some(BOSS_MAGE_MOVESET),
+        EnemyClassEnum::BossAlien => Some(BOSS_ALIEN_MOVESET),
+        EnemyClassEnum::Orc | EnemyClassEnum::Mage => None,
+    }
+}
+
+pub(crate) fn get_loot_table_based_on_enemy_class(enemy_class: EnemyClassEnum) -> LootTable {
+    match enemy_class {
+        EnemyClassEnum::Orc => ORC_LOOT_TABLE,
+        EnemyClassEnum::Mage => MAGE_LOOT_TABLE,
+        EnemyClassEnum::BossOrc | EnemyClassEnum::BossMage | EnemyClassEnum::BossAlien => {
+            BOSS_LOOT_TABLE
+        }
+    }
+}
+
+pub(crate) fn get_bullet_pattern_based_on_name(name: &str) -> Option<BulletPattern> {
+    BULLET_PATTERNS
+        .iter()
+        .find(|bullet_pattern| bullet_pattern.name == name)
+        .copied()
+}
+
 pub(crate) fn get_item_based_on_game_level(item_type: ItemTypeEnum, level: u16) -> ItemTypeEnum {
     let multiplier = ITEM_BASE_MULTIPLIER_BASED_ON_LEVEL * level as f32 + 1.0;
 
@@ -146,15 +207,26 @@ pub(crate) fn get_item_based_on_game_level(item_type: ItemTypeEnum, level: u16)
             defensive,
             shield_type,
             duration_seconds,
+            count,
+            radius,
+            orbit_speed,
+            durability,
+            regrow_seconds,
         }) => {
             let new_offensive = offensive * multiplier;
             let new_defensive = defensive * multiplier;
+            let new_durability = durability * multiplier;
 
             ItemTypeEnum::Shield(Shield {
                 offensive: new_offensive,
                 defensive: new_defensive,
                 shield_type,
                 duration_seconds,
+                count,
+                radius,
+                orbit_speed,
+                durability: new_durability,
+                regrow_seconds,
             })
         }
         ItemTypeEnum::Armor(Armor(armor)) => {
@@ -172,9 +244,50 @@ pub(crate) fn get_item_based_on_game_level(item_type: ItemTypeEnum, level: u16)
         ItemTypeEnum::Invisibility(Invisibility { duration_seconds }) => {
             ItemTypeEnum::Invisibility(crate::Invisibility { duration_seconds })
         }
+        ItemTypeEnum::Coins(Coins(coins)) => {
+            let new_coins = (coins as f32 * multiplier).round() as u32;
+            ItemTypeEnum::Coins(crate::Coins(new_coins))
+        }
     }
 }
 
+pub(crate) fn get_obstacle_sprite_based_on_obstacle_kind(
+    obstacle_kind: ObstacleKind,
+    sprites: &Res<SpritesResources>,
+) -> SpriteInfo<'static> {
+    match obstacle_kind {
+        ObstacleKind::Rock => sprites.0.rock.clone(),
+        ObstacleKind::Crate => sprites.0.crate_box.clone(),
+        ObstacleKind::DiggingMachine => sprites.0.digging_machine_back.clone(),
+    }
+}
+
+pub(crate) fn get_prop_sprite_based_on_prop_kind(
+    prop_kind: PropKind,
+    sprites: &Res<SpritesResources>,
+) -> SpriteInfo<'static> {
+    match prop_kind {
+        PropKind::EnergyPack => sprites.0.energy_pack.clone(),
+        PropKind::Boulder => sprites.0.rock.clone(),
+    }
+}
+
+/// What is left of the prop once it breaks.
+pub(crate) fn get_shards_sprite_based_on_prop_kind(
+    prop_kind: PropKind,
+    sprites: &Res<SpritesResources>,
+) -> SpriteInfo<'static> {
+    match prop_kind {
+        PropKind::EnergyPack => sprites.0.glass_shards.clone(),
+        PropKind::Boulder => sprites.0.rock_shards.clone(),
+    }
+}
+
+/// Levels without a layout get a procedural arena.
+pub(crate) fn get_tilemap_layout_based_on_game_level(game_level: u16) -> Option<&'static str> {
+    LEVEL_LAYOUTS.get(game_level as usize - 1).copied()
+}
+
 pub(crate) fn get_background_texture_based_on_game_level(
     game_level: u16,
     sprites: &Res<SpritesResources>,